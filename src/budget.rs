@@ -0,0 +1,120 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! # Example
+//!
+//!     use cbor::decoder::{Decoder, Error};
+//!
+//!     let mut decoder = Decoder::ll();
+//!     decoder.set_budget(Some(64));
+//!     // Array of 100 empty text strings
+//!     let mut bytes = vec![0x98, 100];
+//!     bytes.extend([0x60; 100]);
+//!     let error = decoder.decode(&bytes).unwrap_err();
+//!     assert!(matches!(error.cause(), Error::BudgetExceeded(64)));
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Memory budget shared by the scanner and the parser of a decode
+///
+/// The scanner charges the payload bytes of strings, and the value stacks of
+/// the parsers charge their entries, and the capacity of arrays, maps, and
+/// strings of indefinite length as they grow. Charges are cumulative, so that
+/// the budget bounds the memory allocated rather than the memory in use.
+///
+/// Clones of a budget share the same account. The default budget is
+/// unlimited, and does not count anything.
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    account: Option<Arc<Account>>,
+}
+
+/// Account of a limited budget
+#[derive(Debug)]
+struct Account {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+/// The budget does not cover a charge; the argument holds the limit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Exceeded(pub usize);
+
+impl Budget {
+    /// Returns a budget of `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        let account = Account {
+            limit,
+            used: AtomicUsize::new(0),
+        };
+        Budget {
+            account: Some(Arc::new(account)),
+        }
+    }
+
+    /// Returns the limit of the budget, or [None] if it is unlimited.
+    pub fn limit(&self) -> Option<usize> {
+        self.account.as_ref().map(|account| account.limit)
+    }
+
+    /// Returns the number of bytes charged so far.
+    pub fn used(&self) -> usize {
+        self.account
+            .as_ref()
+            .map_or(0, |account| account.used.load(Ordering::Relaxed))
+    }
+
+    /// Charges `bytes`, and fails if the budget does not cover them.
+    ///
+    /// Once exceeded, the budget remains exceeded until it is reset.
+    pub fn charge(&self, bytes: usize) -> Result<(), Exceeded> {
+        let Some(account) = &self.account else {
+            return Ok(());
+        };
+        let used = account.used.fetch_add(bytes, Ordering::Relaxed);
+        if used.saturating_add(bytes) > account.limit {
+            account.used.store(usize::MAX, Ordering::Relaxed);
+            Err(Exceeded(account.limit))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Charges the memory of `count` elements of type `E`.
+    pub fn charge_elements<E>(&self, count: usize) -> Result<(), Exceeded> {
+        self.charge(count.saturating_mul(std::mem::size_of::<E>()))
+    }
+
+    /// Discards all charges, e.g., before the next decode.
+    pub fn reset(&self) {
+        if let Some(account) = &self.account {
+            account.used.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl fmt::Display for Exceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Memory budget of {} bytes exceeded", self.0)
+    }
+}
+
+impl std::error::Error for Exceeded {}
@@ -0,0 +1,383 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! A cursor navigates the data items of an encoded buffer without decoding
+//! them. Only the items the caller reads are decoded; other items are skipped
+//! token by token.
+//!
+//! # Example
+//!
+//!     use cbor::cursor::Cursor;
+//!     use cbor::value::Value;
+//!
+//!     // [1, {2: "x", 3: [4, 5]}]
+//!     let bytes = [
+//!         0x82, 0x01, 0xa2, 0x02, 0x61, 0x78, 0x03, 0x82, 0x04, 0x05,
+//!     ];
+//!     let mut cursor = Cursor::new(&bytes);
+//!     cursor.enter().unwrap();              // [
+//!     cursor.skip().unwrap();               //   1,
+//!     cursor.enter().unwrap();              //   {
+//!     cursor.skip().unwrap();               //     2:
+//!     cursor.skip().unwrap();               //        "x",
+//!     cursor.skip().unwrap();               //     3:
+//!     cursor.enter().unwrap();              //        [
+//!     cursor.skip().unwrap();               //          4,
+//!     let value = cursor.read_scalar().unwrap();
+//!     assert_eq!(Value::Uint(5), value);
+//!     assert!(!cursor.next_item().unwrap());
+//!     cursor.leave().unwrap();              //        ]
+//!     cursor.leave().unwrap();              //   }
+//!     cursor.leave().unwrap();              // ]
+//!     assert_eq!(bytes.len(), cursor.offset());
+
+use crate::scanner::{self, Scanner};
+use crate::token::{Kind, Token};
+use crate::value::Value;
+
+pub mod error;
+pub use error::Error;
+
+/// Cursor over an encoded buffer
+///
+/// The cursor is positioned in front of a data item, the _current item_, or
+/// at the end of the enclosing data item. Reading, skipping, or entering the
+/// current item moves the cursor to the next item.
+///
+/// At the top level, the buffer is treated as a CBOR sequence (RFC 8742).
+#[derive(Debug)]
+pub struct Cursor<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    frames: Vec<Frame>,
+}
+
+/// Data item entered by the cursor
+#[derive(Clone, Copy, Debug)]
+enum Frame {
+    /// Data item expecting this many more items; map entries count twice.
+    Definite(u128),
+    /// Data item terminated by `%break`
+    Indefinite,
+}
+
+impl<'a> Cursor<'a> {
+    /// Returns a cursor in front of the first data item of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Returns the offset of the cursor into the buffer.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of data items the cursor has entered.
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if there is a current item, and `false` if the cursor
+    /// is at the end of the enclosing data item, or at the end of the buffer.
+    pub fn next_item(&mut self) -> Result<bool, Error> {
+        match self.frames.last() {
+            None => Ok(self.offset < self.bytes.len()),
+            Some(Frame::Definite(count)) => Ok(*count > 0),
+            Some(Frame::Indefinite) => match self.bytes.get(self.offset) {
+                Some(&byte) => Ok(byte != 0xff),
+                None => Err(Error::Incomplete),
+            },
+        }
+    }
+
+    /// Returns the kind of the current item.
+    pub fn kind(&mut self) -> Result<Kind, Error> {
+        if !self.next_item()? {
+            return Err(Error::End);
+        }
+        let (major_type, argument, _) = self.scan_head(self.offset)?;
+        self.head_kind(major_type, argument)
+    }
+
+    /// Enters the current item, which must be an array, a map, or a tag.
+    ///
+    /// The method returns the head of the item: for arrays and maps this
+    /// includes the number of items or entries, for tags the tag number. The
+    /// cursor is positioned in front of the first item of the array or map,
+    /// or in front of the tagged item.
+    pub fn enter(&mut self) -> Result<Token, Error> {
+        if !self.next_item()? {
+            return Err(Error::End);
+        }
+        let (major_type, argument, offset) = self.scan_head(self.offset)?;
+        let (token, frame) = match (major_type, argument) {
+            (4, Some(n)) => (Token::Array(n), Frame::Definite(n.into())),
+            (5, Some(n)) => (Token::Map(n), Frame::Definite(2 * u128::from(n))),
+            (6, Some(tag)) => (Token::Tag(tag), Frame::Definite(1)),
+            (4, None) => (Token::ArrayX, Frame::Indefinite),
+            (5, None) => (Token::MapX, Frame::Indefinite),
+            _ => {
+                let kind = self.head_kind(major_type, argument)?;
+                return Err(Error::NotContainer(kind));
+            }
+        };
+        self.offset = offset;
+        self.advance();
+        self.frames.push(frame);
+        Ok(token)
+    }
+
+    /// Skips the remaining items of the entered data item, and leaves it.
+    pub fn leave(&mut self) -> Result<(), Error> {
+        while self.next_item()? {
+            self.skip()?;
+        }
+        match self.frames.pop() {
+            None => Err(Error::TopLevel),
+            Some(Frame::Definite(_)) => Ok(()),
+            Some(Frame::Indefinite) => {
+                // Consume %break
+                self.offset += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Skips the current item, including all nested items.
+    ///
+    /// The cursor reads the heads of the skipped items only, and steps over
+    /// the payloads of strings without reading them.
+    pub fn skip(&mut self) -> Result<(), Error> {
+        if !self.next_item()? {
+            return Err(Error::End);
+        }
+        let mut pending = vec![Frame::Definite(1)];
+        while let Some(frame) = pending.last_mut() {
+            if let Frame::Definite(0) = frame {
+                pending.pop();
+                continue;
+            }
+            let (major_type, argument) = self.next_head()?;
+            match (frame, major_type, argument) {
+                (Frame::Indefinite, 7, None) => {
+                    pending.pop();
+                    continue;
+                }
+                (_, 7, None) => return Err(Error::UnexpectedBreak),
+                (Frame::Definite(count), _, _) => *count -= 1,
+                (Frame::Indefinite, _, _) => {}
+            }
+            match (major_type, argument) {
+                (2 | 3, Some(length)) => self.skip_payload(length)?,
+                (4, Some(n)) => pending.push(Frame::Definite(n.into())),
+                (5, Some(n)) => {
+                    pending.push(Frame::Definite(2 * u128::from(n)))
+                }
+                (6, _) => pending.push(Frame::Definite(1)),
+                (2..=5, None) => pending.push(Frame::Indefinite),
+                _ => {}
+            }
+        }
+        self.advance();
+        Ok(())
+    }
+
+    /// Reads the current item, which must be a number, a string, or a simple
+    /// value.
+    pub fn read_scalar(&mut self) -> Result<Value, Error> {
+        if !self.next_item()? {
+            return Err(Error::End);
+        }
+        let (token, mut offset) = self.peek_token()?;
+        let value = match token {
+            Token::Uint(n) => Value::Uint(n),
+            Token::Nint(n) => Value::Nint(n),
+            Token::Float(p, n) => Value::Float(p, n),
+            Token::Simple(n) => Value::Simple(n),
+            Token::Bstr(bytes) => Value::Bstr(bytes),
+            Token::Tstr(bytes) => Value::Tstr(String::from_utf8(bytes)?),
+            Token::BstrX => {
+                let chunks = self.scan_chunks(&mut offset, Kind::Bstr)?;
+                Value::Bstr(chunks.concat())
+            }
+            Token::TstrX => {
+                // Each chunk must be valid UTF-8 on its own.
+                let chunks = self.scan_chunks(&mut offset, Kind::Tstr)?;
+                let text = chunks
+                    .into_iter()
+                    .map(String::from_utf8)
+                    .collect::<Result<String, _>>()?;
+                Value::Tstr(text)
+            }
+            token => return Err(Error::NotScalar(token.kind())),
+        };
+        self.offset = offset;
+        self.advance();
+        Ok(value)
+    }
+
+    /// Returns the kind of the current item from the major type and the
+    /// argument of its head.
+    fn head_kind(
+        &self,
+        major_type: u8,
+        argument: Option<u64>,
+    ) -> Result<Kind, Error> {
+        let kind = match (major_type, argument) {
+            (0, _) => Kind::Uint,
+            (1, _) => Kind::Nint,
+            (2, Some(_)) => Kind::Bstr,
+            (2, None) => Kind::BstrX,
+            (3, Some(_)) => Kind::Tstr,
+            (3, None) => Kind::TstrX,
+            (4, Some(_)) => Kind::Array,
+            (4, None) => Kind::ArrayX,
+            (5, Some(_)) => Kind::Map,
+            (5, None) => Kind::MapX,
+            (6, _) => Kind::Tag,
+            (_, None) => return Err(Error::UnexpectedBreak),
+            _ => match self.bytes[self.offset] & 0x1f {
+                25..=27 => Kind::Float,
+                _ => Kind::Simple,
+            },
+        };
+        Ok(kind)
+    }
+
+    /// Records that the current item has been consumed.
+    fn advance(&mut self) {
+        if let Some(Frame::Definite(count)) = self.frames.last_mut() {
+            *count -= 1;
+        }
+    }
+
+    /// Consumes the next head, and returns its major type and argument.
+    fn next_head(&mut self) -> Result<(u8, Option<u64>), Error> {
+        let (major_type, argument, offset) = self.scan_head(self.offset)?;
+        self.offset = offset;
+        Ok((major_type, argument))
+    }
+
+    /// Scans the head at `offset`, and returns its major type and argument
+    /// with the offset past it; the argument is `None` for indefinite-length
+    /// items and `%break`.
+    fn scan_head(
+        &self,
+        offset: usize,
+    ) -> Result<(u8, Option<u64>, usize), Error> {
+        let Some(&initial_byte) = self.bytes.get(offset) else {
+            return Err(Error::Incomplete);
+        };
+        let major_type = initial_byte >> 5;
+        let width = match initial_byte & 0x1f {
+            info @ 0..=23 => {
+                return Ok((major_type, Some(info.into()), offset + 1));
+            }
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            31 if matches!(major_type, 2..=5 | 7) => {
+                return Ok((major_type, None, offset + 1));
+            }
+            _ => {
+                let error = scanner::Error::UnexpectedHead(initial_byte);
+                return Err(error.into());
+            }
+        };
+        let start = offset + 1;
+        let Some(bytes) = self.bytes.get(start..start + width) else {
+            return Err(Error::Incomplete);
+        };
+        let argument = bytes
+            .iter()
+            .fold(0, |argument, byte| argument << 8 | u64::from(*byte));
+        Ok((major_type, Some(argument), start + width))
+    }
+
+    /// Steps over a string payload of `length` bytes.
+    fn skip_payload(&mut self, length: u64) -> Result<(), Error> {
+        self.offset = self.payload_end(self.offset, length)?;
+        Ok(())
+    }
+
+    /// Returns the offset past a string payload of `length` bytes at
+    /// `start`, if the buffer holds the payload.
+    fn payload_end(&self, start: usize, length: u64) -> Result<usize, Error> {
+        let Ok(length) = usize::try_from(length) else {
+            return Err(scanner::Error::Excessive(length).into());
+        };
+        match start.checked_add(length) {
+            Some(end) if end <= self.bytes.len() => Ok(end),
+            _ => Err(Error::Incomplete),
+        }
+    }
+
+    /// Returns the token of the current item, and the offset past it.
+    fn peek_token(&self) -> Result<(Token, usize), Error> {
+        match self.scan_token(self.offset)? {
+            (Token::Break, _) => Err(Error::UnexpectedBreak),
+            result => Ok(result),
+        }
+    }
+
+    /// Scans the chunks of an indefinite-length string at `offset` up to and
+    /// including `%break`, and advances `offset` past them.
+    fn scan_chunks(
+        &self,
+        offset: &mut usize,
+        kind: Kind,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let mut chunks = Vec::new();
+        loop {
+            let (chunk, next) = self.scan_token(*offset)?;
+            *offset = next;
+            match chunk {
+                Token::Break => return Ok(chunks),
+                Token::Bstr(bytes) if kind == Kind::Bstr => chunks.push(bytes),
+                Token::Tstr(bytes) if kind == Kind::Tstr => chunks.push(bytes),
+                chunk => return Err(Error::UnexpectedChunk(chunk.kind())),
+            }
+        }
+    }
+
+    /// Scans a token at `offset`, and returns it with the offset past it.
+    fn scan_token(&self, offset: usize) -> Result<(Token, usize), Error> {
+        // The scanner allocates the payload of a string as declared, so make
+        // sure the buffer holds it.
+        if let (2 | 3, Some(length), start) = self.scan_head(offset)? {
+            self.payload_end(start, length)?;
+        }
+        let Some(bytes) = self.bytes.get(offset..) else {
+            return Err(Error::Incomplete);
+        };
+        let mut scanner = Scanner::default();
+        let mut iter = bytes.iter();
+        match scanner.consume_until_complete(&mut iter)? {
+            Some(token) => Ok((token, self.bytes.len() - iter.len())),
+            None => Err(Error::Incomplete),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,88 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::scanner;
+use crate::token::Kind;
+
+/// What could possibly go wrong when navigating encoded data?
+#[derive(Debug)]
+pub enum Error {
+    /// The buffer ends within a data item.
+    Incomplete,
+    /// There is no current item; the cursor is at the end of the enclosing
+    /// data item, or at the end of the buffer.
+    End,
+    /// The cursor can not enter an item of this kind.
+    NotContainer(Kind),
+    /// The cursor can not read an item of this kind as a scalar.
+    NotScalar(Kind),
+    /// An indefinite-length string contains a chunk of this kind.
+    UnexpectedChunk(Kind),
+    /// A text string is not valid UTF-8.
+    InvalidUtf8(std::str::Utf8Error),
+    /// The cursor encountered `%break` outside of an indefinite-length item.
+    UnexpectedBreak,
+    /// The cursor can not leave the top level.
+    TopLevel,
+    /// Error propagated from the scanner.
+    Scanner(scanner::Error),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Scanner(error) => Some(error),
+            Error::InvalidUtf8(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Error::*;
+        match self {
+            Incomplete => write!(f, "Unexpected end of buffer"),
+            End => write!(f, "No current item"),
+            NotContainer(kind) => write!(f, "Can not enter {kind}"),
+            NotScalar(kind) => write!(f, "Can not read {kind} as a scalar"),
+            UnexpectedChunk(kind) => {
+                write!(f, "Unexpected string chunk {kind}")
+            }
+            InvalidUtf8(error) => {
+                write!(f, "Invalid UTF-8 in text string: {error}")
+            }
+            UnexpectedBreak => write!(f, "Unexpected %break"),
+            TopLevel => write!(f, "Can not leave the top level"),
+            Scanner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<scanner::Error> for Error {
+    fn from(error: scanner::Error) -> Self {
+        Error::Scanner(error)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(error: std::string::FromUtf8Error) -> Self {
+        Error::InvalidUtf8(error.utf8_error())
+    }
+}
@@ -0,0 +1,211 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+
+/// `[1, {"a": h'0102', "b": [_ 2, 3]}, 24(-1), (_ "x", "yz")]`, followed by
+/// `(_ )`
+const BYTES: [u8; 26] = [
+    0x84, // array(4)
+    0x01, // 1
+    0xa2, // map(2)
+    0x61, 0x61, // "a"
+    0x42, 0x01, 0x02, // h'0102'
+    0x61, 0x62, // "b"
+    0x9f, 0x02, 0x03, 0xff, // [_ 2, 3]
+    0xd8, 0x18, 0x20, // 24(-1)
+    0x7f, 0x61, 0x78, 0x62, 0x79, 0x7a, 0xff, // (_ "x", "yz")
+    0x5f, 0xff, // (_ )
+];
+
+#[test]
+fn navigate() {
+    let mut cursor = Cursor::new(&BYTES[..24]);
+    assert_eq!(Token::Array(4), cursor.enter().unwrap());
+    assert_eq!(Value::Uint(1), cursor.read_scalar().unwrap());
+    assert_eq!(Kind::Map, cursor.kind().unwrap());
+    assert_eq!(Token::Map(2), cursor.enter().unwrap());
+    assert_eq!(Value::Tstr("a".into()), cursor.read_scalar().unwrap());
+    assert_eq!(Value::Bstr(vec![1, 2]), cursor.read_scalar().unwrap());
+    cursor.skip().unwrap();
+    assert_eq!(Token::ArrayX, cursor.enter().unwrap());
+    assert!(cursor.next_item().unwrap());
+    assert_eq!(Value::Uint(2), cursor.read_scalar().unwrap());
+    assert_eq!(Value::Uint(3), cursor.read_scalar().unwrap());
+    assert!(!cursor.next_item().unwrap());
+    cursor.leave().unwrap();
+    assert!(!cursor.next_item().unwrap());
+    cursor.leave().unwrap();
+    assert_eq!(Token::Tag(24), cursor.enter().unwrap());
+    assert_eq!(Value::Nint(0), cursor.read_scalar().unwrap());
+    cursor.leave().unwrap();
+    assert_eq!(Value::Tstr("xyz".into()), cursor.read_scalar().unwrap());
+    assert!(!cursor.next_item().unwrap());
+    cursor.leave().unwrap();
+    assert_eq!(0, cursor.depth());
+    assert!(!cursor.next_item().unwrap());
+    assert_eq!(24, cursor.offset());
+}
+
+#[test]
+fn skip_whole_tree() {
+    let mut cursor = Cursor::new(&BYTES);
+    cursor.skip().unwrap();
+    assert_eq!(24, cursor.offset());
+    assert_eq!(Value::Bstr(vec![]), cursor.read_scalar().unwrap());
+    assert!(!cursor.next_item().unwrap());
+    assert!(matches!(cursor.skip(), Err(Error::End)));
+}
+
+#[test]
+fn leave_skips_remaining_items() {
+    let mut cursor = Cursor::new(&BYTES);
+    cursor.enter().unwrap();
+    cursor.leave().unwrap();
+    assert_eq!(24, cursor.offset());
+}
+
+#[test]
+fn leave_top_level() {
+    let mut cursor = Cursor::new(&[]);
+    assert!(matches!(cursor.leave(), Err(Error::TopLevel)));
+}
+
+#[test]
+fn enter_scalar() {
+    let mut cursor = Cursor::new(&BYTES[1..]);
+    let result = cursor.enter();
+    assert!(matches!(result, Err(Error::NotContainer(Kind::Uint))));
+    assert_eq!(0, cursor.offset());
+}
+
+#[test]
+fn enter_declared_payload() {
+    // Byte string that claims to hold 2^44 bytes
+    let bytes = [0x5b, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cursor = Cursor::new(&bytes);
+    let result = cursor.enter();
+    assert!(matches!(result, Err(Error::NotContainer(Kind::Bstr))));
+    assert!(matches!(cursor.read_scalar(), Err(Error::Incomplete)));
+    assert_eq!(0, cursor.offset());
+    // The same as a chunk of an indefinite-length byte string
+    let bytes = [0x5f, 0x5b, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cursor = Cursor::new(&bytes);
+    assert!(matches!(cursor.read_scalar(), Err(Error::Incomplete)));
+}
+
+#[test]
+fn read_container() {
+    let mut cursor = Cursor::new(&BYTES);
+    let result = cursor.read_scalar();
+    assert!(matches!(result, Err(Error::NotScalar(Kind::Array))));
+    assert_eq!(0, cursor.offset());
+}
+
+#[test]
+fn read_past_end() {
+    let mut cursor = Cursor::new(&BYTES[10..14]);
+    cursor.enter().unwrap();
+    cursor.skip().unwrap();
+    cursor.skip().unwrap();
+    assert!(matches!(cursor.read_scalar(), Err(Error::End)));
+    assert!(matches!(cursor.skip(), Err(Error::End)));
+}
+
+#[test]
+fn incomplete() {
+    let mut cursor = Cursor::new(&BYTES[..12]);
+    assert!(matches!(cursor.skip(), Err(Error::Incomplete)));
+    let mut cursor = Cursor::new(&BYTES[..6]);
+    cursor.enter().unwrap();
+    cursor.skip().unwrap();
+    cursor.enter().unwrap();
+    cursor.skip().unwrap();
+    assert!(matches!(cursor.read_scalar(), Err(Error::Incomplete)));
+}
+
+#[test]
+fn unexpected_chunk() {
+    let mut cursor = Cursor::new(&[0x5f, 0x60, 0xff]);
+    let result = cursor.read_scalar();
+    assert!(matches!(result, Err(Error::UnexpectedChunk(Kind::Tstr))));
+}
+
+#[test]
+fn unexpected_break() {
+    let mut cursor = Cursor::new(&[0x81, 0xff]);
+    assert!(matches!(cursor.skip(), Err(Error::UnexpectedBreak)));
+}
+
+#[test]
+fn stray_break() {
+    let mut cursor = Cursor::new(&[0xff]);
+    assert!(matches!(cursor.kind(), Err(Error::UnexpectedBreak)));
+    assert!(matches!(cursor.enter(), Err(Error::UnexpectedBreak)));
+    assert!(matches!(cursor.read_scalar(), Err(Error::UnexpectedBreak)));
+    assert!(matches!(cursor.skip(), Err(Error::UnexpectedBreak)));
+    let mut cursor = Cursor::new(&[0x81, 0xff]);
+    cursor.enter().unwrap();
+    assert!(matches!(cursor.kind(), Err(Error::UnexpectedBreak)));
+}
+
+#[test]
+fn read_tstrx_split_code_point() {
+    // (_ "A\xc3", "\xa9") is invalid, although its concatenation is valid
+    let mut cursor = Cursor::new(&[0x7f, 0x62, 0x41, 0xc3, 0x61, 0xa9, 0xff]);
+    let result = cursor.read_scalar();
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+    assert_eq!(0, cursor.offset());
+}
+
+#[test]
+fn exhausted_nested_frame() {
+    // [[], [2]]
+    let bytes = [0x82, 0x80, 0x81, 0x02];
+    let mut cursor = Cursor::new(&bytes);
+    assert_eq!(Token::Array(2), cursor.enter().unwrap());
+    assert_eq!(Token::Array(0), cursor.enter().unwrap());
+    assert!(matches!(cursor.kind(), Err(Error::End)));
+    assert!(matches!(cursor.enter(), Err(Error::End)));
+    assert!(matches!(cursor.enter(), Err(Error::End)));
+    cursor.leave().unwrap();
+    assert_eq!(Kind::Array, cursor.kind().unwrap());
+    assert_eq!(Token::Array(1), cursor.enter().unwrap());
+    assert_eq!(Value::Uint(2), cursor.read_scalar().unwrap());
+    assert!(matches!(cursor.kind(), Err(Error::End)));
+}
+
+#[test]
+fn skip_payload() {
+    // [h'0102', "abc", 2]
+    let bytes = [0x83, 0x42, 0x01, 0x02, 0x63, 0x61, 0x62, 0x63, 0x02];
+    let mut cursor = Cursor::new(&bytes);
+    cursor.enter().unwrap();
+    cursor.skip().unwrap();
+    cursor.skip().unwrap();
+    assert_eq!(Value::Uint(2), cursor.read_scalar().unwrap());
+    // A byte string declaring 4 GiB of payload is not allocated.
+    let bytes = [0x5a, 0xff, 0xff, 0xff, 0xff, 0x00];
+    let mut cursor = Cursor::new(&bytes);
+    assert_eq!(Kind::Bstr, cursor.kind().unwrap());
+    assert!(matches!(cursor.skip(), Err(Error::Incomplete)));
+    // Reserved additional information
+    let mut cursor = Cursor::new(&[0x1c]);
+    assert!(matches!(cursor.skip(), Err(Error::Scanner(_))));
+}
@@ -0,0 +1,260 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! # Example
+//!
+//!     use cbor::decoder::Decoder;
+//!     use cbor::value::Value;
+//!
+//!     let mut decoder = Decoder::ll();
+//!     let bytes = [0x82, 0x01, 0x20, 0x17];
+//!
+//!     let (value, count) = decoder.decode(&bytes).unwrap();
+//!     assert_eq!(Value::Array(vec![Value::Uint(1), Value::Nint(0)]), value);
+//!     assert_eq!(3, count);
+//!
+//!     let (value, count) = decoder.decode(&bytes[count..]).unwrap();
+//!     assert_eq!(Value::Uint(0x17), value);
+//!     assert_eq!(1, count);
+
+use std::io::{ErrorKind, Read};
+
+use crate::budget::Budget;
+use crate::parser::{self, Parser, ll, lr};
+use crate::scanner::{self, Scanner};
+
+pub mod error;
+pub use error::Error;
+
+/// Deterministic encoding requirements
+pub mod deterministic;
+use deterministic::Checker;
+pub use deterministic::Violation;
+
+/// CBOR decoder
+///
+/// The decoder feeds bytes through a [Scanner] into a [Parser], and produces
+/// CBOR values. Its parser runs in sequence mode, so that the decoder produces
+/// one value per data item of a CBOR sequence.
+///
+/// The decoder retains any partially decoded data item between calls. In the
+/// case of an error, the safe thing would be to call [Decoder::reset()] before
+/// decoding any further input.
+#[derive(Debug, Default)]
+pub struct Decoder<P> {
+    scanner: Scanner,
+    parser: P,
+    pending: bool,
+    checker: Option<Checker>,
+    budget: Budget,
+}
+
+impl Decoder<ll::Parser> {
+    /// Returns a decoder based on the LL(1) parser.
+    pub fn ll() -> Self {
+        Self::new(ll::Parser::sequence())
+    }
+}
+
+impl Decoder<lr::Parser> {
+    /// Returns a decoder based on the LR(1) parser.
+    pub fn lr() -> Self {
+        Self::new(lr::Parser::sequence())
+    }
+}
+
+impl<P: Parser> Decoder<P> {
+    /// Returns a decoder based on `parser`.
+    pub fn new(parser: P) -> Self {
+        Self {
+            scanner: Scanner::default(),
+            parser,
+            pending: false,
+            checker: None,
+            budget: Budget::default(),
+        }
+    }
+
+    /// Enables or disables the deterministic mode of the decoder.
+    ///
+    /// In deterministic mode, the decoder rejects input that violates the core
+    /// deterministic encoding requirements of RFC 8949, section 4.2.1, with
+    /// [Error::NotDeterministic]. Use this mode, e.g., when verifying
+    /// signatures.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.checker = deterministic.then(Checker::default);
+    }
+
+    /// Sets the memory budget per value in bytes, or lifts it.
+    ///
+    /// The budget covers the payload bytes of strings, the entries of the
+    /// value stack of the parser, and the capacity of arrays and maps; see
+    /// [Budget]. Once a value exceeds the budget, the decoder fails with
+    /// [Error::BudgetExceeded].
+    pub fn set_budget(&mut self, limit: Option<usize>) {
+        self.budget = limit.map(Budget::new).unwrap_or_default();
+        self.scanner.set_budget(self.budget.clone());
+        self.parser.set_budget(self.budget.clone());
+    }
+
+    /// Consumes a byte, maybe produces a CBOR value.
+    ///
+    /// The method returns
+    /// - `Ok(Some(value))` if the decoder has completed a CBOR `value`; or
+    /// - `Ok(None)` if the decoder needs more bytes to complete a value; or
+    /// - `Err(error)` if something went wrong.
+    ///
+    /// Errors of the scanner and the parser come with the offset of the head
+    /// of the offending data item; see [Error::offset()].
+    pub fn push(&mut self, byte: u8) -> Result<Option<P::Output>, Error> {
+        if !self.pending {
+            // Each value has a budget of its own.
+            self.budget.reset();
+        }
+        self.pending = true;
+        self.consume(byte).map_err(|error| match error {
+            Error::Scanner(_) | Error::Parser(_) | Error::BudgetExceeded(_) => {
+                Error::At(self.scanner.head().offset, Box::new(error))
+            }
+            error => error,
+        })
+    }
+
+    /// Feeds a byte through the scanner, the checker, and the parser.
+    fn consume(&mut self, byte: u8) -> Result<Option<P::Output>, Error> {
+        if let Some(checker) = &mut self.checker {
+            checker.byte(self.scanner.offset(), byte);
+        }
+        let Some(token) = self.scanner.consume(byte)? else {
+            return Ok(None);
+        };
+        if let Some(checker) = &mut self.checker {
+            checker.token(self.scanner.head(), &token)?;
+        }
+        let value = self.parser.consume(token)?;
+        if value.is_some() {
+            self.pending = false;
+        }
+        Ok(value)
+    }
+
+    /// Decodes the first CBOR value from `bytes`.
+    ///
+    /// The method returns the value together with the number of bytes it has
+    /// consumed, or [Error::Incomplete] if `bytes` end before the value is
+    /// complete. In the latter case, decoding continues with the next call.
+    pub fn decode(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<(P::Output, usize), Error> {
+        for (index, byte) in bytes.iter().enumerate() {
+            if let Some(value) = self.push(*byte)? {
+                return Ok((value, index + 1));
+            }
+        }
+        Err(Error::Incomplete)
+    }
+
+    /// Decodes the next CBOR value from `reader`.
+    ///
+    /// The decoder reads byte by byte, and never reads past the end of the
+    /// value. Consider wrapping unbuffered readers into a
+    /// [BufReader](std::io::BufReader).
+    ///
+    /// The method returns [Error::Eof] if `reader` ends before the first byte
+    /// of a value, and [Error::Incomplete] if it ends within a value.
+    pub fn from_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+    ) -> Result<P::Output, Error> {
+        let mut buffer = [0u8; 1];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if let Some(value) = self.push(buffer[0])? {
+                        return Ok(value);
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        if self.pending {
+            Err(Error::Incomplete)
+        } else {
+            Err(Error::Eof)
+        }
+    }
+
+    /// Resets the decoder, and discards any partially decoded value.
+    pub fn reset(&mut self) {
+        self.scanner.reset();
+        self.parser.reset();
+        self.pending = false;
+        self.budget.reset();
+        if let Some(checker) = &mut self.checker {
+            checker.reset();
+        }
+    }
+
+    /// Returns the number of bytes consumed by the decoder.
+    ///
+    /// The count includes the bytes consumed before any
+    /// [reset](Decoder::reset). After an error, the offending byte is the
+    /// last one consumed.
+    pub fn offset(&self) -> u64 {
+        self.scanner.offset()
+    }
+
+    /// Returns the underlying parser.
+    pub fn into_parser(self) -> P {
+        self.parser
+    }
+}
+
+impl From<scanner::Error> for Error {
+    fn from(error: scanner::Error) -> Self {
+        match error {
+            scanner::Error::BudgetExceeded(limit) => {
+                Error::BudgetExceeded(limit)
+            }
+            error => Error::Scanner(error),
+        }
+    }
+}
+
+impl From<parser::Error> for Error {
+    fn from(error: parser::Error) -> Self {
+        match error.cause() {
+            parser::Error::BudgetExceeded(limit) => {
+                Error::BudgetExceeded(*limit)
+            }
+            _ => Error::Parser(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests;
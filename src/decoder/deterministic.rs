@@ -0,0 +1,219 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! Core deterministic encoding requirements of RFC 8949, section 4.2.1:
+//! - arguments are encoded as short as possible;
+//! - floating-point numbers use the shortest width that preserves their value;
+//! - indefinite-length items are not used;
+//! - map keys are sorted in the bytewise lexicographic order of their
+//!   encodings.
+
+use std::fmt;
+
+use super::Error;
+use crate::scanner::Head;
+use crate::token::{Precision, Token};
+use crate::value::f64_to_half;
+
+/// Violation of the deterministic encoding requirements
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Violation {
+    /// An argument is not encoded in the shortest form.
+    NonShortestArgument,
+    /// A floating-point number is not encoded in the shortest width that
+    /// preserves its value.
+    NonPreferredFloat,
+    /// A string, array, or map has indefinite length.
+    IndefiniteLength,
+    /// A map key is not greater than the preceding key of the same map.
+    UnsortedKeys,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::NonShortestArgument => {
+                write!(f, "non-shortest argument")
+            }
+            Violation::NonPreferredFloat => {
+                write!(f, "non-preferred floating-point number")
+            }
+            Violation::IndefiniteLength => write!(f, "indefinite length"),
+            Violation::UnsortedKeys => write!(f, "unsorted map keys"),
+        }
+    }
+}
+
+/// Checks bytes and tokens for violations of the deterministic encoding
+/// requirements.
+///
+/// Since indefinite-length items are rejected, the checker only needs to
+/// keep track of definite-length arrays and maps, and of tags.
+#[derive(Debug, Default)]
+pub(super) struct Checker {
+    frames: Vec<Frame>,
+}
+
+/// Open data item
+#[derive(Debug)]
+enum Frame {
+    /// Array expecting this many more items
+    Array(u64),
+    /// Map expecting this many more entries
+    Map {
+        remaining: u64,
+        /// The key being read, if the map expects a key
+        key: Option<Key>,
+        /// Encoding of the preceding key
+        last: Option<Vec<u8>>,
+    },
+    /// Tag expecting the tagged item
+    Tag,
+}
+
+/// Encoding of a map key
+#[derive(Debug, Default)]
+struct Key {
+    offset: u64,
+    bytes: Vec<u8>,
+}
+
+impl Checker {
+    /// Discards all open data items.
+    pub fn reset(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Records the `byte` at `offset` as part of all keys being read.
+    pub fn byte(&mut self, offset: u64, byte: u8) {
+        for frame in self.frames.iter_mut() {
+            if let Frame::Map { key: Some(key), .. } = frame {
+                if key.bytes.is_empty() {
+                    key.offset = offset;
+                }
+                key.bytes.push(byte);
+            }
+        }
+    }
+
+    /// Checks the `token` encoded with `head`.
+    pub fn token(&mut self, head: Head, token: &Token) -> Result<(), Error> {
+        let violation =
+            |violation| Error::NotDeterministic(head.offset, violation);
+        let argument = match token {
+            Token::Uint(n)
+            | Token::Nint(n)
+            | Token::Array(n)
+            | Token::Map(n)
+            | Token::Tag(n) => *n,
+            Token::Bstr(bytes) | Token::Tstr(bytes) => bytes.len() as u64,
+            Token::Simple(n) => u64::from(*n),
+            Token::Float(precision, bits) => {
+                if !is_preferred_float(*precision, *bits) {
+                    return Err(violation(Violation::NonPreferredFloat));
+                }
+                0
+            }
+            Token::BstrX
+            | Token::TstrX
+            | Token::ArrayX
+            | Token::MapX
+            | Token::Break => {
+                return Err(violation(Violation::IndefiniteLength));
+            }
+        };
+        if !matches!(token, Token::Float(..))
+            && head.argument_width() != Some(shortest_width(argument))
+        {
+            return Err(violation(Violation::NonShortestArgument));
+        }
+        match token {
+            Token::Array(n) if *n > 0 => self.frames.push(Frame::Array(*n)),
+            Token::Map(n) if *n > 0 => self.frames.push(Frame::Map {
+                remaining: *n,
+                key: Some(Key::default()),
+                last: None,
+            }),
+            Token::Tag(_) => self.frames.push(Frame::Tag),
+            _ => self.complete()?,
+        }
+        Ok(())
+    }
+
+    /// Records the completion of a value, and closes all data items completed
+    /// by it.
+    fn complete(&mut self) -> Result<(), Error> {
+        loop {
+            match self.frames.last_mut() {
+                None => return Ok(()),
+                Some(Frame::Tag) => {}
+                Some(Frame::Array(n)) if *n > 1 => {
+                    *n -= 1;
+                    return Ok(());
+                }
+                Some(Frame::Array(_)) => {}
+                Some(Frame::Map { key, last, .. }) if key.is_some() => {
+                    let key = key.take().unwrap_or_default();
+                    if last.as_ref().is_some_and(|last| *last >= key.bytes) {
+                        return Err(Error::NotDeterministic(
+                            key.offset,
+                            Violation::UnsortedKeys,
+                        ));
+                    }
+                    *last = Some(key.bytes);
+                    return Ok(());
+                }
+                Some(Frame::Map { remaining, key, .. }) if *remaining > 1 => {
+                    *remaining -= 1;
+                    *key = Some(Key::default());
+                    return Ok(());
+                }
+                Some(Frame::Map { .. }) => {}
+            }
+            self.frames.pop();
+        }
+    }
+}
+
+/// Returns the number of bytes of the shortest encoding of `argument`.
+fn shortest_width(argument: u64) -> usize {
+    match argument {
+        0..=23 => 0,
+        24..=0xff => 1,
+        0x100..=0xffff => 2,
+        0x1_0000..=0xffff_ffff => 4,
+        _ => 8,
+    }
+}
+
+/// Returns `true` if no shorter encoding preserves the value of the
+/// floating-point number.
+fn is_preferred_float(precision: Precision, bits: u64) -> bool {
+    match precision {
+        Precision::Half => true,
+        Precision::Single => {
+            let value = f64::from(f32::from_bits(bits as u32));
+            f64_to_half(value).is_none()
+        }
+        Precision::Double => {
+            let value = f64::from_bits(bits);
+            let single = f64::from(value as f32);
+            single.to_bits() != bits && f64_to_half(value).is_none()
+        }
+    }
+}
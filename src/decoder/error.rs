@@ -0,0 +1,97 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::Violation;
+use crate::parser;
+use crate::scanner;
+
+/// What could possibly go wrong with decoding?
+#[derive(Debug)]
+pub enum Error {
+    /// The input ended before the first byte of a value.
+    Eof,
+    /// The input ended before the value was complete.
+    Incomplete,
+    /// The input violates the deterministic encoding requirements; the
+    /// argument holds the offset of the offending item.
+    NotDeterministic(u64, Violation),
+    /// Decoding the value would exceed the memory budget of this many bytes.
+    BudgetExceeded(usize),
+    /// Error propagated from the scanner.
+    Scanner(scanner::Error),
+    /// Error propagated from the parser.
+    Parser(parser::Error),
+    /// Error propagated from the reader.
+    Io(std::io::Error),
+    /// The error occurred in the data item whose head starts at this byte
+    /// offset into the input.
+    At(u64, Box<Error>),
+}
+
+impl Error {
+    /// Returns the byte offset into the input where the error occurred, if
+    /// known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Error::At(offset, _) | Error::NotDeterministic(offset, _) => {
+                Some(*offset)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the error without its offset.
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::At(_, error) => error,
+            error => error,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Scanner(error) => Some(error),
+            Error::Parser(error) => Some(error),
+            Error::Io(error) => Some(error),
+            Error::At(_, error) => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Eof => write!(f, "End of input"),
+            Error::Incomplete => write!(f, "Unexpected end of input"),
+            Error::NotDeterministic(offset, violation) => {
+                write!(f, "Not deterministic at offset {offset}: {violation}")
+            }
+            Error::BudgetExceeded(limit) => {
+                write!(f, "Memory budget of {limit} bytes exceeded")
+            }
+            Error::Scanner(error) => write!(f, "{error}"),
+            Error::Parser(error) => write!(f, "{error}"),
+            Error::Io(error) => write!(f, "{error}"),
+            Error::At(offset, error) => write!(f, "{error} at offset {offset}"),
+        }
+    }
+}
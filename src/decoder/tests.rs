@@ -0,0 +1,284 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use crate::value::Value;
+
+const BYTES: [u8; 22] = [
+    0x9f, 0x17, 0x18, 0x01, 0x19, 0x01, 0x02, 0x1a, 0x01, 0x02, 0x03, 0x04,
+    0x1b, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0xff,
+];
+
+fn expected() -> Value {
+    Value::Array(vec![
+        Value::Uint(0x17),
+        Value::Uint(0x01),
+        Value::Uint(0x0102),
+        Value::Uint(0x01020304),
+        Value::Uint(0x0102030405060708),
+    ])
+}
+
+#[test]
+fn decode_ll() {
+    let mut decoder = Decoder::ll();
+    let (value, count) = decoder.decode(&BYTES).unwrap();
+    assert_eq!(expected(), value);
+    assert_eq!(BYTES.len(), count);
+}
+
+#[test]
+fn decode_lr() {
+    let mut decoder = Decoder::lr();
+    let (value, count) = decoder.decode(&BYTES).unwrap();
+    assert_eq!(expected(), value);
+    assert_eq!(BYTES.len(), count);
+}
+
+#[test]
+fn decode_sequence() {
+    let mut decoder = Decoder::ll();
+    let bytes = [0x01, 0x20, 0x41, 0xaa];
+    let (value, count) = decoder.decode(&bytes).unwrap();
+    assert_eq!((Value::Uint(1), 1), (value, count));
+    let (value, count) = decoder.decode(&bytes[1..]).unwrap();
+    assert_eq!((Value::Nint(0), 1), (value, count));
+    let (value, count) = decoder.decode(&bytes[2..]).unwrap();
+    assert_eq!((Value::Bstr(vec![0xaa]), 2), (value, count));
+}
+
+#[test]
+fn decode_incomplete() {
+    let mut decoder = Decoder::lr();
+    let result = decoder.decode(&BYTES[..10]);
+    assert!(matches!(result, Err(Error::Incomplete)));
+    let (value, count) = decoder.decode(&BYTES[10..]).unwrap();
+    assert_eq!(expected(), value);
+    assert_eq!(BYTES.len() - 10, count);
+}
+
+#[test]
+fn decode_scanner_error() {
+    let mut decoder = Decoder::ll();
+    assert_eq!(1, decoder.decode(&[0x01, 0x1c]).unwrap().1);
+    let error = decoder.decode(&[0x1c]).unwrap_err();
+    assert!(matches!(error.cause(), Error::Scanner(_)));
+    assert_eq!(Some(1), error.offset());
+}
+
+#[test]
+fn decode_parser_error() {
+    let mut decoder = Decoder::ll();
+    let error = decoder.decode(&[0xff]).unwrap_err();
+    assert!(matches!(error.cause(), Error::Parser(_)));
+    assert_eq!(Some(0), error.offset());
+    // Text string of two bytes that are not valid UTF-8
+    let mut decoder = Decoder::lr();
+    let error = decoder.decode(&[0x81, 0x62, 0xff, 0xff]).unwrap_err();
+    assert!(matches!(error.cause(), Error::Parser(_)));
+    assert_eq!(Some(1), error.offset());
+    assert_eq!(4, decoder.offset());
+}
+
+#[test]
+fn push() {
+    let mut decoder = Decoder::lr();
+    let mut values = Vec::new();
+    for byte in BYTES.iter().chain(&BYTES) {
+        if let Some(value) = decoder.push(*byte).unwrap() {
+            values.push(value);
+        }
+    }
+    assert_eq!(vec![expected(), expected()], values);
+}
+
+#[test]
+fn from_reader() {
+    let mut decoder = Decoder::ll();
+    let mut reader = &BYTES[..];
+    assert_eq!(expected(), decoder.from_reader(&mut reader).unwrap());
+    assert!(matches!(decoder.from_reader(&mut reader), Err(Error::Eof)));
+}
+
+#[test]
+fn from_reader_incomplete() {
+    let mut decoder = Decoder::ll();
+    let result = decoder.from_reader(&BYTES[..5]);
+    assert!(matches!(result, Err(Error::Incomplete)));
+}
+
+#[test]
+fn reset() {
+    let mut decoder = Decoder::ll();
+    assert!(decoder.decode(&BYTES[..5]).is_err());
+    decoder.reset();
+    let (value, _) = decoder.decode(&BYTES).unwrap();
+    assert_eq!(expected(), value);
+}
+
+fn decode_deterministic(bytes: &[u8]) -> Result<(Value, usize), Error> {
+    let mut decoder = Decoder::ll();
+    decoder.set_deterministic(true);
+    decoder.decode(bytes)
+}
+
+fn assert_violation(bytes: &[u8], offset: u64, violation: Violation) {
+    match decode_deterministic(bytes) {
+        Err(Error::NotDeterministic(o, v)) => {
+            assert_eq!((offset, violation), (o, v), "{bytes:02x?}")
+        }
+        other => panic!("{bytes:02x?}: unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn deterministic_valid() {
+    let valid: [&[u8]; 8] = [
+        &[0x17],
+        &[0x18, 0x18],
+        &[0x39, 0x01, 0x00],
+        &[0x82, 0x41, 0x00, 0x60],
+        // {10: 1, 100: 2, -1: 3, "z": 4, "aa": 5, [100]: 6, [-1]: 7, false: 8}
+        &[
+            0xa8, 0x0a, 0x01, 0x18, 0x64, 0x02, 0x20, 0x03, 0x61, 0x7a, 0x04,
+            0x62, 0x61, 0x61, 0x05, 0x81, 0x18, 0x64, 0x06, 0x81, 0x20, 0x07,
+            0xf4, 0x08,
+        ],
+        &[0xf9, 0x3c, 0x00],
+        &[0xfa, 0x47, 0xc3, 0x50, 0x00],
+        &[0xfb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a],
+    ];
+    for bytes in valid {
+        let (_, count) = decode_deterministic(bytes).unwrap();
+        assert_eq!(bytes.len(), count);
+    }
+}
+
+#[test]
+fn deterministic_non_shortest_argument() {
+    use Violation::NonShortestArgument;
+    assert_violation(&[0x18, 0x17], 0, NonShortestArgument);
+    assert_violation(&[0x19, 0x00, 0xff], 0, NonShortestArgument);
+    assert_violation(&[0x81, 0x58, 0x01, 0x00], 1, NonShortestArgument);
+    assert_violation(&[0xd8, 0x01, 0x00], 0, NonShortestArgument);
+    assert_violation(&[0x98, 0x01, 0x00], 0, NonShortestArgument);
+}
+
+#[test]
+fn deterministic_non_preferred_float() {
+    use Violation::NonPreferredFloat;
+    // 1.0 in single and double precision
+    assert_violation(&[0xfa, 0x3f, 0x80, 0x00, 0x00], 0, NonPreferredFloat);
+    let double = [0xfb, 0x3f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert_violation(&double, 0, NonPreferredFloat);
+    // 100000.0 in double precision, fits into single precision
+    let double = [0xfb, 0x40, 0xf8, 0x6a, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert_violation(&double, 0, NonPreferredFloat);
+    // NaN in single precision
+    assert_violation(&[0xfa, 0x7f, 0xc0, 0x00, 0x00], 0, NonPreferredFloat);
+}
+
+#[test]
+fn deterministic_indefinite_length() {
+    use Violation::IndefiniteLength;
+    assert_violation(&[0x9f, 0xff], 0, IndefiniteLength);
+    assert_violation(&[0x81, 0x5f, 0xff], 1, IndefiniteLength);
+    assert_violation(&[0xa1, 0x00, 0xbf, 0xff], 2, IndefiniteLength);
+}
+
+#[test]
+fn deterministic_unsorted_keys() {
+    use Violation::UnsortedKeys;
+    // {1: 0, 0: 0}
+    assert_violation(&[0xa2, 0x01, 0x00, 0x00, 0x00], 3, UnsortedKeys);
+    // {0: 0, 0: 0}
+    assert_violation(&[0xa2, 0x00, 0x00, 0x00, 0x00], 3, UnsortedKeys);
+    // {"aa": 0, "b": 0}; shorter keys sort first
+    let bytes = [0xa2, 0x62, 0x61, 0x61, 0x00, 0x61, 0x62, 0x00];
+    assert_violation(&bytes, 5, UnsortedKeys);
+    // [{[0, {1: 0, 0: 0}]: 0}]; nested keys are checked, too
+    let bytes = [
+        0x81, 0xa1, 0x82, 0x00, 0xa2, 0x01, 0x00, 0x00, 0x00, 0x00,
+    ];
+    assert_violation(&bytes, 7, UnsortedKeys);
+}
+
+#[test]
+fn deterministic_sequence() {
+    let mut decoder = Decoder::lr();
+    decoder.set_deterministic(true);
+    let bytes = [0xa1, 0x00, 0x01, 0xa1, 0x00, 0x01];
+    let (_, count) = decoder.decode(&bytes).unwrap();
+    assert_eq!(3, count);
+    let (_, count) = decoder.decode(&bytes[3..]).unwrap();
+    assert_eq!(3, count);
+}
+
+#[test]
+fn budget_small_strings() {
+    // Array of 200 one-byte text strings
+    let mut bytes = vec![0x98, 200];
+    for _ in 0..200 {
+        bytes.extend([0x61, 0x61]);
+    }
+    for limit in [None, Some(1 << 20), Some(2048)] {
+        let mut ll = Decoder::ll();
+        ll.set_budget(limit);
+        let mut lr = Decoder::lr();
+        lr.set_budget(limit);
+        for result in [ll.decode(&bytes), lr.decode(&bytes)] {
+            match limit {
+                Some(2048) => {
+                    let error = result.unwrap_err();
+                    assert!(matches!(
+                        error.cause(),
+                        Error::BudgetExceeded(2048)
+                    ))
+                }
+                _ => assert_eq!(bytes.len(), result.unwrap().1),
+            }
+        }
+    }
+}
+
+#[test]
+fn budget_declared_payload() {
+    // Byte string that claims to hold 2^40 bytes
+    let bytes = [0x5b, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut decoder = Decoder::ll();
+    decoder.set_budget(Some(1 << 20));
+    let error = decoder.decode(&bytes).unwrap_err();
+    assert!(matches!(error.cause(), Error::BudgetExceeded(_)));
+    assert_eq!(Some(0), error.offset());
+}
+
+#[test]
+fn budget_per_value() {
+    // "abcd" "abcd"
+    let bytes = [0x64, 0x61, 0x62, 0x63, 0x64, 0x64, 0x61, 0x62, 0x63, 0x64];
+    let mut decoder = Decoder::lr();
+    decoder.set_budget(Some(4 + 64));
+    let (_, count) = decoder.decode(&bytes).unwrap();
+    assert_eq!(5, count);
+    let (_, count) = decoder.decode(&bytes[5..]).unwrap();
+    assert_eq!(5, count);
+    decoder.set_budget(Some(3));
+    let error = decoder.decode(&bytes).unwrap_err();
+    assert!(matches!(error.cause(), Error::BudgetExceeded(3)));
+}
@@ -0,0 +1,283 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fmt;
+
+use crate::decoder;
+use crate::value::{TryFromTokenError, TryFromValueError};
+use crate::{cursor, parser, scanner};
+
+/// What could possibly go wrong anywhere in this crate?
+///
+/// The error wraps the error of the module where it occurred, and converts
+/// from each of those with `?`. Use [Error::kind()] to tell the classes of
+/// errors apart, and [Error::offset()] to locate the error in the input.
+///
+/// # Example
+///
+///     use cbor::decoder::Decoder;
+///     use cbor::{Error, ErrorKind};
+///
+///     let mut decoder = Decoder::ll();
+///     // [1, %break]
+///     let result = decoder.decode(&[0x81, 0x01, 0xff]);
+///     assert!(result.is_ok());
+///     let error = Error::from(decoder.decode(&[0xff]).unwrap_err());
+///     assert_eq!(ErrorKind::Malformed, error.kind());
+///     assert_eq!(Some(2), error.offset());
+#[derive(Debug)]
+pub enum Error {
+    /// Error propagated from the scanner.
+    Scanner(scanner::Error),
+    /// Error propagated from a parser.
+    Parser(parser::Error),
+    /// Error propagated from the decoder, other than those of its scanner,
+    /// parser and reader.
+    Decoder(decoder::Error),
+    /// Error propagated from a cursor, other than those of its scanner.
+    Cursor(cursor::Error),
+    /// Error propagated from constructing a value from a token.
+    Value(TryFromTokenError),
+    /// Error propagated from converting between a value and an integer.
+    Integer(TryFromValueError),
+    /// Error propagated from a reader.
+    Io(std::io::Error),
+    /// The error occurred at this byte offset into the input.
+    At(u64, Box<Error>),
+}
+
+/// Class of an [Error]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorKind {
+    /// The input is not well-formed CBOR.
+    Malformed,
+    /// The input ended before the data item was complete; more input may
+    /// resolve the error.
+    Incomplete,
+    /// The input exceeds a limit, such as the memory budget, the stack size
+    /// of a parser, or the `usize` of the platform.
+    LimitExceeded,
+    /// The input is well-formed, but not valid, or does not have the
+    /// expected type or structure.
+    Invalid,
+    /// Reading the input failed.
+    Io,
+    /// The crate encountered an internal error, or was not set up properly.
+    Internal,
+}
+
+impl Error {
+    /// Returns the class of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Scanner(error) => scanner_kind(error),
+            Error::Parser(error) => parser_kind(error),
+            Error::Decoder(error) => decoder_kind(error),
+            Error::Cursor(error) => cursor_kind(error),
+            Error::Value(_) | Error::Integer(_) => ErrorKind::Invalid,
+            Error::Io(_) => ErrorKind::Io,
+            Error::At(_, error) => error.kind(),
+        }
+    }
+
+    /// Returns the byte offset into the input where the error occurred, if
+    /// known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Error::At(offset, _) => Some(*offset),
+            Error::Decoder(decoder::Error::NotDeterministic(offset, _)) => {
+                Some(*offset)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the error located at `offset` into the input.
+    ///
+    /// The offset replaces any offset the error had before.
+    pub fn at(self, offset: u64) -> Self {
+        match self {
+            Error::At(_, error) => Error::At(offset, error),
+            error => Error::At(offset, Box::new(error)),
+        }
+    }
+
+    /// Returns the error without its offset.
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::At(_, error) => error,
+            error => error,
+        }
+    }
+}
+
+fn scanner_kind(error: &scanner::Error) -> ErrorKind {
+    use scanner::Error::*;
+    match error {
+        UnexpectedEof => ErrorKind::Incomplete,
+        UnexpectedHead(_) => ErrorKind::Malformed,
+        Excessive(_) | BudgetExceeded(_) => ErrorKind::LimitExceeded,
+        Internal => ErrorKind::Internal,
+    }
+}
+
+fn parser_kind(error: &parser::Error) -> ErrorKind {
+    use parser::Error::*;
+    match error {
+        Incomplete => ErrorKind::Incomplete,
+        UnexpectedT(..) | UnexpectedNT(..) | Unexpected(_) | TrailingInput => {
+            ErrorKind::Malformed
+        }
+        Scanner(error) => scanner_kind(error),
+        InsufficientStackSize | BudgetExceeded(_) | Excessive(_) => {
+            ErrorKind::LimitExceeded
+        }
+        InvalidUtf8(_) | DuplicateKey(_) | InvalidTag(..) | UnknownTag(_) => {
+            ErrorKind::Invalid
+        }
+        At(_, error) => parser_kind(error),
+        Invalid | Internal | Todo(_) => ErrorKind::Internal,
+    }
+}
+
+fn decoder_kind(error: &decoder::Error) -> ErrorKind {
+    use decoder::Error::*;
+    match error {
+        Eof | Incomplete => ErrorKind::Incomplete,
+        NotDeterministic(..) => ErrorKind::Invalid,
+        BudgetExceeded(_) => ErrorKind::LimitExceeded,
+        Scanner(error) => scanner_kind(error),
+        Parser(error) => parser_kind(error),
+        Io(_) => ErrorKind::Io,
+        At(_, error) => decoder_kind(error),
+    }
+}
+
+fn cursor_kind(error: &cursor::Error) -> ErrorKind {
+    use cursor::Error::*;
+    match error {
+        Incomplete => ErrorKind::Incomplete,
+        UnexpectedChunk(_) | UnexpectedBreak => ErrorKind::Malformed,
+        End | NotContainer(_) | NotScalar(_) | InvalidUtf8(_) | TopLevel => {
+            ErrorKind::Invalid
+        }
+        Scanner(error) => scanner_kind(error),
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Scanner(error) => Some(error),
+            Error::Parser(error) => Some(error),
+            Error::Decoder(error) => Some(error),
+            Error::Cursor(error) => Some(error),
+            Error::Value(error) => Some(error),
+            Error::Integer(error) => Some(error),
+            Error::Io(error) => Some(error),
+            Error::At(_, error) => error.source(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Scanner(error) => write!(f, "{error}"),
+            Error::Parser(error) => write!(f, "{error}"),
+            Error::Decoder(error) => write!(f, "{error}"),
+            Error::Cursor(error) => write!(f, "{error}"),
+            Error::Value(error) => write!(f, "{error}"),
+            Error::Integer(error) => write!(f, "{error}"),
+            Error::Io(error) => write!(f, "{error}"),
+            Error::At(offset, error) => write!(f, "{error} at offset {offset}"),
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            ErrorKind::Malformed => "malformed input",
+            ErrorKind::Incomplete => "incomplete input",
+            ErrorKind::LimitExceeded => "limit exceeded",
+            ErrorKind::Invalid => "invalid data",
+            ErrorKind::Io => "I/O error",
+            ErrorKind::Internal => "internal error",
+        };
+        f.write_str(text)
+    }
+}
+
+impl From<scanner::Error> for Error {
+    fn from(error: scanner::Error) -> Self {
+        Error::Scanner(error)
+    }
+}
+
+impl From<parser::Error> for Error {
+    fn from(error: parser::Error) -> Self {
+        match error {
+            parser::Error::Scanner(error) => Error::Scanner(error),
+            error => Error::Parser(error),
+        }
+    }
+}
+
+impl From<decoder::Error> for Error {
+    fn from(error: decoder::Error) -> Self {
+        match error {
+            decoder::Error::Scanner(error) => Error::Scanner(error),
+            decoder::Error::Parser(error) => error.into(),
+            decoder::Error::Io(error) => Error::Io(error),
+            decoder::Error::At(offset, error) => Error::from(*error).at(offset),
+            error => Error::Decoder(error),
+        }
+    }
+}
+
+impl From<cursor::Error> for Error {
+    fn from(error: cursor::Error) -> Self {
+        match error {
+            cursor::Error::Scanner(error) => Error::Scanner(error),
+            error => Error::Cursor(error),
+        }
+    }
+}
+
+impl From<TryFromTokenError> for Error {
+    fn from(error: TryFromTokenError) -> Self {
+        Error::Value(error)
+    }
+}
+
+impl From<TryFromValueError> for Error {
+    fn from(error: TryFromValueError) -> Self {
+        Error::Integer(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,140 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use crate::cursor::Cursor;
+use crate::decoder::Decoder;
+use crate::parser::{Parser, ll};
+use crate::scanner::Scanner;
+use crate::token::Token;
+use crate::value::Value;
+use std::error::Error as _;
+
+#[test]
+fn kinds() {
+    let mut decoder = Decoder::ll();
+    let error = Error::from(decoder.decode(&[0x1c]).unwrap_err());
+    assert_eq!(ErrorKind::Malformed, error.kind());
+    assert!(matches!(error.cause(), Error::Scanner(_)));
+
+    decoder.reset();
+    let error = Error::from(decoder.decode(&[0x82, 0x01]).unwrap_err());
+    assert_eq!(ErrorKind::Incomplete, error.kind());
+
+    decoder.reset();
+    decoder.set_budget(Some(16));
+    let error = Error::from(decoder.decode(&[0x58, 0x20]).unwrap_err());
+    assert_eq!(ErrorKind::LimitExceeded, error.kind());
+
+    // Text string of one byte that is not valid UTF-8
+    let mut decoder = Decoder::ll();
+    let bytes = [0x61, 0xff];
+    let error = Error::from(decoder.decode(&bytes).unwrap_err());
+    assert_eq!(ErrorKind::Invalid, error.kind());
+    assert!(matches!(error.cause(), Error::Parser(_)));
+
+    let mut decoder = Decoder::ll();
+    let error = Error::from(decoder.from_reader(FailingReader).unwrap_err());
+    assert_eq!(ErrorKind::Io, error.kind());
+    assert!(matches!(error, Error::Io(_)));
+
+    let error = Error::from(parser::Error::Internal);
+    assert_eq!(ErrorKind::Internal, error.kind());
+
+    let error = Error::from(Value::try_from(Token::Break).unwrap_err());
+    assert_eq!(ErrorKind::Invalid, error.kind());
+
+    let error = Error::from(Cursor::new(&[0xff]).skip().unwrap_err());
+    assert_eq!(ErrorKind::Malformed, error.kind());
+}
+
+#[test]
+fn offsets() {
+    let mut decoder = Decoder::ll();
+    decoder.set_deterministic(true);
+    // [1, 24(0)]; the argument of the second item is not in shortest form
+    let bytes = [0x82, 0x01, 0x18, 0x00];
+    let error = Error::from(decoder.decode(&bytes).unwrap_err());
+    assert_eq!(ErrorKind::Invalid, error.kind());
+    assert_eq!(Some(2), error.offset());
+
+    // [1, %break]
+    let mut decoder = Decoder::lr();
+    let error = decoder.decode(&[0x82, 0x01, 0xff]).unwrap_err();
+    let error = Error::from(error);
+    assert_eq!(ErrorKind::Malformed, error.kind());
+    assert_eq!(Some(2), error.offset());
+    assert!(matches!(error.cause(), Error::Parser(_)));
+
+    let error = Error::from(parser::Error::TrailingInput);
+    assert_eq!(None, error.offset());
+    let error = error.at(3).at(5);
+    assert_eq!(Some(5), error.offset());
+    assert_eq!(ErrorKind::Malformed, error.kind());
+    assert!(matches!(error.cause(), Error::Parser(_)));
+    assert_eq!("Trailing input at offset 5", error.to_string());
+}
+
+#[test]
+fn sources() {
+    let error = Error::from(scanner::Error::UnexpectedHead(0x1c)).at(0);
+    let source = error.source().unwrap();
+    assert!(source.downcast_ref::<scanner::Error>().is_some());
+
+    // Text string of one byte that is not valid UTF-8, nested in an array
+    let mut decoder = Decoder::ll();
+    let error = Error::from(decoder.decode(&[0x81, 0x61, 0xff]).unwrap_err());
+    assert_eq!(Some(1), error.offset());
+    let source = error.source().unwrap();
+    assert!(source.downcast_ref::<parser::Error>().is_some());
+    let source = source.source().unwrap();
+    assert!(source.downcast_ref::<std::str::Utf8Error>().is_some());
+
+    let error =
+        Error::from(parser::Error::Scanner(scanner::Error::UnexpectedEof));
+    assert!(matches!(error, Error::Scanner(_)));
+    assert_eq!(ErrorKind::Incomplete, error.kind());
+}
+
+#[test]
+fn question_mark() {
+    fn decode(bytes: &[u8]) -> Result<Option<Value>, Error> {
+        let mut scanner = Scanner::default();
+        let mut parser = ll::Parser::cbor();
+        for byte in bytes {
+            if let Some(token) = scanner.consume(*byte)?
+                && let Some(value) = parser.consume(token)?
+            {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+    assert_eq!(Some(Value::Uint(1)), decode(&[0x01]).unwrap());
+    assert_eq!(ErrorKind::Malformed, decode(&[0xff]).unwrap_err().kind());
+    assert_eq!(ErrorKind::Malformed, decode(&[0x1c]).unwrap_err().kind());
+}
+
+struct FailingReader;
+
+impl std::io::Read for FailingReader {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("failing reader"))
+    }
+}
@@ -28,7 +28,8 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 //! This crate provides a [parser] together with a [scanner]. The scanner
 //! consumes bytes, and produces [tokens](token), which can then be consumed by
 //! the parser. The parser consumes tokens, and may produce as output a CBOR
-//! [value].
+//! [value]. The [decoder] combines scanner and parser for the common case of
+//! decoding values from bytes.
 //!
 //! The scanner and parser have been designed for use with serial lines where
 //! bytes trickle rather than stream.  The scanner works with all forms of
@@ -68,14 +69,11 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 //! use cbor::scanner::Scanner;
 //! use cbor::parser::*;
 //!
-//! fn decode(values: Vec<u8>) -> Result<cbor::value::Value, Error> {
+//! fn decode(values: Vec<u8>) -> Result<cbor::value::Value, cbor::Error> {
 //!     let mut scanner = Scanner::default();
 //!     let mut parser = ll::Parser::cbor();
 //!     for byte in values {
-//!         let Some(token) = scanner
-//!             .consume(byte)
-//!             .map_err(Error::Scanner)?
-//!         else {
+//!         let Some(token) = scanner.consume(byte)? else {
 //!             continue;
 //!         };
 //!         let Some(value) = parser.consume(token)? else {
@@ -83,7 +81,7 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 //!         };
 //!         return Ok(value);
 //!     }
-//!     Err(Error::Incomplete)
+//!     Err(Error::Incomplete.into())
 //! }
 //!
 //! let values = vec![
@@ -112,11 +110,25 @@ pub mod scanner;
 /// the process.
 pub mod parser;
 
+/// Decoding [bytes](u8) into CBOR [values](value), combining [scanner] and
+/// [parser].
+pub mod decoder;
+
+/// Memory budget of a decode, shared by [scanner] and [parser].
+pub mod budget;
+
+/// Lazy navigation of encoded data items.
+pub mod cursor;
+
 /// Everything about CBOR values.
 pub mod value;
 
-// TODO
-// mod render;
+/// Errors of all modules of this crate, with their classes and offsets.
+pub mod error;
+pub use error::{Error, ErrorKind};
+
+/// Rendering of CBOR values in diagnostic notation.
+pub mod render;
 
 #[cfg(test)]
 mod tests;
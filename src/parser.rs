@@ -17,6 +17,8 @@ You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use crate::budget::Budget;
+use crate::token::Precision;
 use crate::value::Value;
 
 /// Terminal and non-terminal symbols.
@@ -26,6 +28,9 @@ use grammar::term::Kind;
 use grammar::term::Term;
 
 pub trait Parser {
+    /// Type of the values produced
+    type Output;
+
     /// Consumes a `token`, maybe produces a CBOR value.
     ///
     /// The method returns
@@ -38,18 +43,60 @@ pub trait Parser {
     ///
     /// In the case of an invalid error, the parser is in a pre-initialization
     /// state. Once the parser is initialised, it can be used again.
-    fn consume(&mut self, term: Term) -> Result<Option<Value>, Error>;
+    fn consume(&mut self, term: Term) -> Result<Option<Self::Output>, Error>;
+
+    /// Resets the parser to its initial state.
+    ///
+    /// Any partially parsed value is discarded. Use this method to recover
+    /// from errors, in conjunction with [Scanner::reset()].
+    ///
+    /// [Scanner::reset()]: crate::scanner::Scanner::reset
+    fn reset(&mut self);
+
+    /// Sets the memory budget, which the parser charges with the values it
+    /// builds.
+    ///
+    /// The default implementation ignores the budget.
+    fn set_budget(&mut self, budget: Budget) {
+        let _ = budget;
+    }
 }
 
 /// Parser errors
 pub mod error;
 pub use error::Error;
 
+/// Paths to nested data items
+pub mod path;
+pub use path::{Path, Segment};
+
+/// Construction of data items
+pub mod builder;
+use builder::Slot;
+pub use builder::{Builder, Entries};
+
+/// Decoding options
+pub mod options;
+pub use options::{
+    DuplicateKeys, Options, TagHandler, TagRegistry, UnknownTags,
+};
+
+/// Progress of partially parsed data items
+pub mod progress;
+pub use progress::{Open, OpenKind, Progress};
+
+/// Selection of nested data items
+pub mod select;
+pub use select::{Select, Selector, Step};
+
 /// LL(1) top-down parser for CBOR
 pub mod ll;
 
 /// LR(1) bottom-up parser for CBOR
 pub mod lr;
 
+/// Event-driven parser for CBOR
+pub mod event;
+
 #[cfg(test)]
 mod tests;
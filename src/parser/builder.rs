@@ -0,0 +1,270 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use options::KeyIndex;
+use std::{fmt, mem};
+
+/// Builder of data items
+///
+/// The parsers recognise the structure of the input, and leave the
+/// construction of the data items to a builder. The builder receives each
+/// data item once it is complete: scalars as they are consumed, strings once
+/// all chunks are consumed, and arrays, maps and tagged items once all nested
+/// data items are built.
+///
+/// [Options] build a [Value], and apply the decoding options. Implement this
+/// trait to build domain types directly, without an intermediate [Value].
+///
+/// # Example
+///
+///     use cbor::parser::{Builder, Error, Parser, ll};
+///     use cbor::parser::grammar::term::Term;
+///     use cbor::token::Precision;
+///
+///     /// Sums up all unsigned integers
+///     struct Sum;
+///
+///     impl Builder for Sum {
+///         type Output = u64;
+///         fn uint(&mut self, n: u64) -> Result<u64, Error> {
+///             Ok(n)
+///         }
+///         fn nint(&mut self, _: u64) -> Result<u64, Error> {
+///             Ok(0)
+///         }
+///         fn float(&mut self, _: Precision, _: u64) -> Result<u64, Error> {
+///             Ok(0)
+///         }
+///         fn simple(&mut self, _: u8) -> Result<u64, Error> {
+///             Ok(0)
+///         }
+///         fn bytes(&mut self, _: Vec<u8>) -> Result<u64, Error> {
+///             Ok(0)
+///         }
+///         fn text(&mut self, _: String) -> Result<u64, Error> {
+///             Ok(0)
+///         }
+///         fn array(&mut self, items: Vec<u64>) -> Result<u64, Error> {
+///             Ok(items.iter().sum())
+///         }
+///         fn map(&mut self, entries: Vec<(u64, u64)>) -> Result<u64, Error> {
+///             Ok(entries.iter().map(|(_, item)| item).sum())
+///         }
+///         fn tag(&mut self, _: u64, item: u64) -> Result<u64, Error> {
+///             Ok(item)
+///         }
+///     }
+///
+///     let mut parser = ll::Parser::with_builder(Sum);
+///     let mut result = None;
+///     for term in [Term::Array(2), Term::Uint(1), Term::Uint(2)] {
+///         result = parser.consume(term).unwrap();
+///     }
+///     assert_eq!(Some(3), result);
+pub trait Builder {
+    /// Type of the data items
+    type Output: fmt::Debug;
+
+    /// Builds an unsigned integer.
+    fn uint(&mut self, n: u64) -> Result<Self::Output, Error>;
+
+    /// Builds the negative integer `-1-n`.
+    fn nint(&mut self, n: u64) -> Result<Self::Output, Error>;
+
+    /// Builds a floating-point number from its encoded `bits` in `precision`.
+    fn float(
+        &mut self,
+        precision: Precision,
+        bits: u64,
+    ) -> Result<Self::Output, Error>;
+
+    /// Builds a simple value.
+    fn simple(&mut self, n: u8) -> Result<Self::Output, Error>;
+
+    /// Builds a byte string from all its chunks.
+    fn bytes(&mut self, bytes: Vec<u8>) -> Result<Self::Output, Error>;
+
+    /// Builds a text string from all its chunks.
+    fn text(&mut self, text: String) -> Result<Self::Output, Error>;
+
+    /// Builds an array from its `items`.
+    fn array(
+        &mut self,
+        items: Vec<Self::Output>,
+    ) -> Result<Self::Output, Error>;
+
+    /// Appends `entry` to the entries of a `map`, as soon as the entry is
+    /// complete.
+    ///
+    /// The default implementation appends the entry. [Options] apply the
+    /// policy for duplicate keys here, so that errors refer to the duplicate
+    /// entry rather than to the complete map.
+    fn entry(
+        &mut self,
+        map: &mut Entries<Self::Output>,
+        entry: (Self::Output, Self::Output),
+    ) -> Result<(), Error> {
+        map.push(entry);
+        Ok(())
+    }
+
+    /// Builds a map from its `entries`, in order, as appended by
+    /// [Builder::entry].
+    fn map(
+        &mut self,
+        entries: Vec<(Self::Output, Self::Output)>,
+    ) -> Result<Self::Output, Error>;
+
+    /// Builds a tagged data item.
+    fn tag(
+        &mut self,
+        tag: u64,
+        item: Self::Output,
+    ) -> Result<Self::Output, Error>;
+}
+
+impl Builder for Options {
+    type Output = Value;
+
+    fn uint(&mut self, n: u64) -> Result<Value, Error> {
+        Ok(Value::Uint(n))
+    }
+
+    fn nint(&mut self, n: u64) -> Result<Value, Error> {
+        Ok(Value::Nint(n))
+    }
+
+    fn float(
+        &mut self,
+        precision: Precision,
+        bits: u64,
+    ) -> Result<Value, Error> {
+        Ok(Value::Float(precision, bits))
+    }
+
+    fn simple(&mut self, n: u8) -> Result<Value, Error> {
+        Ok(Value::Simple(n))
+    }
+
+    fn bytes(&mut self, bytes: Vec<u8>) -> Result<Value, Error> {
+        Ok(Value::Bstr(bytes))
+    }
+
+    fn text(&mut self, text: String) -> Result<Value, Error> {
+        Ok(Value::Tstr(text))
+    }
+
+    fn array(&mut self, items: Vec<Value>) -> Result<Value, Error> {
+        Ok(Value::Array(items))
+    }
+
+    fn entry(
+        &mut self,
+        map: &mut Entries<Value>,
+        entry: (Value, Value),
+    ) -> Result<(), Error> {
+        self.duplicate_keys.insert(map, entry)
+    }
+
+    fn map(&mut self, entries: Vec<(Value, Value)>) -> Result<Value, Error> {
+        Ok(Value::Map(entries))
+    }
+
+    fn tag(&mut self, tag: u64, item: Value) -> Result<Value, Error> {
+        self.apply_tag(tag, item)
+    }
+}
+
+/// Partial result of a parser
+///
+/// Strings and containers collect their chunks and items until they are
+/// complete, and are then built.
+#[derive(Clone, Debug)]
+pub(crate) enum Slot<T> {
+    /// Built data item
+    Item(T),
+    /// Byte string
+    Bytes(Vec<u8>),
+    /// Text string
+    Text(String),
+    /// Array
+    Array(Vec<T>),
+    /// Map, boxed so that all slots stay as small as built data items
+    Map(Box<Entries<T>>),
+}
+
+impl<T> Slot<T> {
+    /// Builds the data item with `builder`, unless it is built already.
+    pub fn build<B>(self, builder: &mut B) -> Result<T, Error>
+    where
+        B: Builder<Output = T>,
+    {
+        match self {
+            Slot::Item(item) => Ok(item),
+            Slot::Bytes(bytes) => builder.bytes(bytes),
+            Slot::Text(text) => builder.text(text),
+            Slot::Array(items) => builder.array(items),
+            Slot::Map(map) => builder.map(map.entries),
+        }
+    }
+}
+
+/// Entries of a map that is being parsed
+///
+/// Besides the entries, the map holds an index of their keys, with which
+/// [Options] look up duplicate keys.
+#[derive(Clone, Debug)]
+pub struct Entries<T> {
+    pub(crate) entries: Vec<(T, T)>,
+    pub(crate) keys: KeyIndex,
+}
+
+impl<T> Entries<T> {
+    /// Returns the slot of an empty map.
+    pub(crate) fn slot() -> Slot<T> {
+        Slot::Map(Box::default())
+    }
+
+    /// Appends `entry`.
+    pub fn push(&mut self, entry: (T, T)) {
+        self.entries.push(entry);
+    }
+
+    /// Returns the entries, in order.
+    pub fn as_slice(&self) -> &[(T, T)] {
+        &self.entries
+    }
+
+    /// Returns the number of bytes allocated for the entries and the index
+    /// of their keys, which the value stacks charge to their budget.
+    pub(crate) fn allocated(&self) -> usize {
+        let entries = self.entries.capacity() * mem::size_of::<(T, T)>();
+        entries + self.keys.allocated()
+    }
+}
+
+impl<T> Default for Entries<T> {
+    fn default() -> Self {
+        Entries {
+            entries: Vec::new(),
+            keys: KeyIndex::default(),
+        }
+    }
+}
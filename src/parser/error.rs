@@ -19,10 +19,11 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use super::*;
 
+use crate::budget::Exceeded;
 use crate::scanner::Error as ScanError;
 
 /// What could possibly go wrong with parsing?
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub enum Error {
     /// The parser is in an invalid state.
     ///
@@ -50,6 +51,24 @@ pub enum Error {
     /// to context stack size for an LL parser, or state stack size for an LR
     /// parser.
     InsufficientStackSize,
+    /// Parsing a token would exceed the memory budget of this many bytes;
+    /// see [Budget](crate::budget::Budget).
+    BudgetExceeded(usize),
+    /// A definite-length array or map has more data items than the parser
+    /// can count; the argument is the count of the head.
+    Excessive(u64),
+    /// A text string is not valid UTF-8.
+    InvalidUtf8(std::str::Utf8Error),
+    /// A map contains this key more than once; see [DuplicateKeys].
+    ///
+    /// [DuplicateKeys]: super::DuplicateKeys
+    DuplicateKey(Value),
+    /// A tag handler rejected the tagged item, for the reason given.
+    InvalidTag(u64, String),
+    /// The tag has no handler, and unknown tags are rejected.
+    UnknownTag(u64),
+    /// The error occurred while parsing the data item at this path.
+    At(Path, Box<Error>),
     /// Internal error
     Internal,
     /// To do (for development purposes only)
@@ -67,7 +86,7 @@ impl std::fmt::Display for Error {
                 f.write_str("The parser encountered ")?;
                 f.write_fmt(format_args!("{actual}"))?;
                 f.write_str(" when it was expecting one of [")?;
-                let mut iter = expected.into_iter().map(|t| format!("{t}"));
+                let mut iter = expected.iter().map(|t| format!("{t}"));
                 if let Some(str) = iter.next() {
                     f.write_str(&str)?;
                     for str in iter {
@@ -82,7 +101,7 @@ impl std::fmt::Display for Error {
                 f.write_str("The parser encountered ")?;
                 f.write_fmt(format_args!("{actual}"))?;
                 f.write_str(" when it was expecting one of [")?;
-                let mut iter = expected.into_iter().map(|nt| format!("{nt}"));
+                let mut iter = expected.iter().map(|nt| format!("{nt}"));
                 if let Some(str) = iter.next() {
                     f.write_str(&str)?;
                     for str in iter {
@@ -97,10 +116,75 @@ impl std::fmt::Display for Error {
             Error::TrailingInput => write!(f, "Trailing input"),
             Error::Scanner(scan_error) => write!(f, "{scan_error}"),
             Error::InsufficientStackSize => write!(f, "507 Insufficient stack size"),
+            Error::BudgetExceeded(limit) => {
+                write!(f, "Memory budget of {limit} bytes exceeded")
+            }
+            Error::Excessive(count) => write!(f, "Excessive count ({count})"),
+            Error::InvalidUtf8(error) => {
+                write!(f, "Invalid UTF-8 in text string: {error}")
+            }
+            Error::DuplicateKey(key) => write!(f, "Duplicate map key {key}"),
+            Error::InvalidTag(tag, reason) => {
+                write!(f, "Invalid item for tag {tag}: {reason}")
+            }
+            Error::UnknownTag(tag) => write!(f, "Unknown tag {tag}"),
+            Error::At(path, error) => write!(f, "{error} at {path}"),
             Error::Internal => write!(f, "500 Internal Error"),
             Error::Todo(string) => write!(f, "TODO: {string}"),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl Error {
+    /// Returns the path to the data item where the error occurred, if the
+    /// item is nested.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::At(path, _) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the error without its path.
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::At(_, error) => error,
+            error => error,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Scanner(error) => Some(error),
+            Error::InvalidUtf8(error) => Some(error),
+            Error::At(_, error) => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(error: std::str::Utf8Error) -> Self {
+        Error::InvalidUtf8(error)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(error: std::string::FromUtf8Error) -> Self {
+        Error::InvalidUtf8(error.utf8_error())
+    }
+}
+
+impl From<ScanError> for Error {
+    fn from(error: ScanError) -> Self {
+        Error::Scanner(error)
+    }
+}
+
+impl From<Exceeded> for Error {
+    fn from(exceeded: Exceeded) -> Self {
+        Error::BudgetExceeded(exceeded.0)
+    }
+}
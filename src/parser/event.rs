@@ -0,0 +1,403 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! An event-driven parser reports the structure of CBOR data items to a
+//! [Handler] as it consumes tokens, rather than building [Value]s. Memory use
+//! depends on the nesting depth of the data items only.
+//!
+//! # Example
+//!
+//!     use cbor::parser::event::{Driver, Handler};
+//!     use cbor::scanner::Scanner;
+//!
+//!     #[derive(Default)]
+//!     struct Counter {
+//!         uints: usize,
+//!     }
+//!
+//!     impl Handler for Counter {
+//!         fn uint(&mut self, _: u64) {
+//!             self.uints += 1;
+//!         }
+//!     }
+//!
+//!     let mut scanner = Scanner::default();
+//!     let mut driver = Driver::new(Counter::default());
+//!     for byte in [0x83, 0x01, 0x02, 0x20] {
+//!         if let Some(token) = scanner.consume(byte).unwrap() {
+//!             driver.consume(token).unwrap();
+//!         }
+//!     }
+//!     assert_eq!(2, driver.handler().uints);
+//!
+//! [Value]: crate::value::Value
+
+use super::*;
+
+/// Receiver of parsing events
+///
+/// The [Driver] invokes the callbacks in the order of the encoded data items.
+/// Every callback has an empty default implementation, so that handlers only
+/// implement the events they are interested in.
+///
+/// Strings are reported as a sequence of chunks between a start and an end
+/// event: definite-length strings have exactly one chunk, indefinite-length
+/// strings have as many chunks as encoded. The chunks of an indefinite-length
+/// string nested as a chunk are reported as chunks of the enclosing string.
+pub trait Handler {
+    /// Invoked for an unsigned integer.
+    fn uint(&mut self, _: u64) {}
+    /// Invoked for a negative integer; the argument is `-1 - n`.
+    fn nint(&mut self, _: u64) {}
+    /// Invoked for a floating-point number; the arguments hold the encoded
+    /// precision and the raw bits.
+    fn float(&mut self, _: Precision, _: u64) {}
+    /// Invoked for a simple value.
+    fn simple(&mut self, _: u8) {}
+    /// Invoked for a tag; the next data item is the tagged item.
+    fn tag(&mut self, _: u64) {}
+    /// Invoked at the start of a byte string.
+    fn start_bstr(&mut self) {}
+    /// Invoked for each chunk of a byte string.
+    fn bstr_chunk(&mut self, _: &[u8]) {}
+    /// Invoked at the end of a byte string.
+    fn end_bstr(&mut self) {}
+    /// Invoked at the start of a text string.
+    fn start_tstr(&mut self) {}
+    /// Invoked for each chunk of a text string; each chunk is valid UTF-8.
+    fn tstr_chunk(&mut self, _: &str) {}
+    /// Invoked at the end of a text string.
+    fn end_tstr(&mut self) {}
+    /// Invoked at the start of an array; the argument is the number of items,
+    /// or `None` for an indefinite-length array.
+    fn start_array(&mut self, _: Option<u64>) {}
+    /// Invoked at the end of an array.
+    fn end_array(&mut self) {}
+    /// Invoked at the start of a map; the argument is the number of entries,
+    /// or `None` for an indefinite-length map.
+    fn start_map(&mut self, _: Option<u64>) {}
+    /// Invoked before the label of each map entry.
+    fn map_key(&mut self) {}
+    /// Invoked before the item of each map entry.
+    fn map_value(&mut self) {}
+    /// Invoked at the end of a map.
+    fn end_map(&mut self) {}
+}
+
+/// Open data item
+#[derive(Clone, Copy, Debug)]
+enum Frame {
+    /// Definite-length array expecting this many more items
+    Array(u64),
+    /// Indefinite-length array
+    ArrayX,
+    /// Definite-length map expecting this many more entries, and whether the
+    /// next value is the item of the current entry
+    Map(u64, bool),
+    /// Indefinite-length map, and whether the next value is the item of the
+    /// current entry
+    MapX(bool),
+    /// Tag expecting the tagged item
+    Tag,
+    /// Indefinite-length byte string, or chunk thereof
+    BstrX,
+    /// Indefinite-length text string, or chunk thereof
+    TstrX,
+}
+
+/// Event-driven CBOR parser
+///
+/// The driver consumes tokens, checks them against the [grammar], and reports
+/// them to its [Handler]. Open data items are tracked on a stack of frames,
+/// which is protected against infinite recursion by an upper bound.
+#[derive(Debug)]
+pub struct Driver<H> {
+    handler: H,
+    frames: Vec<Frame>,
+    upper: usize,
+    sequence: bool,
+    accepted: bool,
+}
+
+impl<H: Handler> Driver<H> {
+    /// Returns a driver for a single CBOR data item.
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler,
+            frames: Vec::new(),
+            upper: 16384,
+            sequence: false,
+            accepted: false,
+        }
+    }
+
+    /// Returns a driver for a CBOR sequence (RFC 8742).
+    pub fn sequence(handler: H) -> Self {
+        Self {
+            sequence: true,
+            ..Self::new(handler)
+        }
+    }
+
+    /// Returns the handler.
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Returns the handler for modification.
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Consumes the driver, and returns the handler.
+    pub fn into_handler(self) -> H {
+        self.handler
+    }
+
+    /// Resets the driver to its initial state, discarding any open data items.
+    pub fn reset(&mut self) {
+        self.frames.clear();
+        self.accepted = false;
+    }
+
+    /// Consumes a `token`, and reports it to the handler.
+    ///
+    /// The method returns
+    /// - `Ok(true)` if the token has completed a top-level data item; or
+    /// - `Ok(false)` if the driver needs more tokens to complete it; or
+    /// - `Err(parse_error)` if something went wrong.
+    ///
+    /// In the case of an error, the state of the driver is unchanged.
+    pub fn consume(&mut self, term: Term) -> Result<bool, Error> {
+        if self.accepted {
+            return Err(Error::TrailingInput);
+        }
+        match (self.frames.last().copied(), term) {
+            // Production: bstrxseq = %break / bstr bstrxseq /
+            //                        %bstrx bstrxseq bstrxseq
+            (Some(Frame::BstrX), Term::Bstr(bytes)) => {
+                self.handler.bstr_chunk(&bytes);
+                Ok(false)
+            }
+            (Some(Frame::BstrX), Term::BstrX) => {
+                self.push(Frame::BstrX)?;
+                Ok(false)
+            }
+            (Some(Frame::BstrX), Term::Break) => {
+                self.frames.pop();
+                if let Some(Frame::BstrX) = self.frames.last() {
+                    return Ok(false);
+                }
+                self.handler.end_bstr();
+                Ok(self.complete())
+            }
+            (Some(Frame::BstrX), term) => Err(Error::UnexpectedT(
+                vec![Kind::Bstr, Kind::BstrX, Kind::Break],
+                term,
+            )),
+            // Production: tstrxseq = %break / tstr tstrxseq /
+            //                        %tstrx tstrxseq tstrxseq
+            (Some(Frame::TstrX), Term::Tstr(bytes)) => {
+                self.handler.tstr_chunk(std::str::from_utf8(&bytes)?);
+                Ok(false)
+            }
+            (Some(Frame::TstrX), Term::TstrX) => {
+                self.push(Frame::TstrX)?;
+                Ok(false)
+            }
+            (Some(Frame::TstrX), Term::Break) => {
+                self.frames.pop();
+                if let Some(Frame::TstrX) = self.frames.last() {
+                    return Ok(false);
+                }
+                self.handler.end_tstr();
+                Ok(self.complete())
+            }
+            (Some(Frame::TstrX), term) => Err(Error::UnexpectedT(
+                vec![Kind::Tstr, Kind::TstrX, Kind::Break],
+                term,
+            )),
+            // Production: arrayxseq = %break
+            (Some(Frame::ArrayX), Term::Break) => {
+                self.frames.pop();
+                self.handler.end_array();
+                Ok(self.complete())
+            }
+            // Production: mapxseq = %break
+            (Some(Frame::MapX(false)), Term::Break) => {
+                self.frames.pop();
+                self.handler.end_map();
+                Ok(self.complete())
+            }
+            (_, term @ Term::Break) => {
+                Err(Error::UnexpectedT(value_kinds(), term))
+            }
+            (_, term) => self.value(term),
+        }
+    }
+
+    /// Reports the start of a value, `term` being its first token.
+    ///
+    /// The token is checked before any event is reported, so that the
+    /// handler sees no events for a token that fails.
+    fn value(&mut self, term: Term) -> Result<bool, Error> {
+        let text = match &term {
+            Term::Tstr(bytes) => std::str::from_utf8(bytes)?,
+            _ => "",
+        };
+        let frame = match term {
+            Term::BstrX => Some(Frame::BstrX),
+            Term::TstrX => Some(Frame::TstrX),
+            Term::Array(n) if n > 0 => Some(Frame::Array(n)),
+            Term::ArrayX => Some(Frame::ArrayX),
+            Term::Map(n) if n > 0 => Some(Frame::Map(n, false)),
+            Term::MapX => Some(Frame::MapX(false)),
+            Term::Tag(_) => Some(Frame::Tag),
+            Term::Break => {
+                return Err(Error::UnexpectedT(value_kinds(), term));
+            }
+            _ => None,
+        };
+        if frame.is_some() && self.frames.len() >= self.upper {
+            return Err(Error::InsufficientStackSize);
+        }
+        match self.frames.last() {
+            Some(Frame::Map(_, false) | Frame::MapX(false)) => {
+                self.handler.map_key()
+            }
+            Some(Frame::Map(_, true) | Frame::MapX(true)) => {
+                self.handler.map_value()
+            }
+            _ => {}
+        }
+        match &term {
+            Term::Uint(n) => self.handler.uint(*n),
+            Term::Nint(n) => self.handler.nint(*n),
+            Term::Float(p, n) => self.handler.float(*p, *n),
+            Term::Simple(n) => self.handler.simple(*n),
+            Term::Bstr(bytes) => {
+                self.handler.start_bstr();
+                self.handler.bstr_chunk(bytes);
+                self.handler.end_bstr();
+            }
+            Term::Tstr(_) => {
+                self.handler.start_tstr();
+                self.handler.tstr_chunk(text);
+                self.handler.end_tstr();
+            }
+            Term::Array(0) => {
+                self.handler.start_array(Some(0));
+                self.handler.end_array();
+            }
+            Term::Map(0) => {
+                self.handler.start_map(Some(0));
+                self.handler.end_map();
+            }
+            Term::BstrX => self.handler.start_bstr(),
+            Term::TstrX => self.handler.start_tstr(),
+            Term::Array(n) => self.handler.start_array(Some(*n)),
+            Term::ArrayX => self.handler.start_array(None),
+            Term::Map(n) => self.handler.start_map(Some(*n)),
+            Term::MapX => self.handler.start_map(None),
+            Term::Tag(tag) => self.handler.tag(*tag),
+            Term::Break => {}
+        }
+        match frame {
+            Some(frame) => {
+                self.frames.push(frame);
+                Ok(false)
+            }
+            None => Ok(self.complete()),
+        }
+    }
+
+    /// Records the completion of a value, and closes all data items completed
+    /// by it. Returns `true` if a top-level data item is complete.
+    fn complete(&mut self) -> bool {
+        loop {
+            match self.frames.last_mut() {
+                None => {
+                    self.accepted = !self.sequence;
+                    return true;
+                }
+                Some(Frame::Tag) => {
+                    self.frames.pop();
+                }
+                Some(Frame::Array(n)) if *n > 1 => {
+                    *n -= 1;
+                    return false;
+                }
+                Some(Frame::Array(_)) => {
+                    self.frames.pop();
+                    self.handler.end_array();
+                }
+                Some(Frame::Map(_, expect_item @ false)) => {
+                    *expect_item = true;
+                    return false;
+                }
+                Some(Frame::Map(n, expect_item)) if *n > 1 => {
+                    *n -= 1;
+                    *expect_item = false;
+                    return false;
+                }
+                Some(Frame::Map(_, _)) => {
+                    self.frames.pop();
+                    self.handler.end_map();
+                }
+                Some(Frame::MapX(expect_item)) => {
+                    *expect_item = !*expect_item;
+                    return false;
+                }
+                Some(Frame::ArrayX | Frame::BstrX | Frame::TstrX) => {
+                    return false;
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, frame: Frame) -> Result<(), Error> {
+        if self.frames.len() < self.upper {
+            self.frames.push(frame);
+            Ok(())
+        } else {
+            Err(Error::InsufficientStackSize)
+        }
+    }
+}
+
+/// Kinds of terminal symbols that can start a value
+fn value_kinds() -> Vec<Kind> {
+    vec![
+        Kind::Array,
+        Kind::ArrayX,
+        Kind::Bstr,
+        Kind::BstrX,
+        Kind::Float,
+        Kind::Map,
+        Kind::MapX,
+        Kind::Nint,
+        Kind::Simple,
+        Kind::Tag,
+        Kind::Tstr,
+        Kind::TstrX,
+        Kind::Uint,
+    ]
+}
+
+#[cfg(test)]
+mod tests;
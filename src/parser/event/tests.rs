@@ -0,0 +1,263 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+
+/// Records events in a notation similar to diagnostic notation.
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+}
+
+impl Handler for Recorder {
+    fn uint(&mut self, n: u64) {
+        self.events.push(format!("{n}"));
+    }
+    fn nint(&mut self, n: u64) {
+        self.events.push(format!("-{}", n as u128 + 1));
+    }
+    fn float(&mut self, _: Precision, n: u64) {
+        self.events.push(format!("float({n:#x})"));
+    }
+    fn simple(&mut self, n: u8) {
+        self.events.push(format!("simple({n})"));
+    }
+    fn tag(&mut self, n: u64) {
+        self.events.push(format!("{n}("));
+    }
+    fn start_bstr(&mut self) {
+        self.events.push("h'".into());
+    }
+    fn bstr_chunk(&mut self, bytes: &[u8]) {
+        self.events.push(format!("{bytes:02x?}"));
+    }
+    fn end_bstr(&mut self) {
+        self.events.push("'".into());
+    }
+    fn start_tstr(&mut self) {
+        self.events.push("\"".into());
+    }
+    fn tstr_chunk(&mut self, text: &str) {
+        self.events.push(text.into());
+    }
+    fn end_tstr(&mut self) {
+        self.events.push("\"".into());
+    }
+    fn start_array(&mut self, len: Option<u64>) {
+        self.events.push(format!("[{len:?}"));
+    }
+    fn end_array(&mut self) {
+        self.events.push("]".into());
+    }
+    fn start_map(&mut self, len: Option<u64>) {
+        self.events.push(format!("{{{len:?}"));
+    }
+    fn map_key(&mut self) {
+        self.events.push("k".into());
+    }
+    fn map_value(&mut self) {
+        self.events.push("v".into());
+    }
+    fn end_map(&mut self) {
+        self.events.push("}".into());
+    }
+}
+
+fn run(driver: &mut Driver<Recorder>, terms: Vec<Term>) -> Vec<bool> {
+    terms
+        .into_iter()
+        .map(|term| driver.consume(term).unwrap())
+        .collect()
+}
+
+#[test]
+fn scalars() {
+    let mut driver = Driver::sequence(Recorder::default());
+    let terms = vec![
+        Term::Uint(1),
+        Term::Nint(0),
+        Term::Float(Precision::Half, 0x3c00),
+        Term::Simple(20),
+    ];
+    assert_eq!(vec![true; 4], run(&mut driver, terms));
+    assert_eq!(
+        vec!["1", "-1", "float(0x3c00)", "simple(20)"],
+        driver.into_handler().events
+    );
+}
+
+#[test]
+fn strings() {
+    let mut driver = Driver::sequence(Recorder::default());
+    let terms = vec![
+        Term::Bstr(vec![0x01]),
+        Term::BstrX,
+        Term::Bstr(vec![0x02]),
+        Term::Bstr(vec![0x03]),
+        Term::Break,
+        Term::TstrX,
+        Term::Tstr(b"ab".to_vec()),
+        Term::Break,
+    ];
+    let expected = vec![true, false, false, false, true, false, false, true];
+    assert_eq!(expected, run(&mut driver, terms));
+    assert_eq!(
+        vec![
+            "h'", "[01]", "'", "h'", "[02]", "[03]", "'", "\"", "ab", "\""
+        ],
+        driver.into_handler().events
+    );
+}
+
+#[test]
+fn nested() {
+    let mut driver = Driver::new(Recorder::default());
+    let terms = vec![
+        Term::Array(3),
+        Term::Tag(1),
+        Term::Uint(2),
+        Term::MapX,
+        Term::Uint(3),
+        Term::Array(0),
+        Term::Break,
+        Term::Map(1),
+        Term::Uint(4),
+        Term::ArrayX,
+        Term::Break,
+    ];
+    let mut expected = vec![false; 10];
+    expected.push(true);
+    assert_eq!(expected, run(&mut driver, terms));
+    assert_eq!(
+        vec![
+            "[Some(3)", "1(", "2", "{None", "k", "3", "v", "[Some(0)", "]",
+            "}", "{Some(1)", "k", "4", "v", "[None", "]", "}", "]"
+        ],
+        driver.into_handler().events
+    );
+}
+
+#[test]
+fn trailing_input() {
+    let mut driver = Driver::new(Recorder::default());
+    assert!(driver.consume(Term::Uint(0)).unwrap());
+    let result = driver.consume(Term::Uint(1));
+    assert!(matches!(result, Err(Error::TrailingInput)));
+    driver.reset();
+    assert!(driver.consume(Term::Uint(1)).unwrap());
+}
+
+#[test]
+fn unexpected_break() {
+    let mut driver = Driver::new(Recorder::default());
+    assert!(!driver.consume(Term::Array(1)).unwrap());
+    let result = driver.consume(Term::Break);
+    assert!(matches!(result, Err(Error::UnexpectedT(_, Term::Break))));
+    assert!(driver.consume(Term::Uint(1)).unwrap());
+}
+
+#[test]
+fn unexpected_map_break() {
+    let mut driver = Driver::new(Recorder::default());
+    assert!(!driver.consume(Term::MapX).unwrap());
+    assert!(!driver.consume(Term::Uint(1)).unwrap());
+    let result = driver.consume(Term::Break);
+    assert!(matches!(result, Err(Error::UnexpectedT(_, Term::Break))));
+}
+
+#[test]
+fn unexpected_chunk() {
+    let mut driver = Driver::new(Recorder::default());
+    assert!(!driver.consume(Term::BstrX).unwrap());
+    let result = driver.consume(Term::Tstr(vec![]));
+    assert!(matches!(result, Err(Error::UnexpectedT(_, _))));
+}
+
+#[test]
+fn array_large() {
+    let mut driver = Driver::new(Recorder::default());
+    let count = 20_000;
+    assert!(!driver.consume(Term::Array(count)).unwrap());
+    for _ in 1..count {
+        assert!(!driver.consume(Term::Uint(0)).unwrap());
+    }
+    assert!(driver.consume(Term::Uint(0)).unwrap());
+}
+
+#[test]
+fn array_excessive_depth() {
+    let mut driver = Driver::new(Recorder::default());
+    let result =
+        (0..20_000).try_for_each(|_| driver.consume(Term::ArrayX).map(|_| ()));
+    assert!(matches!(result, Err(Error::InsufficientStackSize)));
+}
+
+#[test]
+fn tstr_invalid_utf8() {
+    let mut driver = Driver::new(Recorder::default());
+    assert!(!driver.consume(Term::TstrX).unwrap());
+    let result = driver.consume(Term::Tstr(vec![0xc3]));
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+    assert_eq!(vec!["\""], driver.into_handler().events);
+}
+
+#[test]
+fn nested_chunks() {
+    let mut driver = Driver::sequence(Recorder::default());
+    let terms = vec![
+        Term::BstrX,
+        Term::Bstr(vec![0x01]),
+        Term::BstrX,
+        Term::Bstr(vec![0x02]),
+        Term::Break,
+        Term::Break,
+        Term::TstrX,
+        Term::TstrX,
+        Term::Break,
+        Term::Tstr(b"a".to_vec()),
+        Term::Break,
+    ];
+    let mut expected = vec![false; 5];
+    expected.push(true);
+    expected.extend([false; 4]);
+    expected.push(true);
+    assert_eq!(expected, run(&mut driver, terms));
+    assert_eq!(
+        vec!["h'", "[01]", "[02]", "'", "\"", "a", "\""],
+        driver.into_handler().events
+    );
+}
+
+#[test]
+fn error_without_events() {
+    let mut driver = Driver::new(Recorder::default());
+    assert!(!driver.consume(Term::Map(1)).unwrap());
+    let result = driver.consume(Term::Tstr(vec![0xc3]));
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+    assert_eq!(vec!["{Some(1)"], driver.handler().events);
+
+    let mut driver = Driver::new(Recorder::default());
+    driver.upper = 1;
+    assert!(!driver.consume(Term::MapX).unwrap());
+    let result = driver.consume(Term::Array(1));
+    assert!(matches!(result, Err(Error::InsufficientStackSize)));
+    assert_eq!(vec!["{None"], driver.handler().events);
+    assert!(!driver.consume(Term::Uint(1)).unwrap());
+    assert_eq!(vec!["{None", "k", "1"], driver.handler().events);
+}
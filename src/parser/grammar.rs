@@ -79,9 +79,10 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 //! ```
 //!
 //! In parser speak, when the parser processes the terminal symbol `%array(n)`,
-//! it will push the non-terminal `value` onto the parser stack exactly _n_
-//! times -- meaning that it expects to find `%array(n)` followed by _n_
-//! `value`s.
+//! it expects to find `%array(n)` followed by _n_ `value`s. Rather than
+//! pushing the non-terminal `value` onto the parser stack _n_ times, the LL
+//! parser keeps count of the `value`s still expected, so that its stack usage
+//! depends on the nesting depth only.
 //!
 //! Similarly for `%map(n)` except that the parser expect _2n_ `value`s.
 //!
@@ -17,85 +17,389 @@ You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 use super::*;
+use super::path::Tracker;
 use crate::value::Value;
+use action::Action;
 use context_stack::ContextStack;
 use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
 use value_stack::ValueStack;
 
+mod action;
 mod context_stack;
 mod value_stack;
 
 /// CBOR parser.
+///
+/// The parser builds its values with a [Builder], by default with [Options].
+/// The parser is `Send` and `Clone`, so that a partially parsed value can be
+/// handed over to another thread, or parsed on in different ways.
 #[derive(Debug, Default)]
-pub struct Parser {
-    inner: State,
+pub struct Parser<B: Builder = Options> {
+    inner: State<B>,
+    sequence: bool,
+    path: Tracker,
+    recovery: bool,
+    errors: Vec<Error>,
+    marks: Vec<Mark>,
+    skip: Option<usize>,
+}
+
+impl<B: Builder + Clone> Clone for Parser<B>
+where
+    B::Output: Clone,
+{
+    fn clone(&self) -> Self {
+        Parser {
+            inner: self.inner.clone(),
+            sequence: self.sequence,
+            path: self.path.clone(),
+            recovery: self.recovery,
+            errors: self.errors.clone(),
+            marks: self.marks.clone(),
+            skip: self.skip,
+        }
+    }
+}
+
+/// Placeholder for a data item that failed to parse in recovery mode
+const UNDEFINED: u8 = 23;
+
+/// Open array or map
+///
+/// The mark records the lengths of the context stack and the value stack, and
+/// the depth of the path, right after the container was opened. The context
+/// and the value of the container remain at the top of the respective prefix
+/// until the container is complete.
+#[derive(Clone, Copy, Debug)]
+struct Mark {
+    cxt_len: usize,
+    val_len: usize,
+    depth: usize,
 }
 
-impl super::Parser for Parser {
-    fn consume(&mut self, term: Term) -> Result<Option<Value>, Error> {
-        self.inner.do_consume(term)?;
+impl<B: Builder> super::Parser for Parser<B> {
+    type Output = B::Output;
+
+    fn consume(&mut self, term: Term) -> Result<Option<B::Output>, Error> {
+        let update = self.path.update(&term);
+        if let Some(depth) = self.skip {
+            // Skip the remaining tokens of the container that failed to parse
+            self.path.apply(update);
+            if self.path.depth() >= depth {
+                return Ok(None);
+            }
+            self.skip = None;
+            return match self.inner.do_flush() {
+                Ok(()) => self.produce(),
+                Err(error) => {
+                    let error = self.path.wrap(error);
+                    self.recover(error, false)
+                }
+            };
+        }
+        match self.inner.do_consume(term) {
+            Ok(()) => {}
+            Err(error) if self.recovery && is_recoverable(&error) => {
+                let path = self.path.path();
+                let in_item = matches!(path.0.last(), Some(Segment::Item(..)));
+                let error = self.path.wrap(error);
+                self.path.apply(update);
+                return self.recover(error, in_item);
+            }
+            Err(error) => return Err(self.path.wrap(error)),
+        }
+        let opens_container = update.opens_container();
+        self.path.apply(update);
+        if opens_container {
+            self.marks.push(Mark {
+                cxt_len: self.inner.cxt_stack.len(),
+                val_len: self.inner.val_stack.len(),
+                depth: self.path.depth(),
+            });
+        }
+        self.produce()
+    }
+
+    fn reset(&mut self) {
+        self.inner.cxt_stack = ContextStack::cbor();
+        self.inner.val_stack.truncate(0);
+        self.path.reset();
+        self.errors.clear();
+        self.marks.clear();
+        self.skip = None;
+    }
+
+    fn set_budget(&mut self, budget: Budget) {
+        self.inner.val_stack.set_budget(budget);
+    }
+}
+
+impl<B: Builder> Parser<B> {
+    /// Returns the completed value, if any.
+    fn produce(&mut self) -> Result<Option<B::Output>, Error> {
+        let depth = self.path.depth();
+        while self.marks.last().is_some_and(|mark| mark.depth > depth) {
+            self.marks.pop();
+        }
         if self.inner.cxt_stack.len() > 0 {
             Ok(None)
         } else if self.inner.val_stack.len() > 1 {
             Err(Error::Internal)
-        } else if let Some(value) = self.inner.val_stack.pop() {
+        } else if let Some(Slot::Item(value)) = self.inner.val_stack.pop() {
+            if self.sequence {
+                // Re-arm the parser for the next value of the sequence
+                self.inner.cxt_stack = ContextStack::cbor();
+            }
             Ok(Some(value))
         } else {
             Err(Error::Invalid)
         }
     }
+
+    /// Records `error`, and replaces the data item that failed to parse with
+    /// a placeholder.
+    ///
+    /// The placeholder completes the innermost array or map, and the parser
+    /// skips any of its remaining tokens. At the top level, the placeholder
+    /// takes the place of the value. If the item is the item of a map entry,
+    /// `in_item` is `true`.
+    fn recover(
+        &mut self,
+        error: Error,
+        in_item: bool,
+    ) -> Result<Option<B::Output>, Error> {
+        let mut error = error;
+        let mut in_item = Some(in_item);
+        loop {
+            self.errors.push(error);
+            // A container closed by the failing token is no longer open.
+            let cxt_len = self.inner.cxt_stack.len();
+            while self.marks.last().is_some_and(|mark| mark.cxt_len > cxt_len)
+            {
+                self.marks.pop();
+            }
+            let depth = match self.marks.pop() {
+                None => {
+                    self.inner.cxt_stack.truncate(0);
+                    self.inner.val_stack.truncate(0);
+                    let undefined = self.inner.builder.simple(UNDEFINED)?;
+                    self.inner.val_stack.push(Slot::Item(undefined))?;
+                    1
+                }
+                Some(mark) => {
+                    // Once a nested container is complete, the only value
+                    // left above a map is the label of the entry.
+                    let in_item = in_item.take().unwrap_or(
+                        self.inner.val_stack.len() > mark.val_len,
+                    );
+                    self.close_container(mark, in_item)?;
+                    mark.depth
+                }
+            };
+            if self.path.depth() >= depth {
+                self.skip = Some(depth);
+                return Ok(None);
+            }
+            match self.inner.do_flush() {
+                Ok(()) => return self.produce(),
+                Err(next) => error = self.path.wrap(next),
+            }
+        }
+    }
+
+    /// Discards the partial item of the container at `mark`, appends a
+    /// placeholder instead, and completes the container.
+    fn close_container(
+        &mut self,
+        mark: Mark,
+        in_item: bool,
+    ) -> Result<(), Error> {
+        let State {
+            cxt_stack,
+            val_stack,
+            builder,
+            ..
+        } = &mut self.inner;
+        // The label of a map entry is retained if it is complete.
+        let label = if in_item && val_stack.len() > mark.val_len {
+            val_stack.truncate(mark.val_len + 1);
+            match val_stack.pop() {
+                Some(Slot::Item(label)) => Some(label),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        cxt_stack.truncate(mark.cxt_len - 1);
+        val_stack.truncate(mark.val_len);
+        let container = match val_stack.pop() {
+            Some(Slot::Array(mut values)) => {
+                values.push(builder.simple(UNDEFINED)?);
+                Slot::Array(values)
+            }
+            Some(Slot::Map(mut map)) => {
+                let label = match label {
+                    Some(label) => label,
+                    None => builder.simple(UNDEFINED)?,
+                };
+                map.entries.push((label, builder.simple(UNDEFINED)?));
+                Slot::Map(map)
+            }
+            _ => Slot::Item(builder.simple(UNDEFINED)?),
+        };
+        val_stack.push(container)
+    }
 }
 
-impl Parser {
+/// Returns `true` if the parser can recover from `error` by replacing the
+/// data item with a placeholder.
+fn is_recoverable(error: &Error) -> bool {
+    !matches!(
+        error,
+        Error::InsufficientStackSize
+            | Error::BudgetExceeded(_)
+            | Error::Internal
+            | Error::TrailingInput
+            | Error::Todo(_)
+    )
+}
 
+impl Parser {
     /// Returns a parser for a single CBOR value
     pub fn cbor() -> Parser {
+        Parser::with_builder(Options::default())
+    }
+
+    /// Returns a parser for a CBOR sequence (RFC 8742)
+    ///
+    /// The parser produces one value per data item of the sequence, and
+    /// re-arms itself after each value.
+    pub fn sequence() -> Parser {
+        Parser::sequence_with_builder(Options::default())
+    }
+
+    /// Sets the decoding options of the parser
+    pub fn set_options(&mut self, options: Options) {
+        self.inner.builder = options;
+    }
+}
+
+impl<B: Builder> Parser<B> {
+    /// Returns a parser for a single CBOR value that builds its value with
+    /// `builder`
+    pub fn with_builder(builder: B) -> Self {
         Parser {
             inner: State {
                 cxt_stack: ContextStack::cbor(),
                 val_stack: ValueStack::default(),
                 parse_visitor: None,
+                builder,
             },
+            sequence: false,
+            path: Tracker::default(),
+            recovery: false,
+            errors: Vec::new(),
+            marks: Vec::new(),
+            skip: None,
         }
     }
 
+    /// Returns a parser for a CBOR sequence that builds its values with
+    /// `builder`
+    pub fn sequence_with_builder(builder: B) -> Self {
+        Parser {
+            sequence: true,
+            ..Parser::with_builder(builder)
+        }
+    }
+
+    /// Returns a view of the partially parsed value.
+    pub fn progress(&self) -> Progress<'_, B::Output> {
+        Progress::new(&self.path, self.inner.val_stack.slots())
+    }
+
+    /// Returns the builder of the parser
+    pub fn builder(&self) -> &B {
+        &self.inner.builder
+    }
+
+    /// Enables or disables recovery mode
+    ///
+    /// In recovery mode, the parser does not fail on a malformed or invalid
+    /// data item. It records the error instead, inserts `undefined` in place
+    /// of the item, and skips to the end of the enclosing array or map. The
+    /// parser then produces a best-effort value, and the recorded errors are
+    /// available from [Parser::take_errors].
+    pub fn set_recovery(&mut self, recovery: bool) {
+        self.recovery = recovery;
+    }
+
+    /// Returns the errors recorded in recovery mode, and clears them.
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+
     /// Attaches a visitor to the parser
     pub fn set_visitor<Visitor>(&mut self, visitor: Visitor)
     where
-        Visitor: ParserVisitor + 'static,
+        Visitor: ParserVisitor<B::Output> + 'static,
     {
-        self.inner.parse_visitor = Some(Box::new(visitor));
+        self.inner.parse_visitor = Some(Arc::new(Mutex::new(visitor)));
     }
 
     /// Initialises the parser.
-    pub fn init(&self) -> Result<Option<Value>, Error> {
-        if let Some(v) = &self.inner.parse_visitor {
-            v.on_init(&self.inner.cxt_stack, &self.inner.val_stack);
-        }
+    pub fn init(&self) -> Result<Option<B::Output>, Error> {
+        visit(&self.inner.parse_visitor, |v| {
+            v.on_init(&self.inner.cxt_stack, &self.inner.val_stack)
+        });
         Ok(None)
     }
 }
 
-#[derive(Default)]
-struct State {
+#[derive(Clone)]
+struct State<B: Builder> {
     cxt_stack: ContextStack,
-    val_stack: ValueStack,
-    parse_visitor: Option<Box<dyn ParserVisitor>>,
+    val_stack: ValueStack<B::Output>,
+    parse_visitor: Option<SharedVisitor<B::Output>>,
+    builder: B,
+}
+
+impl<B: Builder + Default> Default for State<B> {
+    fn default() -> Self {
+        State {
+            cxt_stack: ContextStack::default(),
+            val_stack: ValueStack::default(),
+            parse_visitor: None,
+            builder: B::default(),
+        }
+    }
 }
 
-impl State {
+impl<B: Builder> State<B> {
     #[inline]
     fn do_consume(&mut self, term: Term) -> Result<(), Error> {
         do_consume(
             &self.parse_visitor,
+            &mut self.builder,
             &mut self.val_stack,
             &mut self.cxt_stack,
             term,
         )
     }
+
+    #[inline]
+    fn do_flush(&mut self) -> Result<(), Error> {
+        do_flush(
+            &self.parse_visitor,
+            &mut self.builder,
+            &mut self.cxt_stack,
+            &mut self.val_stack,
+        )
+    }
 }
 
-impl fmt::Debug for State {
+impl<B: Builder> fmt::Debug for State<B> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("State")
             .field("cxt_stack", &self.cxt_stack)
@@ -109,21 +413,26 @@ impl fmt::Debug for State {
 /// The parsing context denotes the value that is currently being constructed
 /// according to the underlying grammar.
 #[allow(private_interfaces)]
+#[derive(Clone)]
 pub enum Context {
     /// A parsing action for transforming the value stack.
-    Action(String, Box<dyn Fn(&mut ContextStack, &mut ValueStack)>),
+    Action(Action),
     /// The parser recognised this terminal symbol
     TerminalSymbol(Kind),
     /// The parser recognised this non-terminal symbol
     NonTerminalSymbol(NonTerm),
+    /// The parser expects this many more elements of a definite-length array
+    ArrayItems(u64),
+    /// The parser expects this many more entries of a definite-length map
+    MapItems(u64),
 }
 
 impl fmt::Debug for Context {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Context::*;
         match self {
-            Action(name, _) => {
-                write!(f, "{name}()")
+            Action(action) => {
+                write!(f, "{action}()")
             }
             TerminalSymbol(kind) => {
                 let kind_str = format!("{kind:?}").to_lowercase();
@@ -132,387 +441,376 @@ impl fmt::Debug for Context {
             NonTerminalSymbol(non_term) => {
                 write!(f, "<{non_term:?}>")
             }
+            ArrayItems(count) => {
+                write!(f, "{{{count}}}<Value>")
+            }
+            MapItems(count) => {
+                write!(f, "{{{count}}}(<Value> <Value>)")
+            }
         }
     }
 }
 
 /// The `ParserVisitor` trait allows for interested clients to be informed when
 /// the parser transitions from one state to another.
-pub trait ParserVisitor {
+///
+/// The parser may be sent to another thread, so visitors must be `Send`.
+/// Clones of a parser share its visitor, and take turns to inform it.
+pub trait ParserVisitor<T = Value>: Send {
     /// Invoked when the parser is initialised.
-    fn on_init(&self, _: &ContextStack, _: &ValueStack) {}
+    fn on_init(&self, _: &ContextStack, _: &ValueStack<T>) {}
     /// Invoked when the parser is transitioning as it consumes tokens.
-    fn on_input(&self, _: &ContextStack, _: &ValueStack, _: &Term) {}
+    fn on_input(&self, _: &ContextStack, _: &ValueStack<T>, _: &Term) {}
     /// Invoked when the parser is transitioning through intermediate states.
-    fn on_flush(&self, _: &ContextStack, _: &ValueStack) {}
-    /// Invoked when the parser executes a named action.
-    fn on_action(&self, _: &ContextStack, _: &ValueStack, _: &String) {}
+    fn on_flush(&self, _: &ContextStack, _: &ValueStack<T>) {}
+    /// Invoked when the parser executes an action.
+    fn on_action(&self, _: &ContextStack, _: &ValueStack<T>, _: &Action) {}
 }
 
-/// Runs the parser until it can no longer apply productions.
-fn do_flush(
-    parse_visitor: &Option<Box<dyn ParserVisitor>>,
-    cxt_stack: &mut ContextStack,
-    val_stack: &mut ValueStack,
-) -> Result<(), Error> {
-    if let Some(visitor) = parse_visitor {
-        visitor.on_flush(cxt_stack, val_stack);
-    }
-    if let Some(context) = cxt_stack.pop() {
-        do_flush_(parse_visitor, val_stack, cxt_stack, context)
-    } else {
-        Ok(())
+/// Visitor shared by the clones of a parser
+type SharedVisitor<T> = Arc<Mutex<dyn ParserVisitor<T>>>;
+
+/// Informs the visitor, if any, with `notify`.
+fn visit<T>(
+    visitor: &Option<SharedVisitor<T>>,
+    notify: impl FnOnce(&dyn ParserVisitor<T>),
+) {
+    if let Some(visitor) = visitor {
+        notify(&*visitor.lock().unwrap_or_else(PoisonError::into_inner));
     }
 }
 
-fn do_flush_(
-    parse_visitor: &Option<Box<dyn ParserVisitor>>,
-    val_stack: &mut ValueStack,
+/// Runs the parser until it can no longer apply productions.
+fn do_flush<B: Builder>(
+    parse_visitor: &Option<SharedVisitor<B::Output>>,
+    builder: &mut B,
     cxt_stack: &mut ContextStack,
-    context: Context,
+    val_stack: &mut ValueStack<B::Output>,
 ) -> Result<(), Error> {
     use Context::*;
-    match context {
-        Action(name, action) => {
-            if let Some(visitor) = &parse_visitor {
-                visitor.on_action(&cxt_stack, &val_stack, &name);
-            }
-            action(cxt_stack, val_stack);
-            do_flush(parse_visitor, cxt_stack, val_stack)
-        }
-        TerminalSymbol(kind) => {
-            cxt_stack.push_kind(kind)
-        }
-        NonTerminalSymbol(non_term) => {
-            cxt_stack.push_non_term(non_term)
+    loop {
+        visit(parse_visitor, |v| v.on_flush(cxt_stack, val_stack));
+        match cxt_stack.pop() {
+            None => return Ok(()),
+            Some(Action(action)) => {
+                visit(parse_visitor, |v| {
+                    v.on_action(cxt_stack, val_stack, &action)
+                });
+                action.apply(val_stack, builder)?;
+            }
+            // A definite-length container is complete once no more items are
+            // expected.
+            Some(ArrayItems(0) | MapItems(0)) => {}
+            Some(context) => return cxt_stack.push(context),
         }
     }
 }
 
-fn do_consume(
-    parse_visitor: &Option<Box<dyn ParserVisitor>>,
-    val_stack: &mut ValueStack,
+/// Runs the parser until it has consumed `input`.
+fn do_consume<B: Builder>(
+    parse_visitor: &Option<SharedVisitor<B::Output>>,
+    builder: &mut B,
+    val_stack: &mut ValueStack<B::Output>,
     cxt_stack: &mut ContextStack,
     input: Term,
-) -> Result<(), Error> {
-    if let Some(visitor) = parse_visitor {
-        visitor.on_input(cxt_stack, val_stack, &input);
-    }
-    if let Some(context) = cxt_stack.pop() {
-        do_consume_(parse_visitor, val_stack, cxt_stack, context, input)
-    } else {
-        Err(Error::TrailingInput)
-    }
-}
-
-fn do_consume_(
-    parse_visitor: &Option<Box<dyn ParserVisitor>>,
-    val_stack: &mut ValueStack,
-    cxt_stack: &mut ContextStack,
-    context: Context,
-    input: Term,
 ) -> Result<(), Error> {
     use Context::*;
-    match context {
-        Action(name, action) => {
-            if let Some(visitor) = &parse_visitor {
-                visitor.on_action(&cxt_stack, &val_stack, &name);
-            }
-            action(cxt_stack, val_stack);
-            do_consume(parse_visitor, val_stack, cxt_stack, input)
-        }
-
-        TerminalSymbol(kind) if kind == input.kind() => {
-            // Create value from consumed input. If conversion fails, ignore
-            // the error, but consume the token.
-            if let Ok(value) = Value::try_from(input) {
-                val_stack.push(value);
-            }
-            do_flush(parse_visitor, cxt_stack, val_stack)
-        }
-
-        TerminalSymbol(kind) => {
-            // Put context back on stack, and complain about unexpected input
-            cxt_stack.push_kind(kind)?;
-            Err(Error::UnexpectedT(vec![kind], input))
-        }
-
-        NonTerminalSymbol(NonTerm::Value) => match input {
-            Term::Break => {
+    loop {
+        visit(parse_visitor, |v| v.on_input(cxt_stack, val_stack, &input));
+        let Some(context) = cxt_stack.pop() else {
+            return Err(Error::TrailingInput);
+        };
+        match context {
+            Action(action) => {
+                visit(parse_visitor, |v| {
+                    v.on_action(cxt_stack, val_stack, &action)
+                });
+                action.apply(val_stack, builder)?;
+            }
+
+            // Production: {0}<VALUE> = %empty
+            ArrayItems(0) | MapItems(0) => {}
+
+            // Production: {n}<VALUE> = <VALUE> {n-1}<VALUE>
+            ArrayItems(n) => {
+                cxt_stack.push(ArrayItems(n - 1))?;
+                cxt_stack.push_action(action::Action::ArrayPush)?;
                 cxt_stack.push_non_term(NonTerm::Value)?;
-                Err(Error::UnexpectedT(
-                    vec![
-                        Kind::Array,
-                        Kind::ArrayX,
-                        Kind::Bstr,
-                        Kind::BstrX,
-                        Kind::Float,
-                        Kind::Map,
-                        Kind::MapX,
-                        Kind::Nint,
-                        Kind::Simple,
-                        Kind::Tag,
-                        Kind::Tstr,
-                        Kind::TstrX,
-                        Kind::Uint,
-                    ],
-                    input,
-                ))
-            }
-            // Production: value = %uint / %nint / %simple / %float
-            Term::Uint(_)
-            | Term::Nint(_)
-            | Term::Simple(_)
-            | Term::Float(_) => {
-                cxt_stack.push_kind(input.kind())?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: value = bstr
-            Term::Bstr(_) | Term::BstrX => {
-                cxt_stack.push_non_term(NonTerm::Bstr)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: value = tstr
-            Term::Tstr(_) | Term::TstrX => {
-                cxt_stack.push_non_term(NonTerm::Tstr)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: value = array
-            Term::Array(_) | Term::ArrayX => {
-                cxt_stack.push_non_term(NonTerm::Array)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: value = map
-            Term::Map(_) | Term::MapX => {
-                cxt_stack.push_non_term(NonTerm::Map)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
             }
-            // Production: value = tag
-            Term::Tag(_) => {
-                cxt_stack.push_non_term(NonTerm::Tag)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-        },
-
-        NonTerminalSymbol(NonTerm::Array) => match input {
-            // Production: array = %array(n) value ... value ; n times
-            Term::Array(n) => {
-                cxt_stack.push_action("collect_array", array_collect(n))?;
-                cxt_stack.push_multiple_non_term(NonTerm::Value, n.try_into().unwrap())?;
-                cxt_stack.push_kind(Kind::Array)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: array = %arrayx arrayxseq
-            Term::ArrayX => {
-                cxt_stack.push_non_term(NonTerm::ArrayXSeq)?;
-                cxt_stack.push_kind(Kind::ArrayX)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Error
-            _ => {
-                cxt_stack.push_non_term(NonTerm::Array)?;
-                Err(Error::UnexpectedT(vec![Kind::Array, Kind::ArrayX], input))
-            }
-        },
 
-        NonTerminalSymbol(NonTerm::ArrayXSeq) => match input {
-            // Production: arrayxseq = %break
-            Term::Break => {
-                cxt_stack.push_kind(Kind::Break)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: arrayxseq = value arrayxseq
-            _ => {
-                cxt_stack.push_non_term(NonTerm::ArrayXSeq)?;
-                cxt_stack.push_action("array_push", array_push())?;
-                cxt_stack.push_non_term(NonTerm::Value)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
+            // Production: {n}(<VALUE> <VALUE>) = <VALUE> <VALUE> {n-1}(<VALUE> <VALUE>)
+            MapItems(n) => {
+                cxt_stack.push(MapItems(n - 1))?;
+                cxt_stack.push_action(action::Action::MapPush)?;
+                cxt_stack.push_non_term(NonTerm::Value)?; // item
+                cxt_stack.push_non_term(NonTerm::Value)?; // label
             }
-        },
 
-        NonTerminalSymbol(NonTerm::Bstr) => match input {
-            // Production: bstr = %bstr(payload)
-            Term::Bstr(_) => {
-                cxt_stack.push_kind(Kind::Bstr)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
+            TerminalSymbol(kind) if kind == input.kind() => {
+                // Create value from consumed input. Scalars are built right
+                // away. Strings and containers start out with the first chunk,
+                // or empty, and collect their chunks or items as they are
+                // parsed; they are built once complete.
+                let slot = match input {
+                    Term::Uint(n) => Slot::Item(builder.uint(n)?),
+                    Term::Nint(n) => Slot::Item(builder.nint(n)?),
+                    Term::Float(precision, bits) => {
+                        Slot::Item(builder.float(precision, bits)?)
+                    }
+                    Term::Simple(n) => Slot::Item(builder.simple(n)?),
+                    Term::Bstr(bytes) => Slot::Bytes(bytes),
+                    Term::BstrX => Slot::Bytes(Vec::new()),
+                    Term::Tstr(bytes) => match String::from_utf8(bytes) {
+                        Ok(text) => Slot::Text(text),
+                        Err(error) => {
+                            cxt_stack.push_kind(kind)?;
+                            return Err(error.into());
+                        }
+                    },
+                    Term::TstrX => Slot::Text(String::new()),
+                    Term::Array(_) | Term::ArrayX => Slot::Array(Vec::new()),
+                    Term::Map(_) | Term::MapX => Entries::slot(),
+                    Term::Tag(_) | Term::Break => {
+                        return do_flush(
+                            parse_visitor,
+                            builder,
+                            cxt_stack,
+                            val_stack,
+                        );
+                    }
+                };
+                val_stack.push(slot)?;
+                return do_flush(parse_visitor, builder, cxt_stack, val_stack);
             }
-            // Production: bstr = %bstrx bstrxseq
-            Term::BstrX => {
-                cxt_stack.push_non_term(NonTerm::BstrXSeq)?;
-                cxt_stack.push_kind(Kind::BstrX)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Error
-            _ => {
-                cxt_stack.push_non_term(NonTerm::Bstr)?;
-                Err(Error::UnexpectedT(vec![Kind::Bstr, Kind::BstrX], input))
+
+            TerminalSymbol(kind) => {
+                // Put context back on stack, and complain about unexpected input
+                cxt_stack.push_kind(kind)?;
+                return Err(Error::UnexpectedT(vec![kind], input));
             }
-        },
 
-        NonTerminalSymbol(NonTerm::BstrXSeq) => {
-            match input {
-                // Production: bstrxseq = %break
+            NonTerminalSymbol(NonTerm::Value) => match input {
                 Term::Break => {
-                    cxt_stack.push_kind(Kind::Break)?;
-                    do_consume(parse_visitor, val_stack, cxt_stack, input)
+                    cxt_stack.push_non_term(NonTerm::Value)?;
+                    return Err(Error::UnexpectedT(
+                        vec![
+                            Kind::Array,
+                            Kind::ArrayX,
+                            Kind::Bstr,
+                            Kind::BstrX,
+                            Kind::Float,
+                            Kind::Map,
+                            Kind::MapX,
+                            Kind::Nint,
+                            Kind::Simple,
+                            Kind::Tag,
+                            Kind::Tstr,
+                            Kind::TstrX,
+                            Kind::Uint,
+                        ],
+                        input,
+                    ));
                 }
-                // Production: bstrxseq = bstr bstrxseq
+                // Production: value = %uint / %nint / %simple / %float
+                Term::Uint(_)
+                | Term::Nint(_)
+                | Term::Simple(_)
+                | Term::Float(..) => {
+                    cxt_stack.push_kind(input.kind())?;
+                }
+                // Production: value = bstr
                 Term::Bstr(_) | Term::BstrX => {
-                    cxt_stack.push_non_term(NonTerm::BstrXSeq)?;
-                    cxt_stack.push_action("bstr_append", bstr_append())?;
+                    cxt_stack.push_action(action::Action::Build)?;
                     cxt_stack.push_non_term(NonTerm::Bstr)?;
-                    do_consume(parse_visitor, val_stack, cxt_stack, input)
+                }
+                // Production: value = tstr
+                Term::Tstr(_) | Term::TstrX => {
+                    cxt_stack.push_action(action::Action::Build)?;
+                    cxt_stack.push_non_term(NonTerm::Tstr)?;
+                }
+                // Production: value = array
+                Term::Array(_) | Term::ArrayX => {
+                    cxt_stack.push_action(action::Action::Build)?;
+                    cxt_stack.push_non_term(NonTerm::Array)?;
+                }
+                // Production: value = map
+                Term::Map(_) | Term::MapX => {
+                    cxt_stack.push_action(action::Action::Build)?;
+                    cxt_stack.push_non_term(NonTerm::Map)?;
+                }
+                // Production: value = tag
+                Term::Tag(_) => {
+                    cxt_stack.push_non_term(NonTerm::Tag)?;
+                }
+            },
+
+            NonTerminalSymbol(NonTerm::Array) => match input {
+                // Production: array = %array(n) {n}value
+                Term::Array(n) => {
+                    cxt_stack.push(ArrayItems(n))?;
+                    cxt_stack.push_kind(Kind::Array)?;
+                }
+                // Production: array = %arrayx arrayxseq
+                Term::ArrayX => {
+                    cxt_stack.push_non_term(NonTerm::ArrayXSeq)?;
+                    cxt_stack.push_kind(Kind::ArrayX)?;
                 }
                 // Error
                 _ => {
-                    cxt_stack.push_non_term(NonTerm::BstrXSeq)?;
-                    Err(Error::UnexpectedT(
-                        vec![Kind::Break, Kind::Bstr, Kind::BstrX],
+                    cxt_stack.push_non_term(NonTerm::Array)?;
+                    return Err(Error::UnexpectedT(
+                        vec![Kind::Array, Kind::ArrayX],
                         input,
-                    ))
+                    ));
                 }
-            }
-        }
-
-        NonTerminalSymbol(NonTerm::Map) => match input {
-            // Production: map = %map(n) {n}value
-            Term::Map(n) => {
-                cxt_stack.push_action("map_collect", map_collect(n))?;
-                cxt_stack.push_multiple_non_term(NonTerm::Value, (2 * n).try_into().unwrap())?;
-                cxt_stack.push_kind(Kind::Map)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: map = %mapx mapxseq
-            Term::MapX => {
-                cxt_stack.push_non_term(NonTerm::MapXSeq)?;
-                cxt_stack.push_kind(Kind::MapX)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Error
-            _ => {
-                cxt_stack.push_non_term(NonTerm::Map)?;
-                Err(Error::UnexpectedT(vec![Kind::Map, Kind::MapX], input))
-            }
-        },
-
-        NonTerminalSymbol(NonTerm::MapXSeq) => match input {
-            // Production: mapxseq = %break
-            Term::Break => {
-                cxt_stack.push_kind(Kind::Break)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: mapxseq = label:value item:value mapxseq
-            _ => {
-                cxt_stack.push_non_term(NonTerm::MapXSeq)?;
-                cxt_stack.push_action("map_push", map_push())?;
-                cxt_stack.push_non_term(NonTerm::Value)?; // item
-                cxt_stack.push_non_term(NonTerm::Value)?; // label
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-        },
+            },
 
-        NonTerminalSymbol(NonTerm::Tag) => match input {
-            // Production: tag = %tag value
-            Term::Tag(tag) => {
-                cxt_stack.push_action("tag_set", tag_set(tag))?;
-                cxt_stack.push_non_term(NonTerm::Value)?;
-                cxt_stack.push_kind(Kind::Tag)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Error
-            _ => {
-                cxt_stack.push_non_term(NonTerm::Tag)?;
-                Err(Error::UnexpectedT(vec![Kind::Tag], input))
-            }
-        },
+            NonTerminalSymbol(NonTerm::ArrayXSeq) => match input {
+                // Production: arrayxseq = %break
+                Term::Break => {
+                    cxt_stack.push_kind(Kind::Break)?;
+                }
+                // Production: arrayxseq = value arrayxseq
+                _ => {
+                    cxt_stack.push_non_term(NonTerm::ArrayXSeq)?;
+                    cxt_stack.push_action(action::Action::ArrayPush)?;
+                    cxt_stack.push_non_term(NonTerm::Value)?;
+                }
+            },
 
-        NonTerminalSymbol(NonTerm::Tstr) => match input {
-            // Production: tstr = %tstr(payload)
-            Term::Tstr(_) => {
-                cxt_stack.push_kind(Kind::Tstr)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: tstr = %tstrx tstrxseq
-            Term::TstrX => {
-                cxt_stack.push_non_term(NonTerm::TstrXSeq)?;
-                cxt_stack.push_kind(Kind::TstrX)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Error
-            _ => {
-                cxt_stack.push_non_term(NonTerm::Tstr)?;
-                Err(Error::UnexpectedT(vec![Kind::Tstr, Kind::TstrX], input))
-            }
-        },
+            NonTerminalSymbol(NonTerm::Bstr) => match input {
+                // Production: bstr = %bstr(payload)
+                Term::Bstr(_) => {
+                    cxt_stack.push_kind(Kind::Bstr)?;
+                }
+                // Production: bstr = %bstrx bstrxseq
+                Term::BstrX => {
+                    cxt_stack.push_non_term(NonTerm::BstrXSeq)?;
+                    cxt_stack.push_kind(Kind::BstrX)?;
+                }
+                // Error
+                _ => {
+                    cxt_stack.push_non_term(NonTerm::Bstr)?;
+                    return Err(Error::UnexpectedT(
+                        vec![Kind::Bstr, Kind::BstrX],
+                        input,
+                    ));
+                }
+            },
 
-        NonTerminalSymbol(NonTerm::TstrXSeq) => match input {
-            // Production: tstrxseq = %break
-            Term::Break => {
-                cxt_stack.push_kind(Kind::Break)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Production: tstrxseq = tstr tstrxseq
-            Term::Tstr(_) | Term::TstrX => {
-                cxt_stack.push_non_term(NonTerm::TstrXSeq)?;
-                cxt_stack.push_action("append_tstr", tstr_append())?;
-                cxt_stack.push_non_term(NonTerm::Tstr)?;
-                do_consume(parse_visitor, val_stack, cxt_stack, input)
-            }
-            // Error
-            _ => {
-                cxt_stack.push_non_term(NonTerm::TstrXSeq)?;
-                Err(Error::UnexpectedT(
-                    vec![Kind::Break, Kind::Tstr, Kind::TstrX],
-                    input,
-                ))
+            NonTerminalSymbol(NonTerm::BstrXSeq) => {
+                match input {
+                    // Production: bstrxseq = %break
+                    Term::Break => {
+                        cxt_stack.push_kind(Kind::Break)?;
+                    }
+                    // Production: bstrxseq = bstr bstrxseq
+                    Term::Bstr(_) | Term::BstrX => {
+                        cxt_stack.push_non_term(NonTerm::BstrXSeq)?;
+                        cxt_stack.push_action(action::Action::BstrAppend)?;
+                        cxt_stack.push_non_term(NonTerm::Bstr)?;
+                    }
+                    // Error
+                    _ => {
+                        cxt_stack.push_non_term(NonTerm::BstrXSeq)?;
+                        return Err(Error::UnexpectedT(
+                            vec![Kind::Break, Kind::Bstr, Kind::BstrX],
+                            input,
+                        ));
+                    }
+                }
             }
-        },
-    }
-}
-
-////////////////////////////////////////////////////////////////////////////////
-//   Action utilities
-////////////////////////////////////////////////////////////////////////////////
-
-fn array_collect(n: u64) -> impl Fn(&mut ContextStack, &mut ValueStack) {
-    move |_: &mut ContextStack, val_stack: &mut ValueStack| {
-        val_stack.do_array_collect(n)
-    }
-}
 
-fn array_push() -> impl Fn(&mut ContextStack, &mut ValueStack) {
-    move |_: &mut ContextStack, val_stack: &mut ValueStack| {
-        val_stack.do_array_push()
-    }
-}
-
-fn bstr_append() -> impl Fn(&mut ContextStack, &mut ValueStack) {
-    move |_: &mut ContextStack, val_stack: &mut ValueStack| {
-        val_stack.do_bstr_append()
-    }
-}
+            NonTerminalSymbol(NonTerm::Map) => match input {
+                // Production: map = %map(n) {2n}value
+                Term::Map(n) => {
+                    cxt_stack.push(MapItems(n))?;
+                    cxt_stack.push_kind(Kind::Map)?;
+                }
+                // Production: map = %mapx mapxseq
+                Term::MapX => {
+                    cxt_stack.push_non_term(NonTerm::MapXSeq)?;
+                    cxt_stack.push_kind(Kind::MapX)?;
+                }
+                // Error
+                _ => {
+                    cxt_stack.push_non_term(NonTerm::Map)?;
+                    return Err(Error::UnexpectedT(
+                        vec![Kind::Map, Kind::MapX],
+                        input,
+                    ));
+                }
+            },
 
-fn map_collect(n: u64) -> impl Fn(&mut ContextStack, &mut ValueStack) {
-    move |_: &mut ContextStack, val_stack: &mut ValueStack| {
-        val_stack.do_map_collect(n)
-    }
-}
+            NonTerminalSymbol(NonTerm::MapXSeq) => match input {
+                // Production: mapxseq = %break
+                Term::Break => {
+                    cxt_stack.push_kind(Kind::Break)?;
+                }
+                // Production: mapxseq = label:value item:value mapxseq
+                _ => {
+                    cxt_stack.push_non_term(NonTerm::MapXSeq)?;
+                    cxt_stack.push_action(action::Action::MapPush)?;
+                    cxt_stack.push_non_term(NonTerm::Value)?; // item
+                    cxt_stack.push_non_term(NonTerm::Value)?; // label
+                }
+            },
 
-fn map_push() -> impl Fn(&mut ContextStack, &mut ValueStack) {
-    move |_: &mut ContextStack, val_stack: &mut ValueStack| {
-        val_stack.do_map_push()
-    }
-}
+            NonTerminalSymbol(NonTerm::Tag) => match input {
+                // Production: tag = %tag value
+                Term::Tag(tag) => {
+                    cxt_stack.push_action(action::Action::TagSet(tag))?;
+                    cxt_stack.push_non_term(NonTerm::Value)?;
+                    cxt_stack.push_kind(Kind::Tag)?;
+                }
+                // Error
+                _ => {
+                    cxt_stack.push_non_term(NonTerm::Tag)?;
+                    return Err(Error::UnexpectedT(vec![Kind::Tag], input));
+                }
+            },
 
-fn tag_set(tag: u64) -> impl Fn(&mut ContextStack, &mut ValueStack) {
-    move |_: &mut ContextStack, val_stack: &mut ValueStack| {
-        val_stack.do_tag_set(tag)
-    }
-}
+            NonTerminalSymbol(NonTerm::Tstr) => match input {
+                // Production: tstr = %tstr(payload)
+                Term::Tstr(_) => {
+                    cxt_stack.push_kind(Kind::Tstr)?;
+                }
+                // Production: tstr = %tstrx tstrxseq
+                Term::TstrX => {
+                    cxt_stack.push_non_term(NonTerm::TstrXSeq)?;
+                    cxt_stack.push_kind(Kind::TstrX)?;
+                }
+                // Error
+                _ => {
+                    cxt_stack.push_non_term(NonTerm::Tstr)?;
+                    return Err(Error::UnexpectedT(
+                        vec![Kind::Tstr, Kind::TstrX],
+                        input,
+                    ));
+                }
+            },
 
-fn tstr_append() -> impl Fn(&mut ContextStack, &mut ValueStack) {
-    move |_: &mut ContextStack, val_stack: &mut ValueStack| {
-        val_stack.do_tstr_append()
+            NonTerminalSymbol(NonTerm::TstrXSeq) => match input {
+                // Production: tstrxseq = %break
+                Term::Break => {
+                    cxt_stack.push_kind(Kind::Break)?;
+                }
+                // Production: tstrxseq = tstr tstrxseq
+                Term::Tstr(_) | Term::TstrX => {
+                    cxt_stack.push_non_term(NonTerm::TstrXSeq)?;
+                    cxt_stack.push_action(action::Action::TstrAppend)?;
+                    cxt_stack.push_non_term(NonTerm::Tstr)?;
+                }
+                // Error
+                _ => {
+                    cxt_stack.push_non_term(NonTerm::TstrXSeq)?;
+                    return Err(Error::UnexpectedT(
+                        vec![Kind::Break, Kind::Tstr, Kind::TstrX],
+                        input,
+                    ));
+                }
+            },
+        }
     }
 }
 
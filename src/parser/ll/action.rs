@@ -0,0 +1,77 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{Builder, Error, ValueStack};
+use std::fmt;
+
+/// Semantic action of the LL parser
+///
+/// An action transforms the value stack once the parser has recognised the
+/// right-hand side of a production. Actions are plain values; dispatching an
+/// action does not allocate.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Action {
+    /// Appends the value on top of the value stack to the array below it.
+    ArrayPush,
+    /// Appends the byte string on top of the value stack to the byte string
+    /// below it.
+    BstrAppend,
+    /// Builds the string, array or map on top of the value stack once it is
+    /// complete.
+    Build,
+    /// Appends the label and item on top of the value stack to the map below
+    /// them.
+    MapPush,
+    /// Tags the value on top of the value stack with the tag number.
+    TagSet(u64),
+    /// Appends the text string on top of the value stack to the text string
+    /// below it.
+    TstrAppend,
+}
+
+impl Action {
+    /// Applies the action to the value stack.
+    pub fn apply<B: Builder>(
+        self,
+        val_stack: &mut ValueStack<B::Output>,
+        builder: &mut B,
+    ) -> Result<(), Error> {
+        match self {
+            Action::ArrayPush => val_stack.do_array_push(builder),
+            Action::BstrAppend => val_stack.do_bstr_append(),
+            Action::Build => val_stack.do_build(builder),
+            Action::MapPush => val_stack.do_map_push(builder),
+            Action::TagSet(tag) => val_stack.do_tag_set(tag, builder),
+            Action::TstrAppend => val_stack.do_tstr_append(),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::ArrayPush => write!(f, "array_push"),
+            Action::BstrAppend => write!(f, "bstr_append"),
+            Action::Build => write!(f, "build"),
+            Action::MapPush => write!(f, "map_push"),
+            Action::TagSet(tag) => write!(f, "tag_set({tag})"),
+            Action::TstrAppend => write!(f, "tstr_append"),
+        }
+    }
+}
@@ -24,7 +24,7 @@ use super::*;
 ///
 /// The stack is protected against infinite recursion by an upper bound to its
 /// length.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ContextStack {
     inner: Vec<Context>,
     upper: usize,
@@ -46,51 +46,28 @@ impl ContextStack {
         self.inner.pop()
     }
 
-    pub fn push_kind(&mut self, kind: Kind) -> Result<(), Error> {
-        if self.inner.len() < self.upper {
-            Ok(self.inner.push(Context::TerminalSymbol(kind)))
-        } else {
-            Err(Error::InsufficientStackSize)
-        }
+    pub fn truncate(&mut self, len: usize) {
+        self.inner.truncate(len)
     }
 
-    pub fn push_non_term(&mut self, non_term: NonTerm) -> Result<(), Error> {
+    pub fn push(&mut self, context: Context) -> Result<(), Error> {
         if self.inner.len() < self.upper {
-            Ok(self.inner.push(Context::NonTerminalSymbol(non_term)))
+            self.inner.push(context);
+            Ok(())
         } else {
             Err(Error::InsufficientStackSize)
         }
     }
 
-    pub fn push_multiple_non_term(
-        &mut self,
-        non_term: NonTerm,
-        count: usize,
-    ) -> Result<(), Error> {
-        if count <= self.upper && self.inner.len() < self.upper - count {
-            for _ in 0..count {
-                self.inner.push(Context::NonTerminalSymbol(non_term));
-            }
-            Ok(())
-        } else {
-            Err(Error::InsufficientStackSize)
-        }
+    pub fn push_kind(&mut self, kind: Kind) -> Result<(), Error> {
+        self.push(Context::TerminalSymbol(kind))
     }
 
-    pub fn push_action<Action>(
-        &mut self,
-        name: impl ToString,
-        s: Action,
-    ) -> Result<(), Error>
-    where
-        Action: Fn(&mut ContextStack, &mut ValueStack) + 'static,
-    {
-        if self.inner.len() < self.upper {
-            Ok(self
-                .inner
-                .push(Context::Action(name.to_string(), Box::new(s))))
-        } else {
-            Err(Error::InsufficientStackSize)
-        }
+    pub fn push_non_term(&mut self, non_term: NonTerm) -> Result<(), Error> {
+        self.push(Context::NonTerminalSymbol(non_term))
+    }
+
+    pub fn push_action(&mut self, action: Action) -> Result<(), Error> {
+        self.push(Context::Action(action))
     }
 }
@@ -53,10 +53,10 @@ fn scanner_parser() {
     let mut parser = Parser::cbor();
     let decode = |s: &mut Scanner, p: &mut Parser| {
         for byte in values {
-            if let Some(token) = s.consume(byte).unwrap() {
-                if let result @ Some(_) = p.consume(token).unwrap() {
-                    return result;
-                }
+            if let Some(token) = s.consume(byte).unwrap()
+                && let result @ Some(_) = p.consume(token).unwrap()
+            {
+                return result;
             }
         }
         None
@@ -29,8 +29,8 @@ impl ParserVisitor for EprintlnVisitor {
     fn on_flush(&self, context: &ContextStack, values: &ValueStack) {
         eprintln!("{values:?}  {context:?}");
     }
-    fn on_action(&self, _context: &ContextStack, _values: &ValueStack, name: &String) {
-        eprintln!(" -=- {name} -=- ");
+    fn on_action(&self, _context: &ContextStack, _values: &ValueStack, action: &Action) {
+        eprintln!(" -=- {action} -=- ");
     }
 }
 
@@ -141,7 +141,7 @@ fn bstr0() {
     let mut parser = Parser::cbor();
     parser.set_visitor(EprintlnVisitor {});
     let octets: [u8; 6] = rand::random();
-    let bstr_value = Vec::<u8>::from_iter(octets.into_iter());
+    let bstr_value = Vec::<u8>::from_iter(octets);
     let bstr_result = parser
         .init()
         .and_then(|_| parser.consume(Term::Bstr(bstr_value.clone())))
@@ -540,3 +540,416 @@ fn mapx2() {
     assert_eq!(1, label1.as_nint().unwrap());
     assert_eq!(1, item1.as_uint().unwrap());
 }
+
+#[test]
+fn array_large() {
+    let mut parser = Parser::cbor();
+    let count: u64 = 20_000;
+    assert!(parser.consume(Term::Array(count)).unwrap().is_none());
+    for n in 0..count - 1 {
+        assert!(parser.consume(Term::Uint(n % 24)).unwrap().is_none());
+    }
+    let result = parser
+        .consume(Term::Uint((count - 1) % 24))
+        .unwrap()
+        .unwrap()
+        .as_array()
+        .unwrap();
+    assert_eq!(20_000, result.len());
+    assert_eq!(Value::Uint(1), result[1]);
+}
+
+#[test]
+fn array_excessive() {
+    let mut parser = Parser::cbor();
+    let result = parser
+        .consume(Term::Array(u64::MAX))
+        .and_then(|_| parser.consume(Term::Uint(0)))
+        .and_then(|_| parser.consume(Term::Uint(1)))
+        .unwrap();
+    assert_eq!(None, result);
+}
+
+#[test]
+fn map_excessive() {
+    let mut parser = Parser::cbor();
+    let result = parser
+        .consume(Term::Map(u64::MAX))
+        .and_then(|_| parser.consume(Term::Uint(0)))
+        .and_then(|_| parser.consume(Term::Uint(1)))
+        .unwrap();
+    assert_eq!(None, result);
+}
+
+#[test]
+fn array_deeply_nested() {
+    let mut parser = Parser::cbor();
+    let depth = 5_000;
+    for _ in 0..depth {
+        assert_eq!(None, parser.consume(Term::Array(1)).unwrap());
+    }
+    let mut result = parser.consume(Term::Uint(0)).unwrap().unwrap();
+    for _ in 0..depth {
+        result = result.as_array().unwrap().remove(0);
+    }
+    assert_eq!(Value::Uint(0), result);
+}
+
+#[test]
+fn stacks_clone_send() {
+    fn assert_clone_send<T: Clone + Send>() {}
+    assert_clone_send::<ContextStack>();
+    assert_clone_send::<ValueStack>();
+    assert_clone_send::<Parser>();
+}
+
+#[test]
+fn clone_partial() {
+    let mut parser = Parser::cbor();
+    parser.set_visitor(EprintlnVisitor {});
+    assert_eq!(None, parser.consume(Term::Array(2)).unwrap());
+    assert_eq!(None, parser.consume(Term::Uint(1)).unwrap());
+    let mut clone = parser.clone();
+    let handle = std::thread::spawn(move || clone.consume(Term::Uint(3)));
+    let expected = Value::Array(vec![Value::Uint(1), Value::Uint(2)]);
+    assert_eq!(Some(expected), parser.consume(Term::Uint(2)).unwrap());
+    let expected = Value::Array(vec![Value::Uint(1), Value::Uint(3)]);
+    assert_eq!(Some(expected), handle.join().unwrap().unwrap());
+}
+
+/// Visitor that counts its inputs; it is `Send`, but not `Sync`
+#[derive(Default)]
+struct CountingVisitor {
+    inputs: std::cell::Cell<usize>,
+}
+
+impl ParserVisitor for CountingVisitor {
+    fn on_input(&self, _: &ContextStack, _: &ValueStack, _: &Term) {
+        self.inputs.set(self.inputs.get() + 1);
+    }
+}
+
+#[test]
+fn visitor_send() {
+    let mut parser = Parser::cbor();
+    parser.set_visitor(CountingVisitor::default());
+    let handle = std::thread::spawn(move || parser.consume(Term::Uint(0)));
+    assert_eq!(Some(Value::Uint(0)), handle.join().unwrap().unwrap());
+}
+
+#[test]
+fn single_trailing_input() {
+    let mut parser = Parser::cbor();
+    assert_eq!(Some(Value::Uint(0)), parser.consume(Term::Uint(0)).unwrap());
+    let result = parser.consume(Term::Uint(1));
+    assert!(matches!(result, Err(Error::TrailingInput)));
+}
+
+#[test]
+fn sequence() {
+    let mut parser = Parser::sequence();
+    let tokens = [
+        Term::Uint(0),
+        Term::Array(2),
+        Term::Uint(1),
+        Term::Nint(2),
+        Term::Tag(3),
+        Term::Simple(4),
+    ];
+    let expected = vec![
+        Value::Uint(0),
+        Value::Array(vec![Value::Uint(1), Value::Nint(2)]),
+        Value::Tag(3, Box::new(Value::Simple(4))),
+    ];
+    let mut actual = Vec::new();
+    for token in tokens {
+        if let Some(value) = parser.consume(token).unwrap() {
+            actual.push(value);
+        }
+    }
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn reset() {
+    let mut parser = Parser::cbor();
+    assert_eq!(None, parser.consume(Term::Array(2)).unwrap());
+    assert_eq!(None, parser.consume(Term::Uint(1)).unwrap());
+    parser.reset();
+    assert_eq!(Some(Value::Uint(0)), parser.consume(Term::Uint(0)).unwrap());
+    parser.reset();
+    assert_eq!(Some(Value::Uint(1)), parser.consume(Term::Uint(1)).unwrap());
+}
+
+#[test]
+fn map_duplicate_keys() {
+    let tokens = || {
+        [
+            Term::Map(3),
+            Term::Uint(1),
+            Term::Uint(10),
+            Term::Uint(2),
+            Term::Uint(20),
+            Term::Uint(1),
+            Term::Uint(30),
+        ]
+    };
+    let decode = |duplicate_keys| {
+        let mut parser = Parser::cbor();
+        parser.set_options(Options {
+            duplicate_keys,
+            ..Options::default()
+        });
+        for token in tokens() {
+            if let Some(value) = parser.consume(token)? {
+                return Ok(value);
+            }
+        }
+        Err(Error::Incomplete)
+    };
+    let entry = |k, v| (Value::Uint(k), Value::Uint(v));
+    let actual = decode(DuplicateKeys::KeepAll).unwrap();
+    let expected = vec![entry(1, 10), entry(2, 20), entry(1, 30)];
+    assert_eq!(Value::Map(expected), actual);
+    let actual = decode(DuplicateKeys::KeepFirst).unwrap();
+    let expected = vec![entry(1, 10), entry(2, 20)];
+    assert_eq!(Value::Map(expected), actual);
+    let actual = decode(DuplicateKeys::KeepLast).unwrap();
+    let expected = vec![entry(1, 30), entry(2, 20)];
+    assert_eq!(Value::Map(expected), actual);
+    let error = decode(DuplicateKeys::Reject).unwrap_err();
+    assert!(matches!(error.cause(), Error::DuplicateKey(Value::Uint(1))));
+    assert_eq!(".{1}", error.path().unwrap().to_string());
+}
+
+#[test]
+fn mapx_duplicate_keys() {
+    let mut parser = Parser::cbor();
+    parser.set_options(Options {
+        duplicate_keys: DuplicateKeys::KeepLast,
+        ..Options::default()
+    });
+    let tokens = [
+        Term::MapX,
+        Term::Uint(1),
+        Term::Uint(10),
+        Term::Uint(2),
+        Term::Uint(20),
+        Term::Uint(1),
+        Term::Uint(30),
+        Term::Break,
+    ];
+    let expected = Value::Map(vec![
+        (Value::Uint(1), Value::Uint(30)),
+        (Value::Uint(2), Value::Uint(20)),
+    ]);
+    for token in tokens {
+        if let Some(actual) = parser.consume(token).unwrap() {
+            assert_eq!(expected, actual);
+            return;
+        }
+    }
+    panic!("Test fell through the end");
+}
+
+#[test]
+fn tstr_invalid_utf8() {
+    let mut parser = Parser::cbor();
+    let result = parser.consume(Term::Tstr(vec![0x41, 0xff]));
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+}
+
+#[test]
+fn tstrx_split_code_point() {
+    // U+00E9 encoded as 0xc3 0xa9, split across two chunks
+    let mut parser = Parser::cbor();
+    let result = parser
+        .consume(Term::TstrX)
+        .and_then(|_| parser.consume(Term::Tstr(vec![0x41, 0xc3])));
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+}
+
+#[test]
+fn tstrx_utf8() {
+    let mut parser = Parser::cbor();
+    let result = parser
+        .consume(Term::TstrX)
+        .and_then(|_| parser.consume(Term::Tstr("é".into())))
+        .and_then(|_| parser.consume(Term::Tstr("ß".into())))
+        .and_then(|_| parser.consume(Term::Break))
+        .unwrap()
+        .unwrap();
+    assert_eq!(Some("éß"), result.as_str());
+}
+
+#[test]
+fn tag_registry() {
+    // Transform unsigned bignums into integers, and reject large ones
+    let mut tags = TagRegistry::default();
+    tags.register(2, |tag, value| {
+        let bytes = value.as_bstr().unwrap_or_default();
+        if bytes.len() > 8 {
+            return Err(Error::InvalidTag(tag, "bignum too large".into()));
+        }
+        let n = bytes.iter().fold(0, |n, byte| n << 8 | u64::from(*byte));
+        Ok(Value::Uint(n))
+    });
+    let options = Options { tags, ..Options::default() };
+    let decode = |bytes: Vec<u8>| {
+        let mut parser = Parser::cbor();
+        parser.set_options(options.clone());
+        parser
+            .consume(Term::Array(2))
+            .and_then(|_| parser.consume(Term::Tag(2)))
+            .and_then(|_| parser.consume(Term::Bstr(bytes)))
+            .and_then(|_| parser.consume(Term::Tag(3)))
+            .and_then(|_| parser.consume(Term::Uint(0)))
+    };
+    let expected = Value::Array(vec![
+        Value::Uint(0x0100),
+        Value::Tag(3, Box::new(Value::Uint(0))),
+    ]);
+    assert_eq!(Some(expected), decode(vec![0x01, 0x00]).unwrap());
+    let error = decode(vec![0x01; 9]).unwrap_err();
+    assert!(matches!(error.cause(), Error::InvalidTag(2, _)));
+    assert_eq!("[0]", error.path().unwrap().to_string());
+}
+
+/// Feeds `terms` to `parser`, and returns the last result.
+fn consume_all(
+    parser: &mut Parser,
+    terms: Vec<Term>,
+) -> Result<Option<Value>, Error> {
+    let mut result = Ok(None);
+    for term in terms {
+        result = parser.consume(term);
+    }
+    result
+}
+
+#[test]
+fn recovery_array() {
+    // [1, "\xff", 3]
+    let mut parser = Parser::cbor();
+    parser.set_recovery(true);
+    let terms = vec![
+        Term::Array(3),
+        Term::Uint(1),
+        Term::Tstr(vec![0xff]),
+        Term::Uint(3),
+    ];
+    let value = consume_all(&mut parser, terms).unwrap().unwrap();
+    let expected = Value::Array(vec![Value::Uint(1), Value::Simple(23)]);
+    assert_eq!(expected, value);
+    let errors = parser.take_errors();
+    assert_eq!(1, errors.len());
+    assert!(matches!(errors[0].cause(), Error::InvalidUtf8(_)));
+    assert_eq!("[1]", errors[0].path().unwrap().to_string());
+    assert!(parser.take_errors().is_empty());
+}
+
+#[test]
+fn recovery_nested() {
+    // {"a": [1, %break], "b": {_ "c": "\xff", "d": [_ 4]}, "e": 5}
+    let mut parser = Parser::cbor();
+    parser.set_recovery(true);
+    let terms = vec![
+        Term::Map(3),
+        Term::Tstr("a".into()),
+        Term::Array(2),
+        Term::Uint(1),
+        Term::Break,
+        Term::Tstr("b".into()),
+        Term::MapX,
+        Term::Tstr("c".into()),
+        Term::Tstr(vec![0xff]),
+        Term::Tstr("d".into()),
+        Term::ArrayX,
+        Term::Uint(4),
+        Term::Break,
+        Term::Break,
+        Term::Tstr("e".into()),
+        Term::Uint(5),
+    ];
+    let value = consume_all(&mut parser, terms).unwrap().unwrap();
+    let text = |text: &str| Value::Tstr(text.into());
+    let expected = Value::Map(vec![
+        (text("a"), Value::Array(vec![Value::Uint(1), Value::Simple(23)])),
+        (text("b"), Value::Map(vec![(text("c"), Value::Simple(23))])),
+        (text("e"), Value::Uint(5)),
+    ]);
+    assert_eq!(expected, value);
+    let paths = parser
+        .take_errors()
+        .iter()
+        .map(|error| error.path().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![r#".{"a"}[1]"#, r#".{"b"}.{"c"}"#], paths);
+}
+
+#[test]
+fn recovery_bstrx() {
+    // [(_ 1 h'01'), 3]
+    let mut parser = Parser::cbor();
+    parser.set_recovery(true);
+    let terms = vec![
+        Term::Array(2),
+        Term::BstrX,
+        Term::Uint(1),
+        Term::Bstr(vec![1]),
+        Term::Break,
+        Term::Uint(3),
+    ];
+    let value = consume_all(&mut parser, terms).unwrap().unwrap();
+    assert_eq!(Value::Array(vec![Value::Simple(23)]), value);
+    let errors = parser.take_errors();
+    assert_eq!(1, errors.len());
+    assert!(matches!(errors[0].cause(), Error::UnexpectedT(_, Term::Uint(1))));
+    assert_eq!("[0]", errors[0].path().unwrap().to_string());
+}
+
+#[test]
+fn recovery_tstrx() {
+    // [(_ "a" (_ "\xff" "b")), 3]
+    let mut parser = Parser::cbor();
+    parser.set_recovery(true);
+    let terms = vec![
+        Term::Array(2),
+        Term::TstrX,
+        Term::Tstr("a".into()),
+        Term::TstrX,
+        Term::Tstr(vec![0xff]),
+        Term::Tstr("b".into()),
+        Term::Break,
+        Term::Break,
+        Term::Uint(3),
+    ];
+    let value = consume_all(&mut parser, terms).unwrap().unwrap();
+    assert_eq!(Value::Array(vec![Value::Simple(23)]), value);
+    let errors = parser.take_errors();
+    assert_eq!(1, errors.len());
+    assert!(matches!(errors[0].cause(), Error::InvalidUtf8(_)));
+    assert_eq!("[0]", errors[0].path().unwrap().to_string());
+}
+
+#[test]
+fn recovery_top_level() {
+    // %break 1
+    let mut parser = Parser::sequence();
+    parser.set_recovery(true);
+    let value = parser.consume(Term::Break).unwrap();
+    assert_eq!(Some(Value::Simple(23)), value);
+    assert_eq!(Some(Value::Uint(1)), parser.consume(Term::Uint(1)).unwrap());
+    let errors = parser.take_errors();
+    assert_eq!(1, errors.len());
+    assert!(matches!(errors[0], Error::UnexpectedT(_, Term::Break)));
+}
+
+#[test]
+fn recovery_disabled() {
+    let mut parser = Parser::cbor();
+    let terms = vec![Term::Array(2), Term::Uint(1), Term::Tstr(vec![0xff])];
+    let result = consume_all(&mut parser, terms);
+    assert!(matches!(result, Err(Error::At(..))));
+    assert!(parser.take_errors().is_empty());
+}
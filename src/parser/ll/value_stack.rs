@@ -17,18 +17,31 @@ You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::{Builder, Error, Slot};
+use crate::budget::Budget;
 use crate::value::Value;
 
 /// Parsing value stack
 ///
 /// The value stack keeps track of partial results as the final result is being
-/// contructed.
-#[derive(Debug, Default)]
-pub struct ValueStack {
-    inner: Vec<Value>,
+/// contructed. It charges its entries, and the growth of arrays, maps, and
+/// strings, to its memory budget.
+#[derive(Clone, Debug)]
+pub struct ValueStack<T = Value> {
+    inner: Vec<Slot<T>>,
+    budget: Budget,
 }
 
-impl ValueStack {
+impl<T> Default for ValueStack<T> {
+    fn default() -> Self {
+        ValueStack {
+            inner: Vec::new(),
+            budget: Budget::default(),
+        }
+    }
+}
+
+impl<T> ValueStack<T> {
     /// Returns the number of values in a value stack.
     pub fn len(&self) -> usize {
         self.inner.len()
@@ -36,36 +49,35 @@ impl ValueStack {
 
     /// Removes the last value from a value stack and returns it, or [None] if
     /// it is empty.
-    pub fn pop(&mut self) -> Option<Value> {
+    pub(crate) fn pop(&mut self) -> Option<Slot<T>> {
         self.inner.pop()
     }
 
-    /// Appends a value to the back of a value stack.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the new capacity exceeds `isize::MAX` _bytes_.
-    pub fn push(&mut self, value: Value) {
-        self.inner.push(value)
+    /// Sets the memory budget of a value stack.
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
     }
 
-    /// Removes the last `n` values from a value stack, and replaces them with
-    /// an array of those values.
+    /// Returns the values on the stack, from the bottom up.
+    pub(crate) fn slots(&self) -> &[Slot<T>] {
+        &self.inner
+    }
+
+    /// Shortens a value stack, keeping the first `len` values.
+    pub fn truncate(&mut self, len: usize) {
+        self.inner.truncate(len)
+    }
+
+    /// Appends a value to the back of a value stack, and charges the entry to
+    /// the budget.
     ///
     /// # Panics
     ///
-    /// Panics if there are less than `n` values on the value stack.
-    ///
-    /// Panics if `n` exceeds `isize::MAX`.
-    pub fn do_array_collect(&mut self, n: u64) {
-        let sz = usize::try_from(n).unwrap();
-        let mut values: Vec<Value> = Vec::with_capacity(sz);
-        for _ in 0..n {
-            let value = self.pop().unwrap();
-            values.push(value);
-        }
-        values.reverse();
-        self.push(Value::Array(values));
+    /// Panics if the new capacity exceeds `isize::MAX` _bytes_.
+    pub(crate) fn push(&mut self, value: Slot<T>) -> Result<(), Error> {
+        self.budget.charge_elements::<Slot<T>>(1)?;
+        self.inner.push(value);
+        Ok(())
     }
 
     /// Removes the last value from a value stack. Removes the then last value
@@ -78,49 +90,74 @@ impl ValueStack {
     /// Panics if the value stack has less than two elements.
     ///
     /// Panics if the second value is not an array.
-    pub fn do_array_push(&mut self) {
-        let value = self.pop().unwrap();
-        let mut values = self.pop().unwrap().as_array().unwrap();
+    pub fn do_array_push<B>(&mut self, builder: &mut B) -> Result<(), Error>
+    where
+        B: Builder<Output = T>,
+    {
+        let value = self.pop().unwrap().build(builder)?;
+        let Some(Slot::Array(values)) = self.inner.last_mut() else {
+            panic!("Expected value stack [… array value]");
+        };
+        let capacity = values.capacity();
         values.push(value);
-        self.push(Value::Array(values));
+        Ok(self.budget.charge_elements::<T>(values.capacity() - capacity)?)
     }
 
-    pub fn do_bstr_append(&mut self) {
-        let mut bytes = self.pop().unwrap().as_bstr().unwrap();
-        let mut parent = self.pop().unwrap().as_bstr().unwrap();
+    pub fn do_bstr_append(&mut self) -> Result<(), Error> {
+        let Some(Slot::Bytes(mut bytes)) = self.pop() else {
+            panic!("Expected value stack [… bstr bstr]");
+        };
+        let Some(Slot::Bytes(parent)) = self.inner.last_mut() else {
+            panic!("Expected value stack [… bstr bstr]");
+        };
+        let capacity = parent.capacity();
         parent.append(&mut bytes);
-        self.push(Value::Bstr(parent));
+        Ok(self.budget.charge(parent.capacity() - capacity)?)
     }
 
-    pub fn do_map_collect(&mut self, n: u64) {
-        let sz = usize::try_from(n).unwrap();
-        let mut values: Vec<(Value, Value)> = Vec::with_capacity(sz);
-        for _ in 0..n {
-            let value = self.pop().unwrap();
-            let label = self.pop().unwrap();
-            values.push((label, value));
-        }
-        values.reverse();
-        self.push(Value::Map(values));
+    pub fn do_build<B>(&mut self, builder: &mut B) -> Result<(), Error>
+    where
+        B: Builder<Output = T>,
+    {
+        let value = self.pop().unwrap().build(builder)?;
+        self.push(Slot::Item(value))
     }
 
-    pub fn do_map_push(&mut self) {
-        let value = self.pop().unwrap();
-        let label = self.pop().unwrap();
-        let mut values = self.pop().unwrap().as_map().unwrap();
-        values.push((label, value));
-        self.push(Value::Map(values));
+    pub fn do_map_push<B>(&mut self, builder: &mut B) -> Result<(), Error>
+    where
+        B: Builder<Output = T>,
+    {
+        let value = self.pop().unwrap().build(builder)?;
+        let label = self.pop().unwrap().build(builder)?;
+        let Some(Slot::Map(map)) = self.inner.last_mut() else {
+            panic!("Expected value stack [… map value value]");
+        };
+        let allocated = map.allocated();
+        builder.entry(map, (label, value))?;
+        Ok(self.budget.charge(map.allocated() - allocated)?)
     }
 
-    pub fn do_tag_set(&mut self, tag: u64) {
-        let value = self.pop().unwrap();
-        self.push(Value::Tag(tag, Box::new(value)));
+    pub fn do_tag_set<B>(
+        &mut self,
+        tag: u64,
+        builder: &mut B,
+    ) -> Result<(), Error>
+    where
+        B: Builder<Output = T>,
+    {
+        let value = self.pop().unwrap().build(builder)?;
+        self.push(Slot::Item(builder.tag(tag, value)?))
     }
 
-    pub fn do_tstr_append(&mut self) {
-        let bytes = self.pop().unwrap().as_tstr().unwrap();
-        let mut parent = self.pop().unwrap().as_tstr().unwrap();
-        parent.push_str(&bytes);
-        self.push(Value::Tstr(parent.into_bytes()));
+    pub fn do_tstr_append(&mut self) -> Result<(), Error> {
+        let Some(Slot::Text(text)) = self.pop() else {
+            panic!("Expected value stack [… tstr tstr]");
+        };
+        let Some(Slot::Text(parent)) = self.inner.last_mut() else {
+            panic!("Expected value stack [… tstr tstr]");
+        };
+        let capacity = parent.capacity();
+        parent.push_str(&text);
+        Ok(self.budget.charge(parent.capacity() - capacity)?)
     }
 }
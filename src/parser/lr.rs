@@ -19,11 +19,12 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use super::*;
 
-use crate::value::Value;
-
 mod action;
 use action::Action;
 
+mod grammar;
+use grammar::GRAMMAR;
+
 mod production;
 use production::Production;
 
@@ -33,105 +34,229 @@ use state::State;
 mod state_stack;
 use state_stack::StateStack;
 
+mod table;
+use table::{Item, TABLE};
+
 mod value_stack;
 use value_stack::ValueStack;
 
 use super::grammar::non_term::NonTermExt;
+use super::path::Tracker;
 
 /// CBOR parser.
-#[derive(Debug, Default)]
-pub struct Parser {
+///
+/// The parser builds its values with a [Builder], by default with [Options].
+/// The parser is `Send` and `Clone`, so that a partially parsed value can be
+/// handed over to another thread, or parsed on in different ways.
+#[derive(Clone, Debug)]
+pub struct Parser<B: Builder = Options> {
     states: StateStack,
-    values: ValueStack,
+    values: ValueStack<B::Output>,
+    sequence: bool,
+    accepted: bool,
+    builder: B,
+    path: Tracker,
 }
 
-impl super::Parser for Parser {
-    fn consume(&mut self, term: Term) -> Result<Option<Value>, Error> {
-        self.do_consume(Some(term))
+impl<B: Builder + Default> Default for Parser<B> {
+    fn default() -> Self {
+        Parser {
+            states: StateStack::default(),
+            values: ValueStack::default(),
+            sequence: false,
+            accepted: false,
+            builder: B::default(),
+            path: Tracker::default(),
+        }
+    }
+}
+
+impl<B: Builder> super::Parser for Parser<B> {
+    type Output = B::Output;
+
+    fn consume(&mut self, term: Term) -> Result<Option<B::Output>, Error> {
+        if self.accepted {
+            return Err(Error::TrailingInput);
+        }
+        let update = self.path.update(&term);
+        let value = self
+            .do_consume(Some(term))
+            .map_err(|error| self.path.wrap(error))?;
+        self.path.apply(update);
+        Ok(value)
+    }
+
+    fn reset(&mut self) {
+        self.states = StateStack::cbor();
+        self.values.clear();
+        self.accepted = false;
+        self.path.reset();
+    }
+
+    fn set_budget(&mut self, budget: Budget) {
+        self.values.set_budget(budget);
     }
 }
 
 impl Parser {
     /// Returns a parser initialised for parsing a single CBOR value
     pub fn cbor() -> Parser {
+        Parser::with_builder(Options::default())
+    }
+
+    /// Returns a parser initialised for parsing a CBOR sequence (RFC 8742)
+    ///
+    /// The parser produces one value per data item of the sequence, and
+    /// re-arms itself after each value.
+    pub fn sequence() -> Parser {
+        Parser::sequence_with_builder(Options::default())
+    }
+
+    /// Sets the decoding options of the parser
+    pub fn set_options(&mut self, options: Options) {
+        self.builder = options;
+    }
+}
+
+impl<B: Builder> Parser<B> {
+    /// Returns a parser initialised for parsing a single CBOR value, which
+    /// builds its value with `builder`
+    pub fn with_builder(builder: B) -> Self {
         Parser {
             states: StateStack::cbor(),
             values: ValueStack::default(),
+            sequence: false,
+            accepted: false,
+            builder,
+            path: Tracker::default(),
         }
     }
 
+    /// Returns a parser initialised for parsing a CBOR sequence, which builds
+    /// its values with `builder`
+    pub fn sequence_with_builder(builder: B) -> Self {
+        Parser {
+            sequence: true,
+            ..Parser::with_builder(builder)
+        }
+    }
+
+    /// Returns a view of the partially parsed value.
+    pub fn progress(&self) -> Progress<'_, B::Output> {
+        Progress::new(&self.path, self.values.slots())
+    }
+
+    /// Returns the builder of the parser
+    pub fn builder(&self) -> &B {
+        &self.builder
+    }
+
+    /// Runs the parser until it has consumed `term`, and has applied all
+    /// reductions that do not need further input.
     fn do_consume(
         &mut self,
         term: Option<Term>,
-    ) -> Result<Option<Value>, Error> {
-        #[cfg(debug_assertions)]
-        if let Some(term) = &term {
-            eprintln!("{} {} ← {}", self.values, self.states, term);
-        } else {
-            eprintln!("{} {} ⊣", self.values, self.states);
-        }
-        let Some(current) = self.states.last() else {
-            return Err(Error::Invalid);
-        };
-        let Some(action) = next_action(current, term)? else {
-            return Ok(None);
-        };
-        match action {
-            Action::Shift(state) => self.shift(state),
-            Action::Reduce(rule) => self.reduce(rule),
-            Action::Accept => self.accept(),
+    ) -> Result<Option<B::Output>, Error> {
+        let mut term = term;
+        loop {
+            #[cfg(debug_assertions)]
+            if let Some(term) = &term {
+                eprintln!("{} {} ← {}", self.values, self.states, term);
+            } else {
+                eprintln!("{} {} ⊣", self.values, self.states);
+            }
+            let Some(current) = self.states.last() else {
+                return Err(Error::Invalid);
+            };
+            let Some(action) = Self::next_action(current, term.take())? else {
+                return Ok(None);
+            };
+            match action {
+                Action::Shift(state) => self.shift(state)?,
+                Action::Reduce(rule) => self.reduce(rule)?,
+                Action::Accept => return self.accept(),
+            }
         }
     }
 
-    fn shift(&mut self, state: State) -> Result<Option<Value>, Error> {
+    fn shift(&mut self, state: State) -> Result<(), Error> {
         #[cfg(debug_assertions)]
         eprintln!("Shift {}", state);
+        // Maps collect their entries as they are complete.
+        if let Some(Term::Map(_) | Term::MapX) = state.term {
+            self.values.push(Entries::slot())?;
+        }
         // States = […]
         self.states.push(state)?;
         // States = [… state]
-        self.do_consume(None)
+        Ok(())
     }
 
-    fn reduce(&mut self, rule: &Production<'_>) -> Result<Option<Value>, Error> {
+    fn reduce(&mut self, rule: Production<B>) -> Result<(), Error> {
         #[cfg(debug_assertions)]
         eprintln!("Reduce {}", rule);
         let nt = (rule.reduce)(self)?;
+        if GRAMMAR[rule.num_id].left != nt.into() {
+            return Err(Error::Internal);
+        }
         let next_state = goto(self.states.last(), nt)?;
-        self.states.push(next_state)?;
-        self.do_consume(None)
+        if next_state.completes_entry() {
+            // Values = [… map[…] value value]
+            self.values.merge_map_value_value(&mut self.builder)?;
+            // Values = [… map[… (value,value)]]
+        }
+        self.states.push(next_state)
+    }
+
+    /// Pops the states the parser has entered for the right-hand side of
+    /// production `rule`, and returns the terminal symbol it has shifted to
+    /// enter the first of them.
+    fn pop_states(&mut self, rule: usize) -> Result<Option<Term>, Error> {
+        let complete = Item::new(rule, GRAMMAR[rule].right.len());
+        let Some(mut state) = self.states.pop() else {
+            return Err(Error::Internal);
+        };
+        if state.item != complete {
+            return Err(Error::Internal);
+        }
+        for _ in 1..state.depth() {
+            match self.states.pop() {
+                Some(next) if next.item.rule == complete.rule => state = next,
+                _ => return Err(Error::Internal),
+            }
+        }
+        Ok(state.term)
     }
 
     /// 0: <START> ← <VALUE>
-    fn accept(&mut self) -> Result<Option<Value>, Error> {
-        const NAME: &str = "Accept";
-        // States: [Init Accept]
+    fn accept(&mut self) -> Result<Option<B::Output>, Error> {
+        // States: [<START> → ∙ <VALUE>  <START> → <VALUE> ∙]
         // Values: [value]
-        let Some(State::Accept) = self.states.pop() else {
-            panic!("{NAME}: Expected state `Accept`");
+        self.pop_states(0)?;
+        let Some(result) = self.values.pop_item() else {
+            return Err(Error::Internal);
         };
-        let Some(result) = self.values.pop() else {
-            panic!("{NAME}: Expected value stack [… value]");
-        };
-        // States: [Init]
+        // States: [<START> → ∙ <VALUE>]
         // Values: []
+        // Unless parsing a sequence, the parser rejects any further input.
+        self.accepted = !self.sequence;
         #[cfg(debug_assertions)]
         {
-            eprintln!("{NAME}");
+            eprintln!("Accept");
             eprintln!("{} {}", self.values, self.states);
-            eprintln!("⇒ {result}");
+            eprintln!("⇒ {result:?}");
         }
         Ok(Some(result))
     }
 
     /// 1: <VALUE> ← %uint(n)
     fn reduce01(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 1";
-        // States: [… ValueUint(n)]
+        // States: [… %uint(n) ∙]
         // Values: […]
-        let Some(State::ValueUint(n)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueUint`");
+        let Some(Term::Uint(n)) = self.pop_states(1)? else {
+            return Err(Error::Internal);
         };
-        self.values.push(Value::Uint(n))?;
+        self.values.push_item(self.builder.uint(n)?)?;
         // States: […]
         // Values: [… uint(n)]
         Ok(NonTerm::Value)
@@ -139,13 +264,12 @@ impl Parser {
 
     /// 2: <VALUE> ← %nint(n)
     fn reduce02(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 2";
-        // States = [… ValueNint(n)]
+        // States = [… %nint(n) ∙]
         // Values = […]
-        let Some(State::ValueNint(n)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueNint`");
+        let Some(Term::Nint(n)) = self.pop_states(2)? else {
+            return Err(Error::Internal);
         };
-        self.values.push(Value::Nint(n))?;
+        self.values.push_item(self.builder.nint(n)?)?;
         // States = […]
         // Values = [… nint(n)]
         Ok(NonTerm::Value)
@@ -153,13 +277,12 @@ impl Parser {
 
     /// 3: <VALUE> ← %float
     fn reduce03(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 3";
-        // States: [… ValueFloat(n)]
+        // States: [… %float(p, n) ∙]
         // Values: […]
-        let Some(State::ValueFloat(n)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueFloat`");
+        let Some(Term::Float(p, n)) = self.pop_states(3)? else {
+            return Err(Error::Internal);
         };
-        self.values.push(Value::Float(n))?;
+        self.values.push_item(self.builder.float(p, n)?)?;
         // States: […]
         // Values: [… Float(n)]
         Ok(NonTerm::Value)
@@ -167,12 +290,13 @@ impl Parser {
 
     /// 4: <VALUE> ← <BSTR>
     fn reduce04(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 4";
-        // States: [… ValueBstr]
+        // States: [… <BSTR> ∙]
         // Values: [… bstr]
-        let Some(State::ValueBstr) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueBstr`");
+        self.pop_states(4)?;
+        let Some(Slot::Bytes(bytes)) = self.values.pop() else {
+            return Err(Error::Internal);
         };
+        self.values.push_item(self.builder.bytes(bytes)?)?;
         // States: […]
         // Values: [… bstr]
         Ok(NonTerm::Value)
@@ -180,12 +304,13 @@ impl Parser {
 
     /// 5: <VALUE> ← <TSTR>
     fn reduce05(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 5";
-        // States: [… ValueTstr]
+        // States: [… <TSTR> ∙]
         // Values: [… tstr]
-        let Some(State::ValueTstr) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueTstr`");
+        self.pop_states(5)?;
+        let Some(Slot::Text(text)) = self.values.pop() else {
+            return Err(Error::Internal);
         };
+        self.values.push_item(self.builder.text(text)?)?;
         // States: […]
         // Values: [… tstr]
         Ok(NonTerm::Value)
@@ -193,13 +318,12 @@ impl Parser {
 
     /// 6: <VALUE> ← %simple
     fn reduce06(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 6";
-        // States: [… ValueSimple(n)]
+        // States: [… %simple(n) ∙]
         // Values: […]
-        let Some(State::ValueSimple(n)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueSimple`");
+        let Some(Term::Simple(n)) = self.pop_states(6)? else {
+            return Err(Error::Internal);
         };
-        self.values.push(Value::Simple(n))?;
+        self.values.push_item(self.builder.simple(n)?)?;
         // States: […]
         // Values: [… Simple(n)]
         Ok(NonTerm::Value)
@@ -207,16 +331,15 @@ impl Parser {
 
     /// 7: <VALUE> ← %tag <VALUE>
     fn reduce07(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 7";
-        // States = [… TagNumber(t) ValueTag]
+        // States = [… %tag(t) ∙ <VALUE>  %tag(t) <VALUE> ∙]
         // Values = [… value]
-        let Some(State::ValueTag) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueTag`")
+        let Some(Term::Tag(t)) = self.pop_states(7)? else {
+            return Err(Error::Internal);
         };
-        let Some(State::TagNumber(t)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `TagNumber`")
+        let Some(value) = self.values.pop_item() else {
+            return Err(Error::Internal);
         };
-        self.values.to_tagged(t);
+        self.values.push_item(self.builder.tag(t, value)?)?;
         // States = […]
         // Values = [… tag(t,value)]
         Ok(NonTerm::Value)
@@ -224,39 +347,22 @@ impl Parser {
 
     /// 8: <VALUE> ← %array(n) {n}<VALUE>
     fn reduce08(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 8";
-        // States: [… Array(0,n)…Array(n,n)] #Array = n+1 times
+        // States: [… %array(n) {k}<VALUE> ∙ {n-k}<VALUE>…] #k = 0…n
         // Values: [… (Value…Value)] #Value = n times
-        let n = {
-            let Some(State::ValueArray(k, n)) = self.states.pop() else {
-                panic!("{NAME}: Expected state `Array(_,_)`")
-            };
-            if k != n {
-                panic!("{NAME}: Expected state `Array({n},{n})`")
-            }
-            n
-        };
-        // States: [… Array(0,n)…Array(n-1,n)]
-        // Values: [… (Value…Value)] #Value = n times
-        let Ok(sz) = usize::try_from(n) else {
-            panic!("{NAME}: Excessive count {n}")
-        };
-        let mut result = Vec::<Value>::with_capacity(sz);
-        for p in 0..n {
-            let k = n - p - 1;
-            let Some(State::ValueArray(k_, n_)) = self.states.pop() else {
-                panic!("{NAME}: Expected state `Array({k},{n})`")
-            };
-            if k_ != k || n_ != n {
-                panic!("{NAME}: Expected state `Array({k},{n})`")
-            }
-            let Some(value) = self.values.pop() else {
-                panic!("{NAME}: Expected value stack [… Value]")
+        let Some(Term::Array(n)) = self.pop_states(8)? else {
+            return Err(Error::Internal);
+        };
+        let sz = usize::try_from(n).map_err(|_| Error::Excessive(n))?;
+        self.values.budget().charge_elements::<B::Output>(sz)?;
+        let mut result = Vec::with_capacity(sz);
+        for _ in 0..n {
+            let Some(value) = self.values.pop_item() else {
+                return Err(Error::Internal);
             };
             result.push(value);
         }
         result.reverse();
-        self.values.push(Value::Array(result))?;
+        self.values.push_item(self.builder.array(result)?)?;
         // States: […]
         // Values: [… Array[Value…Value]] #Value = n times
         Ok(NonTerm::Value)
@@ -264,16 +370,14 @@ impl Parser {
 
     /// 9: <VALUE> ← %arrayx <ARRAYXSEQ>
     fn reduce09(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 9";
-        // States = [… ArrayXOpen ValueArrayX]
+        // States = [… %arrayx ∙ <ARRAYXSEQ>  %arrayx <ARRAYXSEQ> ∙]
         // Values = [… array]
-        let Some(State::ValueArrayX) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueArrayX`")
+        self.pop_states(9)?;
+        self.values.reverse_array()?;
+        let Some(Slot::Array(items)) = self.values.pop() else {
+            return Err(Error::Internal);
         };
-        let Some(State::ArrayXSeqOpen) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ArrayXOpen`")
-        };
-        self.values.reverse_array();
+        self.values.push_item(self.builder.array(items)?)?;
         // States = […]
         // Values = [… array]
         Ok(NonTerm::Value)
@@ -281,79 +385,27 @@ impl Parser {
 
     /// 10: <VALUE> ← %map(n) {2n}<VALUE>
     fn reduce10(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 10";
-        // States: [… Map(0,n) … Map(n,n)] #Map = n+1 times
-        // Values: [… Value … Value] #Value = n times
-        let n = {
-            let Some(State::ValueMap(k, n)) = self.states.pop() else {
-                panic!("{NAME}: Expected state `ValueMap(_,_)`")
-            };
-            if k != n {
-                panic!("{NAME}: Expected state `ValueMap({n},{n})`")
-            }
-            n
+        // States: [… %map(n) {k}<VALUE> ∙ {2n-k}<VALUE>…] #k = 0…2n
+        // Values: [… map[(value,value)…]] #Value = 2n times
+        self.pop_states(10)?;
+        let Some(Slot::Map(map)) = self.values.pop() else {
+            return Err(Error::Internal);
         };
-        // States: [… Map(0,n) … Map(n-1,n)] #Map = n times
-        // Values: [… Value … Value] #Value = n times
-        assert_eq!(0, n % 2);
-        let half_n = n / 2;
-        let Ok(sz) = usize::try_from(half_n) else {
-            panic!("{NAME}: Excessive count {half_n}")
-        };
-        let mut result = Vec::<(Value, Value)>::with_capacity(sz);
-        let mut k = n;
-        for _ in 0..half_n {
-            let Some(State::ValueMap(_k, _n)) = self.states.pop() else {
-                panic!("{NAME}: Expected state `ValueMap({k},{n})`")
-            };
-            if _k + 1 != k || _n != n {
-                panic!("{NAME}: Expected state `ValueMap({k},{n})`")
-            }
-            k -= 1;
-            let Some(State::ValueMap(_k, _n)) = self.states.pop() else {
-                panic!("{NAME}: Expected state `ValueMap(k,{n})`")
-            };
-            if _k + 1 != k || _n != n {
-                panic!("{NAME}: Expected state `ValueMap({k},{n})`")
-            }
-            k -= 1;
-            let Some(value2) = self.values.pop() else {
-                panic!("{NAME}: Expected value stack [… value value]")
-            };
-            let Some(value1) = self.values.pop() else {
-                panic!("{NAME}: Expected value stack [… value value]")
-            };
-            result.push((value1, value2));
-        }
-        result.reverse();
-        self.values.push(Value::Map(result))?;
+        self.values.push_item(self.builder.map(map.entries)?)?;
         // States: […]
-        // Values: [… map[(value,value)…)]] #Value = n times
-        #[cfg(debug_assertions)]
-        {
-            let Some(value) = self.values.last() else {
-                panic!("{NAME}: Failed to produce value stack [… map]")
-            };
-            let Some(map) = value.as_map_ref() else {
-                panic!("{NAME}: Failed to produce value stack [… map]")
-            };
-            assert_eq!(map.len(), half_n.try_into().unwrap())
-        }
+        // Values: [… map[(value,value)…)]] #Value = 2n times
         Ok(NonTerm::Value)
     }
 
     /// 11: <VALUE> ← %mapx <MAPXSEQ>
     fn reduce11(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 11";
-        // States = [… MapXSeqOpen ValueMapX]
+        // States = [… %mapx ∙ <MAPXSEQ>  %mapx <MAPXSEQ> ∙]
         // Values = [… map]
-        let Some(State::ValueMapX) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ValueMapX`")
+        self.pop_states(11)?;
+        let Some(Slot::Map(map)) = self.values.pop() else {
+            return Err(Error::Internal);
         };
-        let Some(State::MapXSeqOpen) = self.states.pop() else {
-            panic!("{NAME}: Expected state `MapXSeqOpen`")
-        };
-        self.values.reverse_map();
+        self.values.push_item(self.builder.map(map.entries)?)?;
         // States = […]
         // Values = [… map]
         Ok(NonTerm::Value)
@@ -361,13 +413,12 @@ impl Parser {
 
     /// 12: <BSTR> ← %bstr
     fn reduce12(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 12";
-        // States: [… BstrBstr(bytes)]
+        // States: [… %bstr(bytes) ∙]
         // Values: […]
-        let Some(State::BstrBstr(bytes)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `BstrBstr`");
+        let Some(Term::Bstr(bytes)) = self.pop_states(12)? else {
+            return Err(Error::Internal);
         };
-        self.values.push(Value::Bstr(bytes))?;
+        self.values.push(Slot::Bytes(bytes))?;
         // States: […]
         // Values: [… bstr(bytes)]
         Ok(NonTerm::Bstr)
@@ -375,15 +426,9 @@ impl Parser {
 
     /// 13: <BSTR> ← %bstrx <BSTRXSEQ>
     fn reduce13(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 13";
-        // States: [… BstrXOpen BstrBstrX]
+        // States: [… %bstrx ∙ <BSTRXSEQ>  %bstrx <BSTRXSEQ> ∙]
         // Values: [… bstr]
-        let Some(State::BstrBstrX) = self.states.pop() else {
-            panic!("{NAME}: Expected state `BstrBstrX`");
-        };
-        let Some(State::BstrXSeqOpen) = self.states.pop() else {
-            panic!("{NAME}: Expected state `BstrXOpen`");
-        };
+        self.pop_states(13)?;
         // States: […]
         // Values: [… bstr]
         Ok(NonTerm::Bstr)
@@ -391,13 +436,12 @@ impl Parser {
 
     /// 14: <TSTR> ← %tstr
     fn reduce14(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 14";
-        // States: [… TstrTstr(text)]
+        // States: [… %tstr(bytes) ∙]
         // Values: […]
-        let Some(State::TstrTstr(text)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `TstrTstr`");
+        let Some(Term::Tstr(bytes)) = self.pop_states(14)? else {
+            return Err(Error::Internal);
         };
-        self.values.push(Value::Tstr(text))?;
+        self.values.push(Slot::Text(String::from_utf8(bytes)?))?;
         // States: […]
         // Values: [… tstr(text)]
         Ok(NonTerm::Tstr)
@@ -405,15 +449,9 @@ impl Parser {
 
     /// 15: <TSTR> ← %tstrx <TSTRXSEQ>
     fn reduce15(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 15";
-        // States: [… TstrXOpen TstrTstrX]
+        // States: [… %tstrx ∙ <TSTRXSEQ>  %tstrx <TSTRXSEQ> ∙]
         // Values: [… tstr]
-        let Some(State::TstrTstrX) = self.states.pop() else {
-            panic!("{NAME}: Expected state `TstrTstrX`");
-        };
-        let Some(State::TstrXSeqOpen) = self.states.pop() else {
-            panic!("{NAME}: Expected state `TstrXOpen`");
-        };
+        self.pop_states(15)?;
         // States: […]
         // Values: [… tstr]
         Ok(NonTerm::Tstr)
@@ -421,13 +459,10 @@ impl Parser {
 
     /// 16: <BSTRXSEQ> ← %break
     fn reduce16(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 16";
-        // States = [… BstrXSeqBreak]
+        // States = [… %break ∙]
         // Values = […]
-        let Some(State::BstrXSeqBreak) = self.states.pop() else {
-            panic!("{NAME}: Expected state `BstrXSeqBreak`");
-        };
-        self.values.push(Value::Bstr(Vec::new()))?;
+        self.pop_states(16)?;
+        self.values.push(Slot::Bytes(Vec::new()))?;
         // States = […]
         // Values = [… bstr]
         Ok(NonTerm::BstrXSeq)
@@ -435,16 +470,10 @@ impl Parser {
 
     /// 17: <BSTRXSEQ> ← <BSTR> <BSTRXSEQ>
     fn reduce17(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 17";
-        // States: [… BstrXBstr[as…] BstrXPrepend]
-        // Values: [… bstr[bs…]]
-        let Some(State::BstrXSeqMore) = self.states.pop() else {
-            panic!("{NAME}: Expected state `BstrXPrepend`");
-        };
-        let Some(State::BstrXSeqBstr(bytes)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `BstrXBytes`");
-        };
-        self.values.bstr_prepend(bytes);
+        // States: [… <BSTR> ∙ <BSTRXSEQ>  <BSTR> <BSTRXSEQ> ∙]
+        // Values: [… bstr[as…] bstr[bs…]]
+        self.pop_states(17)?;
+        self.values.merge_bstr_bstr()?;
         // States: […]
         // Values: [… bstr[as…bs…]]
         Ok(NonTerm::BstrXSeq)
@@ -452,13 +481,10 @@ impl Parser {
 
     /// 18: <TSTRXSEQ> ← %break
     fn reduce18(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 18";
-        // States = [… TstrXSeqBreak]
+        // States = [… %break ∙]
         // Values = […]
-        let Some(State::TstrXSeqBreak) = self.states.pop() else {
-            panic!("{NAME}: Expected state `TstrXSeqBreak`");
-        };
-        self.values.push(Value::Tstr(Vec::new()))?;
+        self.pop_states(18)?;
+        self.values.push(Slot::Text(String::new()))?;
         // States = […]
         // Values = [… tstr]
         Ok(NonTerm::TstrXSeq)
@@ -466,16 +492,10 @@ impl Parser {
 
     /// 19: <TSTRXSEQ> ← <TSTR> <TSTRXSEQ>
     fn reduce19(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 19";
-        // States: [… TstrXTstr[as…] TstrXPrepend]
-        // Values: [… tstr[ts…]]
-        let Some(State::TstrXSeqMore) = self.states.pop() else {
-            panic!("{NAME}: Expected state `TstrXPrepend`");
-        };
-        let Some(State::TstrXSeqTstr(bytes)) = self.states.pop() else {
-            panic!("{NAME}: Expected state `TstrXBytes`");
-        };
-        self.values.tstr_prepend(bytes);
+        // States: [… <TSTR> ∙ <TSTRXSEQ>  <TSTR> <TSTRXSEQ> ∙]
+        // Values: [… tstr[as…] tstr[bs…]]
+        self.pop_states(19)?;
+        self.values.merge_tstr_tstr()?;
         // States: […]
         // Values: [… tstr[as…bs…]]
         Ok(NonTerm::TstrXSeq)
@@ -483,13 +503,10 @@ impl Parser {
 
     /// 20: <ARRAYXSEQ> ← %break
     fn reduce20(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 20";
-        // States = [… ArrayXSeqBreak]
+        // States = [… %break ∙]
         // Values = […]
-        let Some(State::ArrayXSeqBreak) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ArrayXSeqBreak`");
-        };
-        self.values.push(Value::Array(Vec::new()))?;
+        self.pop_states(20)?;
+        self.values.push(Slot::Array(Vec::new()))?;
         // States = […]
         // Values = [… array[]]
         Ok(NonTerm::ArrayXSeq)
@@ -497,16 +514,10 @@ impl Parser {
 
     /// 21: <ARRAYXSEQ> ← <VALUE> <ARRAYXSEQ>
     fn reduce21(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 21";
-        // States = [… ArrayXElement ArrayXAppend]
+        // States = [… <VALUE> ∙ <ARRAYXSEQ>  <VALUE> <ARRAYXSEQ> ∙]
         // Values = [… value array[…]]
-        let Some(State::ArrayXSeqMore) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ArrayXAppend`");
-        };
-        let Some(State::ArrayXSeqValue) = self.states.pop() else {
-            panic!("{NAME}: Expected state `ArrayXElement`");
-        };
-        self.values.merge_value_array();
+        self.pop_states(21)?;
+        self.values.merge_value_array()?;
         // States = […]
         // Values = [… array[… value]]
         Ok(NonTerm::ArrayXSeq)
@@ -514,556 +525,108 @@ impl Parser {
 
     /// 22: <MAPXSEQ> ← %break
     fn reduce22(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 22";
-        // States = [… MapXSeqBreak]
+        // States = [… %break ∙]
         // Values = […]
-        let Some(State::MapXSeqBreak) = self.states.pop() else {
-            panic!("{NAME}: Expected state `MapXSeqBreak`");
-        };
-        self.values.push(Value::Map(Vec::new()))?;
+        self.pop_states(22)?;
         // States = […]
-        // Values = [… map[]]
+        // Values = […]
         Ok(NonTerm::MapXSeq)
     }
 
     /// 23: <MAPXSEQ> ← <VALUE> <VALUE> <MAPXSEQ>
     fn reduce23(&mut self) -> Result<NonTerm, Error> {
-        const NAME: &str = "Reduce 23";
-        // States = [… MapXSeqValue1 MapXSeqValue2 MapXSeqMore]
-        // Values = [… value value map[…]]
-        let Some(State::MapXSeqMore) = self.states.pop() else {
-            panic!("{NAME}: Expected state `MapXSeqMore`");
-        };
-        let Some(State::MapXSeqValue2) = self.states.pop() else {
-            panic!("{NAME}: Expected state `MapXSeqValue2`");
-        };
-        let Some(State::MapXSeqValue1) = self.states.pop() else {
-            panic!("{NAME}: Expected state `MapXSeqValue1`");
-        };
-        self.values.merge_value_value_map();
+        // States = [… <VALUE> ∙ <VALUE> <MAPXSEQ>
+        //           <VALUE> <VALUE> ∙ <MAPXSEQ>
+        //           <VALUE> <VALUE> <MAPXSEQ> ∙]
+        // Values = [… map[… (value,value)…]]
+        self.pop_states(23)?;
+        // The entry was merged into the map as it was complete.
         // States = […]
-        // Values = [… map[… (value,value)]]
-        #[cfg(debug_assertions)]
-        {
-            let Some(value) = self.values.last() else {
-                panic!("{NAME}: Failed to produce value stack [… value]")
-            };
-            let Some(map) = value.as_map_ref() else {
-                panic!("{NAME}: Failed to produce value stack [… map]")
-            };
-            let Some(_) = map.last() else {
-                panic!("{NAME}: Failed to produce value stack [… map[… entry]]")
-            };
-        }
+        // Values = [… map[… (value,value)…]]
+        debug_assert!(matches!(
+            self.values.last(),
+            Some(Slot::Map(map)) if !map.entries.is_empty()
+        ));
         Ok(NonTerm::MapXSeq)
     }
-}
-
-/// Determines the action to take when the terminal symbol `term` is consumed
-/// while in state `state`.
-fn next_action<'a>(
-    state: &State,
-    term: Option<Term>,
-) -> Result<Option<Action<'a>>, Error> {
-    // Returns the _accept_ action.
-    #[inline]
-    fn accept<'b>() -> Result<Option<Action<'b>>, Error> {
-        Ok(Some(Action::Accept))
-    }
-
-    // Returns a _reduce_ action using the reduction function of the specified
-    // production `p`
-    #[inline]
-    fn reduce<'b>(p: &'b Production<'b>) -> Result<Option<Action<'b>>, Error> {
-        Ok(Some(Action::Reduce(p)))
-    }
-
-    // Return a _shift_ action pushing the specified `state` onto the stack.
-    #[inline]
-    fn shift<'b>(state: State) -> Result<Option<Action<'b>>, Error> {
-        Ok(Some(Action::Shift(state)))
-    }
-
-    // Default set of actions when reading a terminal symbol that introduces a
-    // value.
-    //
-    // Invoke as `start_value!(term; _ => todo!())`.
-    //
-    // The first argument is an identifier of type `Option<Term>` that is being
-    // matched upon.
-    //
-    // The second argument is a list of match arms of the form
-    // `pattern => expression` that SHALL complete the match expression.
-    //
-    // The default set of actions matches on one of the following: `None`,
-    // `Some(term)` where `term` matches anything but `Term::Break`. The second
-    // argument must specify what to do when a `Term::Break` is encountered
-    // when expecting a `Value`.
-    macro_rules! start_value {
-        ($id:ident; $( $pattern:pat => $expr:expr )+) => {
-            match $id {
-                None                  => Ok(None),
-                Some(Term::Uint(n))   => shift(State::ValueUint(n)),
-                Some(Term::Nint(n))   => shift(State::ValueNint(n)),
-                Some(Term::Float(n))  => shift(State::ValueFloat(n)),
-                Some(Term::Simple(n)) => shift(State::ValueSimple(n)),
-                Some(Term::Tag(t))    => shift(State::TagNumber(t)),
-                Some(Term::Bstr(b))   => shift(State::BstrBstr(b)),
-                Some(Term::BstrX)     => shift(State::BstrXSeqOpen),
-                Some(Term::Tstr(t))   => shift(State::TstrTstr(t)),
-                Some(Term::TstrX)     => shift(State::TstrXSeqOpen),
-                Some(Term::Array(n))  => shift(State::ValueArray(0, n)),
-                Some(Term::ArrayX)    => shift(State::ArrayXSeqOpen),
-                Some(Term::Map(n))    => shift(State::ValueMap(0, 2 * n)),
-                Some(Term::MapX)      => shift(State::MapXSeqOpen),
-                $( $pattern           => $expr, )+
-            }
-        }
-    }
-
-    // Default set of actions when reading a terminal symbol that introduces a
-    // value. Panics when encountering an unexpected terminal symbol.
-    macro_rules! start_value_or_panic {
-        ($id:ident) => {
-            start_value!($id;
-                         Some(term) => panic!(
-                             "Error: Unhandled action for state:{state} and input:{term}"
-                         ))
-        }
-    }
-
-    // Default set of actions when reading a terminal symbol that introduces a
-    // byte string of indefinite length.
-    //
-    // Invoke as `start_bstrx!(term; _ => todo!())`.
-    //
-    // The first argument is an identifier of type `Option<Term>` that is being
-    // matched upon.
-    //
-    // The second argument is a list of match arms of the form
-    // `pattern => expression` that SHALL complete the match expression.
-    //
-    // The default set of actions matches on one of the following: `None`,
-    // `Some(Term::Break)`, and `Some(Term::Bstr)`. The second argument must
-    // specify what to do when another terminal symbol is encountered when
-    // expecting a `Bstr`.
-    macro_rules! start_bstrx {
-        ($id: ident; $( $pattern:pat => $expr:expr )+) => {
-            match term {
-                None                    => Ok(None),
-                Some(Term::Break)       => shift(State::BstrXSeqBreak),
-                Some(Term::Bstr(bytes)) => shift(State::BstrXSeqBstr(bytes)),
-                $( $pattern             => $expr, )+
-            }
-        }
-    }
 
-    // Default set of actions when reading a terminal symbol that introduces a
-    // byte string of indefinite length. Panics when encountering an unexpected
-    // terminal symbol.
-    macro_rules! start_bstrx_or_panic {
-        ($id:ident) => {
-            start_bstrx!($id;
-                         Some(term) => panic!(
-                             "Error: Unhandled action for state:{state} and input:{term}"
-                         ))
+    /// Determines the action to take when the terminal symbol `term` is
+    /// consumed while in state `state`.
+    fn next_action(
+        state: &State,
+        term: Option<Term>,
+    ) -> Result<Option<Action<B>>, Error> {
+        let Some(entry) = TABLE.entry(state.item) else {
+            return Err(Error::Internal);
+        };
+        match entry.reduce.map(usize::from) {
+            Some(0) => return Ok(Some(Action::Accept)),
+            Some(rule) => match Self::PRODUCTIONS.get(rule - 1) {
+                Some(production) => {
+                    return Ok(Some(Action::Reduce(*production)));
+                }
+                None => return Err(Error::Internal),
+            },
+            None => {}
         }
-    }
-
-    // Default set of actions when reading a terminal symbol that introduces a
-    // text string of indefinite length.
-    //
-    // Invoke as `start_tstrx!(term; _ => todo!())`.
-    //
-    // The first argument is an identifier of type `Option<Term>` that is being
-    // matched upon.
-    //
-    // The second argument is a list of match arms of the form
-    // `pattern => expression` that SHALL complete the match expression.
-    //
-    // The default set of actions matches on one of the following: `None`,
-    // `Some(Term::Break)`, and `Some(Term::Tstr)`. The second argument must
-    // specify what to do when another terminal symbol is encountered when
-    // expecting a `Tstr`.
-    macro_rules! start_tstrx {
-        ($id: ident; $( $pattern:pat => $expr:expr )+) => {
-            match term {
-                None                    => Ok(None),
-                Some(Term::Break)       => shift(State::TstrXSeqBreak),
-                Some(Term::Tstr(bytes)) => shift(State::TstrXSeqTstr(bytes)),
-                $( $pattern             => $expr, )+
+        let Some(term) = term else {
+            return Ok(None);
+        };
+        match entry.shift(term.kind()) {
+            Some(target) => {
+                Ok(Some(Action::Shift(State::shift(target, term)?)))
             }
+            None => Err(Error::UnexpectedT(entry.expected(), term)),
         }
     }
-
-    // Default set of actions when reading a terminal symbol that introduces a
-    // text string of indefinite length. Panics when encountering an unexpected
-    // terminal symbol.
-    macro_rules! start_tstrx_or_panic {
-        ($id:ident) => {
-            start_tstrx!($id;
-                         Some(term) => panic!(
-                             "Error: Unhandled action for state:{state} and input:{term}"
-                         ))
-        }
-    }
-
-    match state {
-        State::Invalid                    => panic!("Error: Invalid state"),
-
-        State::Accept                     => accept(),
-
-        State::ValueUint(_)               => reduce(&PRODUCTION_01),
-        State::ValueNint(_)               => reduce(&PRODUCTION_02),
-        State::ValueFloat(_)              => reduce(&PRODUCTION_03),
-        State::ValueBstr                  => reduce(&PRODUCTION_04),
-        State::ValueTstr                  => reduce(&PRODUCTION_05),
-        State::ValueSimple(_)             => reduce(&PRODUCTION_06),
-        State::ValueTag                   => reduce(&PRODUCTION_07),
-        State::ValueArray(k, n) if k == n => reduce(&PRODUCTION_08),
-        State::ValueArrayX                => reduce(&PRODUCTION_09),
-        State::ValueMap(k, n) if k == n   => reduce(&PRODUCTION_10),
-        State::ValueMapX                  => reduce(&PRODUCTION_11),
-
-        State::BstrBstr(_)                => reduce(&PRODUCTION_12),
-        State::BstrBstrX                  => reduce(&PRODUCTION_13),
-
-        State::TstrTstr(_)                => reduce(&PRODUCTION_14),
-        State::TstrTstrX                  => reduce(&PRODUCTION_15),
-
-        State::BstrXSeqBreak              => reduce(&PRODUCTION_16),
-        State::BstrXSeqMore               => reduce(&PRODUCTION_17),
-
-        State::TstrXSeqBreak              => reduce(&PRODUCTION_18),
-        State::TstrXSeqMore               => reduce(&PRODUCTION_19),
-
-        State::ArrayXSeqBreak             => reduce(&PRODUCTION_20),
-        State::ArrayXSeqMore              => reduce(&PRODUCTION_21),
-
-        State::MapXSeqBreak               => reduce(&PRODUCTION_22),
-        State::MapXSeqMore                => reduce(&PRODUCTION_23),
-
-        State::Init                       => start_value_or_panic!(term),
-        State::TagNumber(_)               => start_value_or_panic!(term),
-        State::ValueArray(k, m) if k < m  => start_value_or_panic!(term),
-        State::ValueMap(k, m) if k < m    => start_value_or_panic!(term),
-        State::MapXSeqValue1              => start_value_or_panic!(term),
-        State::BstrXSeqOpen               => start_bstrx_or_panic!(term),
-        State::BstrXSeqBstr(_)            => start_bstrx_or_panic!(term),
-        State::TstrXSeqOpen               => start_tstrx_or_panic!(term),
-        State::TstrXSeqTstr(_)            => start_tstrx_or_panic!(term),
-
-        State::ArrayXSeqOpen              => start_value!(term;
-            Some(Term::Break)             => shift(State::ArrayXSeqBreak)
-        ),
-
-        State::ArrayXSeqValue             => start_value!(term;
-            Some(Term::Break)             => shift(State::ArrayXSeqBreak)
-        ),
-
-        State::MapXSeqOpen                => start_value!(term;
-            Some(Term::Break)             => shift(State::MapXSeqBreak)
-        ),
-
-        State::MapXSeqValue2              => start_value!(term;
-            Some(Term::Break)             => shift(State::MapXSeqBreak)
-        ),
-
-        State::ValueArray(k, n) if k > n => shift(State::Invalid),
-        State::ValueArray(_, _)          => shift(State::Invalid),
-
-        State::ValueMap(k, n) if k > n   => shift(State::Invalid),
-        State::ValueMap(_, _)            => shift(State::Invalid),
-
-    }
 }
 
+/// Determines the state to enter from state `state` when the parser has
+/// reduced a production to `nt`.
 fn goto(state: Option<&State>, nt: NonTerm) -> Result<State, Error> {
-    match state {
-        None => Err(Error::Unexpected(format!("Not handled: goto state:⊥, nt:{nt}"))),
-        Some(state) => goto2(state, nt),
-    }
-}
-
-fn goto2(state: &State, nt: NonTerm) -> Result<State, Error> {
-    macro_rules! unexpected {
-        (was: $id:ident, expected: [$($exp:path),+]) => {
-            Err(Error::UnexpectedNT(vec![$($exp),*], $id))
-        }
-    }
-    macro_rules! goto {
-        ($id:ident; [ $( $path:path => $expr:expr ),* $(,)?]) => {
-            match $id {
-                $( $path => $expr, )*
-                other    => unexpected!(was: other, expected: [$($path),*]),
-            }
-        };
-        ($id:ident; $path:path => $expr:expr $(,)?) => {
-            match $id {
-                $path => $expr,
-                other => unexpected!(was: other, expected: [$path]),
-            }
-        }
-    }
-    use State::*;
-    match state {
-        Init => goto!(nt; [
-            NonTerm::Value => Ok(Accept),
-            NonTerm::Bstr => Ok(ValueBstr),
-            NonTerm::Tstr => Ok(ValueTstr),
-        ]),
-        TagNumber(_) => goto!(nt; NonTerm::Value => Ok(ValueTag)),
-        BstrXSeqOpen => goto!(nt; NonTerm::BstrXSeq => Ok(BstrBstrX)),
-        BstrXSeqBstr(_) => goto!(nt; NonTerm::BstrXSeq => Ok(BstrXSeqMore)),
-        TstrXSeqOpen => goto!(nt; NonTerm::TstrXSeq => Ok(TstrTstrX)),
-        TstrXSeqTstr(_) => goto!(nt; NonTerm::TstrXSeq => Ok(TstrXSeqMore)),
-        ArrayXSeqOpen => goto!(nt; [
-            NonTerm::ArrayXSeq => Ok(ValueArrayX),
-            NonTerm::Value => Ok(ArrayXSeqValue),
-        ]),
-        ArrayXSeqValue => goto!(nt; [
-            NonTerm::ArrayXSeq => Ok(ArrayXSeqMore),
-            NonTerm::Value => Ok(ArrayXSeqValue),
-        ]),
-        ValueArray(k, n) => goto!(nt; NonTerm::Value => Ok(State::array_next(*k, *n)),),
-        MapXSeqOpen => goto!(nt; [
-            NonTerm::MapXSeq => Ok(ValueMapX),
-            NonTerm::Value => Ok(MapXSeqValue1),
-        ]),
-        MapXSeqValue1 => goto!(nt; NonTerm::Value => Ok(MapXSeqValue2)),
-        MapXSeqValue2 => goto!(nt; [
-            NonTerm::MapXSeq => Ok(MapXSeqMore),
-            NonTerm::Value => Ok(MapXSeqValue1),
-        ]),
-        ValueMap(k, n) => goto!(nt; NonTerm::Value => Ok(State::map_next(*k, *n))),
-        other => Err(Error::Unexpected(format!("Not handled: goto state:{other}, nt:{nt}"))),
+    let Some(state) = state else {
+        let message = format!("Not handled: goto state:⊥, nt:{nt}");
+        return Err(Error::Unexpected(message));
+    };
+    let Some(entry) = TABLE.entry(state.item) else {
+        return Err(Error::Internal);
+    };
+    match entry.goto(nt) {
+        Some(target) => Ok(State::goto(target, state)),
+        None => Err(Error::UnexpectedNT(entry.expected_nt(), nt)),
     }
 }
 
-/******************************************************************************\
- *                                                                             *
- * Productions                                                                 *
- *                                                                             *
- * 01: <VALUE>     → %uint                                                     *
- * 02: <VALUE>     → %nint                                                     *
- * 03: <VALUE>     → %float                                                    *
- * 04: <VALUE>     → <BSTR>                                                    *
- * 05: <VALUE>     → <TSTR>                                                    *
- * 06: <VALUE>     → %simple                                                   *
- * 07: <VALUE>     → %tag value                                                *
- * 08: <VALUE>     → %array(n) {n}<VALUE>                                      *
- * 09: <VALUE>     → %arrayx <ARRAYXSEQ>                                       *
- * 10: <VALUE>     → %map(n) {2n}<VALUE>                                       *
- * 11: <VALUE>     → %mapx <MAPXSEQ>                                           *
- * 12: <BSTR>      → %bstr                                                     *
- * 13: <BSTR>      → %bstrx <BSTRXSEQ>                                         *
- * 14: <TSTR>      → %tstr                                                     *
- * 15: <TSTR>      → %tstrx <TSTRXSEQ>                                         *
- * 16: <BSTRXSEQ>  → %break                                                    *
- * 17: <BSTRXSEQ>  → <BSTR> <BSTRXSEQ>                                         *
- * 18: <TSTRXSEQ>  → %break                                                    *
- * 19: <TSTRXSEQ>  → <TSTR> <TSTRXSEQ>                                         *
- * 20: <ARRAYXSEQ> → %break                                                    *
- * 21: <ARRAYXSEQ> → <VALUE> <ARRAYXSEQ>                                       *
- * 22: <MAPXSEQ>   → %break                                                    *
- * 23: <MAPXSEQ>   → <VALUE> <VALUE> <MAPXSEQ>                                 *
- *                                                                             *
- * Productions, extended                                                       *
- *                                                                             *
- * 00: <START> → <VALUE>                                                       *
- *                                                                             *
-\******************************************************************************/
-
-macro_rules! production {
-    ( $left:path => [ $($right:path),* ] ) => {};
-    ( $left:path => [ $($right:path),* ; $mul:expr ] ) => {};
+impl<B: Builder> Parser<B> {
+    /// Productions of [GRAMMAR] with their reduction functions, except for
+    /// the start production, which the parser accepts
+    const PRODUCTIONS: [Production<B>; 23] = [
+        Production { num_id: 1, reduce: Self::reduce01 },
+        Production { num_id: 2, reduce: Self::reduce02 },
+        Production { num_id: 3, reduce: Self::reduce03 },
+        Production { num_id: 4, reduce: Self::reduce04 },
+        Production { num_id: 5, reduce: Self::reduce05 },
+        Production { num_id: 6, reduce: Self::reduce06 },
+        Production { num_id: 7, reduce: Self::reduce07 },
+        Production { num_id: 8, reduce: Self::reduce08 },
+        Production { num_id: 9, reduce: Self::reduce09 },
+        Production { num_id: 10, reduce: Self::reduce10 },
+        Production { num_id: 11, reduce: Self::reduce11 },
+        Production { num_id: 12, reduce: Self::reduce12 },
+        Production { num_id: 13, reduce: Self::reduce13 },
+        Production { num_id: 14, reduce: Self::reduce14 },
+        Production { num_id: 15, reduce: Self::reduce15 },
+        Production { num_id: 16, reduce: Self::reduce16 },
+        Production { num_id: 17, reduce: Self::reduce17 },
+        Production { num_id: 18, reduce: Self::reduce18 },
+        Production { num_id: 19, reduce: Self::reduce19 },
+        Production { num_id: 20, reduce: Self::reduce20 },
+        Production { num_id: 21, reduce: Self::reduce21 },
+        Production { num_id: 22, reduce: Self::reduce22 },
+        Production { num_id: 23, reduce: Self::reduce23 },
+    ];
 }
 
-production!(NonTerm::Value     => [Term::Uint]);
-production!(NonTerm::Value     => [Term::Nint]);
-production!(NonTerm::Value     => [Term::Float]);
-production!(NonTerm::Value     => [NonTerm::Bstr]);
-production!(NonTerm::Value     => [NonTerm::Tstr]);
-production!(NonTerm::Value     => [Term::Simple]);
-production!(NonTerm::Value     => [Term::Tag, NonTerm::Value]);
-production!(NonTerm::Value     => [Term::Array(n), NonTerm::Value; n]);
-production!(NonTerm::Value     => [Term::ArrayX, NonTerm::ArrayXSeq]);
-production!(NonTerm::Value     => [Term::Map(n), NonTerm::Value; 2*n]);
-production!(NonTerm::Value     => [Term::MapX, NonTerm::MapXSeq]);
-production!(NonTerm::Bstr      => [Term::Bstr]);
-production!(NonTerm::Bstr      => [Term::BstrX, NonTerm::BstrXSeq]);
-production!(NonTerm::Tstr      => [Term::Tstr]);
-production!(NonTerm::Tstr      => [Term::TstrX, NonTerm::TstrXSeq]);
-production!(NonTerm::BstrXSeq  => [Term::Break]);
-production!(NonTerm::BstrXSeq  => [NonTerm::Bstr, NonTerm::BstrXSeq]);
-production!(NonTerm::TstrXSeq  => [Term::Break]);
-production!(NonTerm::TstrXSeq  => [NonTerm::Tstr, NonTerm::TstrXSeq]);
-production!(NonTerm::ArrayXSeq => [Term::Break]);
-production!(NonTerm::ArrayXSeq => [NonTerm::Value, NonTerm::ArrayXSeq]);
-production!(NonTerm::MapXSeq   => [Term::Break]);
-production!(NonTerm::MapXSeq   => [NonTerm::Value, NonTerm::MapXSeq]);
-
-const PRODUCTION_01: Production = Production {
-    num_id: 1,
-    left: NonTermExt::Value,
-    right: &["%uint"],
-    reduce: Parser::reduce01,
-};
-
-const PRODUCTION_02: Production = Production {
-    num_id: 2,
-    left: NonTermExt::Value,
-    right: &["%nint"],
-    reduce: Parser::reduce02,
-};
-
-const PRODUCTION_03: Production = Production {
-    num_id: 3,
-    left: NonTermExt::Value,
-    right: &["%float"],
-    reduce: Parser::reduce03,
-};
-
-const PRODUCTION_04: Production = Production {
-    num_id: 4,
-    left: NonTermExt::Value,
-    right: &["<BSTR>"],
-    reduce: Parser::reduce04,
-};
-
-const PRODUCTION_05: Production = Production {
-    num_id: 5,
-    left: NonTermExt::Value,
-    right: &["<TSTR>"],
-    reduce: Parser::reduce05,
-};
-
-const PRODUCTION_06: Production = Production {
-    num_id: 6,
-    left: NonTermExt::Value,
-    right: &["%simple"],
-    reduce: Parser::reduce06,
-};
-
-const PRODUCTION_07: Production = Production {
-    num_id: 7,
-    left: NonTermExt::Value,
-    right: &["%tag", "<VALUE>"],
-    reduce: Parser::reduce07,
-};
-
-const PRODUCTION_08: Production = Production {
-    num_id: 8,
-    left: NonTermExt::Value,
-    right: &["%array(n)", "{n}<VALUE>"],
-    reduce: Parser::reduce08,
-};
-
-const PRODUCTION_09: Production = Production {
-    num_id: 9,
-    left: NonTermExt::Value,
-    right: &["%arrayx", "<ARRAYXSEQ>"],
-    reduce: Parser::reduce09,
-};
-
-const PRODUCTION_10: Production = Production {
-    num_id: 10,
-    left: NonTermExt::Value,
-    right: &["%map(n)", "{2n}<VALUE>"],
-    reduce: Parser::reduce10,
-};
-
-const PRODUCTION_11: Production = Production {
-    num_id: 11,
-    left: NonTermExt::Value,
-    right: &["%mapx", "<MAPXSEQ>"],
-    reduce: Parser::reduce11,
-};
-
-const PRODUCTION_12: Production = Production {
-    num_id: 12,
-    left: NonTermExt::Bstr,
-    right: &["%bstr"],
-    reduce: Parser::reduce12,
-};
-
-const PRODUCTION_13: Production = Production {
-    num_id: 13,
-    left: NonTermExt::Bstr,
-    right: &["%bstrx", "<BSTRXSEQ>"],
-    reduce: Parser::reduce13,
-};
-
-const PRODUCTION_14: Production = Production {
-    num_id: 14,
-    left: NonTermExt::Tstr,
-    right: &["%tstr"],
-    reduce: Parser::reduce14,
-};
-
-const PRODUCTION_15: Production = Production {
-    num_id: 15,
-    left: NonTermExt::Tstr,
-    right: &["%tstrx", "<TSTRXSEQ>"],
-    reduce: Parser::reduce15,
-};
-
-const PRODUCTION_16: Production = Production {
-    num_id: 16,
-    left: NonTermExt::BstrXSeq,
-    right: &["%break"],
-    reduce: Parser::reduce16,
-};
-
-const PRODUCTION_17: Production = Production {
-    num_id: 17,
-    left: NonTermExt::BstrXSeq,
-    right: &["<BSTR>", "<BSTRXSEQ>"],
-    reduce: Parser::reduce17,
-};
-
-const PRODUCTION_18: Production = Production {
-    num_id: 18,
-    left: NonTermExt::TstrXSeq,
-    right: &["%break"],
-    reduce: Parser::reduce18,
-};
-
-const PRODUCTION_19: Production = Production {
-    num_id: 19,
-    left: NonTermExt::TstrXSeq,
-    right: &["<TSTR>", "<TSTRXSEQ>"],
-    reduce: Parser::reduce19,
-};
-
-const PRODUCTION_20: Production = Production {
-    num_id: 20,
-    left: NonTermExt::ArrayXSeq,
-    right: &["%break"],
-    reduce: Parser::reduce20,
-};
-
-const PRODUCTION_21: Production = Production {
-    num_id: 21,
-    left: NonTermExt::ArrayXSeq,
-    right: &["<VALUE>", "<ARRAYXSEQ>"],
-    reduce: Parser::reduce21,
-};
-
-const PRODUCTION_22: Production = Production {
-    num_id: 22,
-    left: NonTermExt::MapXSeq,
-    right: &["%break"],
-    reduce: Parser::reduce22,
-};
-
-const PRODUCTION_23: Production = Production {
-    num_id: 23,
-    left: NonTermExt::MapXSeq,
-    right: &["<VALUE>", "<VALUE>", "<MAPXSEQ>"],
-    reduce: Parser::reduce23,
-};
-
 ////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
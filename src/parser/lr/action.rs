@@ -21,8 +21,8 @@ use super::*;
 
 // Actions
 #[derive(Debug)]
-pub enum Action<'a> {
+pub enum Action<B: Builder> {
     Accept,
-    Reduce(&'a Production<'a>),
+    Reduce(Production<B>),
     Shift(State),
 }
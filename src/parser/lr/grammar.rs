@@ -16,41 +16,136 @@ General Public License for more details.
 You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
+//! Grammar of the LR parser
 //!
-//! This is an experimental approach to a bottom-up parser for the CBOR grammar.
-//!
-//! # LR(1) grammar for CBOR
+//! The LR parser implements the following grammar. Its action and goto tables
+//! are derived from the productions listed in [GRAMMAR], see [super::table].
 //!
 //! ```ABNF
-//! 00 start     = value;
-//!
-//! 01 value     = %uint;
-//! 02 value     = %nint;
-//! 03 value     = %simple;
-//! 04 value     = %float;
-//! 50 value     = %tag(n) value;
-//! 06 value     = %array(n) {n}value;
-//! 07 value     = %arrayx arrayxseq %break;
-//! 08 value     = %map(n) {2n}value;
-//! 09 value     = %mapx mapxseq %break;
-//! 10 value     = bstr;
-//! 11 value     = tstr;
-//!
-//! 12 arrayxseq = %empty;
-//! 13 arrayxseq = arrayxseq value;
-//!
-//! 14 mapxseq   = %empty;
-//! 15 mapxseq   = mapxseq value value;
-//!
-//! 16 bstr      = %bstr(bytes);
-//! 17 bstr      = %bstrx bstrxseq;
-//!
-//! 18 bstrxseq  = %break;
-//! 19 bstrxseq  = bstr bstrxseq;
+//! 00 <START>     → <VALUE>
+//! 01 <VALUE>     → %uint
+//! 02 <VALUE>     → %nint
+//! 03 <VALUE>     → %float
+//! 04 <VALUE>     → <BSTR>
+//! 05 <VALUE>     → <TSTR>
+//! 06 <VALUE>     → %simple
+//! 07 <VALUE>     → %tag <VALUE>
+//! 08 <VALUE>     → %array(n) {n}<VALUE>
+//! 09 <VALUE>     → %arrayx <ARRAYXSEQ>
+//! 10 <VALUE>     → %map(n) {2n}<VALUE>
+//! 11 <VALUE>     → %mapx <MAPXSEQ>
+//! 12 <BSTR>      → %bstr
+//! 13 <BSTR>      → %bstrx <BSTRXSEQ>
+//! 14 <TSTR>      → %tstr
+//! 15 <TSTR>      → %tstrx <TSTRXSEQ>
+//! 16 <BSTRXSEQ>  → %break
+//! 17 <BSTRXSEQ>  → <BSTR> <BSTRXSEQ>
+//! 18 <TSTRXSEQ>  → %break
+//! 19 <TSTRXSEQ>  → <TSTR> <TSTRXSEQ>
+//! 20 <ARRAYXSEQ> → %break
+//! 21 <ARRAYXSEQ> → <VALUE> <ARRAYXSEQ>
+//! 22 <MAPXSEQ>   → %break
+//! 23 <MAPXSEQ>   → <VALUE> <VALUE> <MAPXSEQ>
+//! ```
 //!
-//! 20 tstr      = %tstr(bytes);
-//! 21 tstr      = %tstrx tstrxseq %break;
+//! The grammar is the one of [crate::parser::grammar]: the chunks of an
+//! indefinite-length string are strings of the same type.
 //!
-//! 22 tstrxseq  = %empty;
-//! 23 tstrxseq  = tstrxseq tstr;
-//! ```
+//! The repetition `{n}<VALUE>` is resolved at run time: the parser counts the
+//! values of a definite-length array or map in its state, see [State].
+
+use super::*;
+use std::fmt;
+
+/// Symbol on the right-hand side of a production
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Symbol {
+    /// Terminal symbol of this kind
+    T(Kind),
+    /// Non-terminal symbol
+    N(NonTerm),
+    /// Non-terminal symbol, repeated this many times the payload of the
+    /// preceding terminal symbol
+    Repeat(u64, NonTerm),
+}
+
+/// Production of the grammar
+#[derive(Clone, Copy, Debug)]
+pub struct Rule {
+    /// Left-hand side symbol
+    pub left: NonTermExt,
+    /// Right-hand side symbols
+    pub right: &'static [Symbol],
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symbol::T(kind) => {
+                write!(f, "%{}", format!("{kind:?}").to_lowercase())
+            }
+            Symbol::N(non_term) => non_term.fmt(f),
+            Symbol::Repeat(1, non_term) => write!(f, "{{n}}{non_term}"),
+            Symbol::Repeat(k, non_term) => write!(f, "{{{k}n}}{non_term}"),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} →", self.left)?;
+        self.right
+            .iter()
+            .try_for_each(|symbol| write!(f, " {symbol}"))
+    }
+}
+
+macro_rules! symbol {
+    (T($kind:ident)) => {
+        Symbol::T(Kind::$kind)
+    };
+    (N($non_term:ident)) => {
+        Symbol::N(NonTerm::$non_term)
+    };
+    (Repeat($k:literal, $non_term:ident)) => {
+        Symbol::Repeat($k, NonTerm::$non_term)
+    };
+}
+
+macro_rules! production {
+    ($left:ident => [$($symbol:ident $args:tt),*]) => {
+        Rule {
+            left: NonTermExt::$left,
+            right: &[$(symbol!($symbol $args)),*],
+        }
+    };
+}
+
+/// Productions of the grammar, indexed by their numerical identifiers
+#[rustfmt::skip]
+pub const GRAMMAR: [Rule; 24] = [
+    production!(Start     => [N(Value)]),
+    production!(Value     => [T(Uint)]),
+    production!(Value     => [T(Nint)]),
+    production!(Value     => [T(Float)]),
+    production!(Value     => [N(Bstr)]),
+    production!(Value     => [N(Tstr)]),
+    production!(Value     => [T(Simple)]),
+    production!(Value     => [T(Tag), N(Value)]),
+    production!(Value     => [T(Array), Repeat(1, Value)]),
+    production!(Value     => [T(ArrayX), N(ArrayXSeq)]),
+    production!(Value     => [T(Map), Repeat(2, Value)]),
+    production!(Value     => [T(MapX), N(MapXSeq)]),
+    production!(Bstr      => [T(Bstr)]),
+    production!(Bstr      => [T(BstrX), N(BstrXSeq)]),
+    production!(Tstr      => [T(Tstr)]),
+    production!(Tstr      => [T(TstrX), N(TstrXSeq)]),
+    production!(BstrXSeq  => [T(Break)]),
+    production!(BstrXSeq  => [N(Bstr), N(BstrXSeq)]),
+    production!(TstrXSeq  => [T(Break)]),
+    production!(TstrXSeq  => [N(Tstr), N(TstrXSeq)]),
+    production!(ArrayXSeq => [T(Break)]),
+    production!(ArrayXSeq => [N(Value), N(ArrayXSeq)]),
+    production!(MapXSeq   => [T(Break)]),
+    production!(MapXSeq   => [N(Value), N(Value), N(MapXSeq)]),
+];
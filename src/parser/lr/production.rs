@@ -20,28 +20,31 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 use super::*;
 
 /// Production of a grammar
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct Production<'a> {
-    /// Numerical identifier
+pub struct Production<B: Builder> {
+    /// Numerical identifier, the index of the production in [GRAMMAR]
     pub num_id: usize,
-    /// Left-hand side symbol
-    pub left: NonTermExt,
-    /// Right-hand side symbols
-    pub right: &'a [&'a str],
     /// Reduction function that reduces the right-hand side to the left-hand side.
-    pub reduce: fn(&mut Parser) -> Result<NonTerm, Error>,
+    pub reduce: fn(&mut Parser<B>) -> Result<NonTerm, Error>,
 }
 
-impl<'a> std::fmt::Display for Production<'a> {
+impl<B: Builder> Clone for Production<B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B: Builder> Copy for Production<B> {}
+
+impl<B: Builder> std::fmt::Debug for Production<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Production")
+            .field("num_id", &self.num_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: Builder> std::fmt::Display for Production<B> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Production {
-            num_id,
-            left,
-            right,
-            reduce,
-        } = self;
-        let _ignored = &[reduce];
-        write!(f, "{:3} {} → {}", num_id, left, right.join(" "))
+        write!(f, "{:3} {}", self.num_id, GRAMMAR[self.num_id])
     }
 }
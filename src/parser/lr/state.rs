@@ -17,235 +17,117 @@ You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
-/// Parser states
-#[derive(Clone, Debug, Default)]
-pub enum State {
-    /// The parser is in an invalid state.
-    /// ```plain
-    /// <ERROR> ∙
-    /// ```
-    #[default]
-    Invalid,
-
-    /// The parser is ready to receive its first token.
-    /// ```plain
-    /// <START> → ∙ <VALUE>
-    /// ----------------------------------
-    /// <VALUE> → ∙ %uint
-    /// <VALUE> → ∙ %tag <VALUE>
-    /// <VALUE> → ∙ %arrayxseq <ARRAYXSEQ>
-    /// ```
-    Init,
-
-    /// The parser has accepted a complete CBOR value.
-    /// ```plain
-    /// <START> → <VALUE> ∙
-    /// ```
-    Accept,
-
-    /// The parser has accepted an unsigned integer value.
-    /// ```plain
-    /// <VALUE> → %uint ∙
-    /// ```
-    ValueUint(u64),
-    /// The parser has accepted a negative integer value.
-    /// ```plain
-    /// <VALUE> → %nint ∙
-    /// ```
-    ValueNint(u64),
-    /// The parser has accepted a floating-point value.
-    /// ```plain
-    /// <VALUE> → %float(n) ∙
-    /// ```
-    ValueFloat(u64),
-    /// The parser has accepted a byte string.
-    /// ```plain
-    /// <VALUE> → <BSTR> ∙
-    /// ```
-    ValueBstr,
-    /// The parser has accepted a text string.
-    /// ```plain
-    /// <VALUE> → <TSTR> ∙
-    /// ```
-    ValueTstr,
-    /// The parser has accepted a simple value.
-    /// ```plain
-    /// <VALUE> → %simple ∙
-    /// ```
-    ValueSimple(u8),
-    /// The parser has accepted a tagged value.
-    /// ```plain
-    /// <VALUE> → %tag <VALUE> ∙
-    /// ```
-    ValueTag,
-    /// The parser has accepted an indefinite-length array.
-    /// ```plain
-    /// <VALUE> → %arrayx <ARRAYXSEQ> ∙
-    /// ```
-    ValueArrayX,
-    /// The parser has accepted an indefinite-length map.
-    /// ```plain
-    /// <VALUE> → %mapx <MAPXSEQ> ∙
-    /// ```
-    ValueMapX,
-
-    /// The parser has started parsing an indefinite-length byte string.
-    /// ```plain
-    /// <BSTR> → %bstrx ∙ <BSTRXSEQ>
-    /// ```
-    BstrXSeqOpen,
-    /// The parser has finished parsing an indefinite-length byte string.
-    /// ```plain
-    /// <BSTRXSEQ> → %break ∙
-    /// ```
-    BstrXSeqBreak,
-    /// The parser has accepted a byte string while parsing an indefinite-length
-    /// byte string.
-    /// ```plain
-    /// <BSTRXSEQ> → %bstr ∙ <BSTRXSEQ>
-    /// ```
-    BstrXSeqBstr(Vec<u8>),
-    /// The parser has integrated the parsed byte string into the currently parsed
-    /// indefinite-length byte string.
-    /// ```plain
-    /// <BSTRXSEQ> → <BSTR> <BSTRXSEQ> ∙
-    /// ```
-    BstrXSeqMore,
-    /// The parser has accepted a definite-length byte string.
-    /// ```plain
-    /// <BSTR> → %bstr ∙
-    /// ```
-    BstrBstr(Vec<u8>),
-    /// The parser has accepted an indefinite-length byte string.
-    /// ```plain
-    /// <BSTR> → %bstrx <BSTRXSEQ> ∙
-    /// ```
-    BstrBstrX,
-
-    /// The parser has started parsing an indefinite-length text string.
-    /// ```plain
-    /// <TSTR> → %tstrx ∙ <TSTRXSEQ>
-    /// ```
-    TstrXSeqOpen,
-    /// The parser has finished parsing an indefinite-length text string.
-    /// ```plain
-    /// <TSTRXSEQ> → %break ∙
-    /// ```
-    TstrXSeqBreak,
-    /// The parser has accepted a text string while parsing an indefinite-length
-    /// text string.
-    /// ```plain
-    /// <TSTRXSEQ> → %tstr ∙ <TSTRXSEQ>
-    /// ```
-    TstrXSeqTstr(Vec<u8>),
-    /// The parser has integrated the parsed text string into the currently parsed
-    /// indefinite-length text string.
-    /// ```plain
-    /// <TSTRXSEQ> → <TSTR> <TSTRXSEQ> ∙
-    /// ```
-    TstrXSeqMore,
-    /// The parser has accepted a definite-length text string.
-    /// ```plain
-    /// <TSTR> → %tstr ∙
-    /// ```
-    TstrTstr(Vec<u8>),
-    /// The parser has accepted an indefinite-length text string.
-    /// ```plain
-    /// <TSTR> → %tstrx <TSTRXSEQ> ∙
-    /// ```
-    TstrTstrX,
-
-    /// The parser has started parsing a tagged value.
-    /// ```plain
-    /// <VALUE> → %tag ∙ <VALUE>
-    /// ```
-    TagNumber(u64),
+use super::grammar::{GRAMMAR, Symbol};
+use super::table::Item;
+use super::*;
+
+/// Parser state
+///
+/// A state is identified by the kernel item of the tables, see
+/// [super::table]. It keeps the terminal symbol the parser has shifted to
+/// enter it, and counts the values of a repetition: the state of
+/// `<VALUE> → %array(n) ∙ {n}<VALUE>` holds `%array(n)`, and the parser
+/// enters `<VALUE> → %array(n) {n}<VALUE> ∙` once it has counted `n` values.
+#[derive(Clone, Debug)]
+pub struct State {
+    /// Kernel item that identifies the state
+    pub item: Item,
+    /// Terminal symbol shifted to enter the state
+    pub term: Option<Term>,
+    /// Number of values recognised, and expected, by a repetition
+    pub count: (u64, u64),
+}
 
-    /// The parser is parsing a definite-length array.
-    /// ```plain
-    /// <VALUE> → %array(n) {k}<VALUE> ∙ {n-k}<VALUE>
-    /// ```
-    ValueArray(u64, u64),
+impl State {
+    /// Returns the state of the parser ready to receive its first token.
+    pub fn init() -> State {
+        State::new(Item::new(0, 0))
+    }
 
-    /// The parser has started parsing an indefinite-length array.
-    /// ```plain
-    /// <VALUE> → %arrayx ∙ <ARRAYXSEQ>
-    /// ```
-    ArrayXSeqOpen,
-    /// The parser has finished parsing an indefinite-length array.
-    /// ```plain
-    /// <ARRAYXSEQ> → %break ∙
-    /// ```
-    ArrayXSeqBreak,
-    /// The parser has accepted a CBOR value while parsing an indefinite-length
-    /// array.
-    /// ```plain
-    /// <ARRAYXSEQ> → <VALUE> ∙ <ARRAYXSEQ>
-    /// ```
-    ArrayXSeqValue,
-    /// The parser has integrated the parsed value into the currently parsed
-    /// indefinite-length array.
-    /// ```plain
-    /// <ARRAYXSEQ> → <VALUE> <ARRAYXSEQ> ∙
-    /// ```
-    ArrayXSeqMore,
+    fn new(item: Item) -> State {
+        State {
+            item,
+            term: None,
+            count: (0, 0),
+        }
+    }
 
-    /// The parser is parsing a definite-length map.
-    /// ```plain
-    /// <VALUE> → %map(n) {k}<VALUE> ∙ {2n-k}<VALUE>
-    /// ```
-    ValueMap(u64, u64),
+    /// Returns the state of a repetition that has recognised `k` of `n`
+    /// values, which is the state past the repetition once `k` reaches `n`.
+    fn repeat(item: Item, k: u64, n: u64) -> State {
+        let item = if k < n { item } else { item.advance() };
+        State {
+            count: (k, n),
+            ..State::new(item)
+        }
+    }
 
-    /// The parser has started parsing an indefinite-length map.
-    /// ```plain
-    /// <VALUE> → %mapx ∙ <MAPXSEQ>
-    /// ```
-    MapXSeqOpen,
-    /// The parser has finished parsing an indefinite-length map.
-    /// ```plain
-    /// <MAPXSEQ> → %break ∙
-    /// ```
-    MapXSeqBreak,
-    /// The parser has accepted a CBOR value while parsing an indefinite-length
-    /// map.
-    /// ```plain
-    /// <MAPXSEQ> → <VALUE> ∙ <VALUE> <MAPXSEQ>
-    /// ```
-    MapXSeqValue1,
-    /// The parser has accepted a CBOR value while parsing an indefinite-length
-    /// map.
-    /// ```plain
-    /// <MAPXSEQ> → <VALUE> <VALUE> ∙ <MAPXSEQ>
-    /// ```
-    MapXSeqValue2,
-    /// The parser has integrated the parsed values into the currently parsed
-    /// indefinite-length map.
-    /// ```plain
-    /// <MAPXSEQ> → <VALUE> <VALUE> <MAPXSEQ> ∙
-    /// ```
-    MapXSeqMore,
-}
+    /// Returns the state identified by `item`, which the parser enters when
+    /// it shifts `term`.
+    ///
+    /// Fails if `term` starts a repetition too long to count.
+    pub fn shift(item: Item, term: Term) -> Result<State, Error> {
+        let state = match (item.next(), &term) {
+            (Some(Symbol::Repeat(k, _)), Term::Array(n) | Term::Map(n)) => {
+                let count = k.checked_mul(*n).ok_or(Error::Excessive(*n))?;
+                State::repeat(item, 0, count)
+            }
+            (Some(Symbol::Repeat(..)), _) => return Err(Error::Internal),
+            _ => State::new(item),
+        };
+        Ok(State {
+            term: Some(term),
+            ..state
+        })
+    }
 
-impl State {
-    pub fn array_next(k: u64, n: u64) -> State {
-        if k < n {
-            State::ValueArray(k + 1, n)
-        } else {
-            State::ValueArray(n, n)
+    /// Returns the state identified by `item`, which the parser enters from
+    /// state `from` when it has reduced a production.
+    pub fn goto(item: Item, from: &State) -> State {
+        match item.next() {
+            // The repetition loops back to its item.
+            Some(Symbol::Repeat(..)) => {
+                State::repeat(item, from.count.0 + 1, from.count.1)
+            }
+            _ => State::new(item),
         }
     }
 
-    pub fn map_next(k: u64, n: u64) -> State {
-        if k < n {
-            State::ValueMap(k + 1, n)
-        } else {
-            State::ValueMap(n, n)
+    /// Returns the number of states the parser has entered for the
+    /// right-hand side of the production, provided that the state completes
+    /// it.
+    pub fn depth(&self) -> u64 {
+        GRAMMAR[usize::from(self.item.rule)]
+            .right
+            .iter()
+            .map(|symbol| match symbol {
+                Symbol::Repeat(..) => self.count.1,
+                _ => 1,
+            })
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Returns `true` if the parser has completed a map entry when it enters
+    /// the state.
+    ///
+    /// That is after an even number of values of a definite-length map, and
+    /// after the second value of an entry of an indefinite-length map.
+    pub fn completes_entry(&self) -> bool {
+        let rule = &GRAMMAR[usize::from(self.item.rule)];
+        match rule.right {
+            [Symbol::T(Kind::Map), Symbol::Repeat(..)] => {
+                self.count.0 > 0 && self.count.0.is_multiple_of(2)
+            }
+            _ => rule.left == NonTermExt::MapXSeq && self.item.dot == 2,
         }
     }
 }
 
 impl std::fmt::Display for State {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self.count {
+            (0, 0) => write!(f, "{}", self.item),
+            (k, n) => write!(f, "{} ({k}/{n})", self.item),
+        }
     }
 }
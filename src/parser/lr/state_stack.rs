@@ -20,7 +20,7 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 use super::State;
 use super::Error;
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct StateStack {
     inner: Vec<State>,
     upper: usize,
@@ -29,7 +29,7 @@ pub struct StateStack {
 impl StateStack {
     pub fn cbor() -> StateStack {
         StateStack {
-            inner: vec![State::Init],
+            inner: vec![State::init()],
             upper: 16384,
         }
     }
@@ -41,7 +41,8 @@ impl StateStack {
     }
     pub fn push(&mut self, state: State) -> Result<(), Error> {
         if self.inner.len() < self.upper {
-            Ok(self.inner.push(state))
+            self.inner.push(state);
+            Ok(())
         } else {
             Err(Error::InsufficientStackSize)
         }
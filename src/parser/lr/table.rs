@@ -0,0 +1,341 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! Action and goto tables of the LR parser
+//!
+//! The tables are derived from the productions in [GRAMMAR] by the canonical
+//! LR(0) construction, which runs at compile time. Each state of the resulting
+//! automaton is identified by a single _kernel item_, so that the tables are
+//! indexed by items; a grammar with conflicts, or with a state that cannot be
+//! identified by a single item, fails to compile.
+//!
+//! A repetition `{n}<VALUE>` loops back to its item after each value. The
+//! item past the repetition becomes a state of its own, which the parser
+//! enters once it has counted `n` values.
+
+use super::grammar::{GRAMMAR, Symbol};
+use super::*;
+use std::fmt;
+
+/// Tables of the CBOR grammar
+pub static TABLE: Table = match Table::build() {
+    Ok(table) => table,
+    Err(message) => panic!("{}", message),
+};
+
+/// Number of kinds of terminal symbols, `%break` being the last one
+const KINDS: usize = Kind::Break as usize + 1;
+
+/// Number of non-terminal symbols, `<VALUE>` being the last one
+const NON_TERMS: usize = NonTerm::Value as usize + 1;
+
+/// Number of items per production
+const DOTS: usize = dots();
+
+/// Number of items of the grammar
+const ITEMS: usize = GRAMMAR.len() * DOTS;
+
+/// Position of the parser in the right-hand side of a production
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Item {
+    /// Numerical identifier of the production
+    pub rule: u8,
+    /// Number of symbols recognised
+    pub dot: u8,
+}
+
+/// Actions and transitions of the state identified by an item
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    /// Whether the item identifies a state
+    state: bool,
+    /// Production to reduce, if the item is complete
+    pub reduce: Option<u8>,
+    /// Successor states, indexed by the kind of terminal symbol
+    shift: [Option<(Kind, Item)>; KINDS],
+    /// Successor states, indexed by the non-terminal symbol
+    goto: [Option<(NonTerm, Item)>; NON_TERMS],
+}
+
+/// LR(0) action and goto tables
+#[derive(Debug)]
+pub struct Table {
+    entries: [Entry; ITEMS],
+}
+
+impl Item {
+    /// Returns the item of production `rule` with `dot` symbols recognised.
+    pub const fn new(rule: usize, dot: usize) -> Item {
+        assert!(rule < GRAMMAR.len() && dot < DOTS);
+        Item {
+            rule: rule as u8,
+            dot: dot as u8,
+        }
+    }
+
+    /// Returns the symbol following the dot, or `None` if the item is
+    /// complete.
+    pub const fn next(&self) -> Option<Symbol> {
+        let right = GRAMMAR[self.rule as usize].right;
+        if (self.dot as usize) < right.len() {
+            Some(right[self.dot as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the item with the dot moved past the next symbol.
+    pub const fn advance(&self) -> Item {
+        Item::new(self.rule as usize, self.dot as usize + 1)
+    }
+
+    /// Returns the index of the item in the tables.
+    const fn index(&self) -> usize {
+        self.rule as usize * DOTS + self.dot as usize
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = &GRAMMAR[usize::from(self.rule)];
+        write!(f, "{} →", rule.left)?;
+        for (index, symbol) in rule.right.iter().enumerate() {
+            let dot = if index == usize::from(self.dot) {
+                " ∙"
+            } else {
+                ""
+            };
+            write!(f, "{dot} {symbol}")?;
+        }
+        if usize::from(self.dot) >= rule.right.len() {
+            write!(f, " ∙")?;
+        }
+        Ok(())
+    }
+}
+
+impl Entry {
+    const EMPTY: Entry = Entry {
+        state: false,
+        reduce: None,
+        shift: [None; KINDS],
+        goto: [None; NON_TERMS],
+    };
+
+    /// Returns the successor state on a terminal symbol of kind `kind`.
+    pub fn shift(&self, kind: Kind) -> Option<Item> {
+        self.shift[kind as usize].map(|(_, item)| item)
+    }
+
+    /// Returns the successor state on the non-terminal symbol `non_term`.
+    pub fn goto(&self, non_term: NonTerm) -> Option<Item> {
+        self.goto[non_term as usize].map(|(_, item)| item)
+    }
+
+    /// Returns the kinds of terminal symbols the state can shift.
+    pub fn expected(&self) -> Vec<Kind> {
+        self.shift.iter().flatten().map(|(kind, _)| *kind).collect()
+    }
+
+    /// Returns the non-terminal symbols the state has transitions on.
+    pub fn expected_nt(&self) -> Vec<NonTerm> {
+        self.goto.iter().flatten().map(|(nt, _)| *nt).collect()
+    }
+}
+
+impl Table {
+    /// Builds the tables of [GRAMMAR], whose first production is the start
+    /// production.
+    ///
+    /// Fails if the grammar has conflicts, or a state that cannot be
+    /// identified by a single item.
+    const fn build() -> Result<Table, &'static str> {
+        let mut entries = [Entry::EMPTY; ITEMS];
+        let mut queued = [false; ITEMS];
+        let mut pending = [Item::new(0, 0); ITEMS];
+        let mut len = 1;
+        queued[0] = true;
+        while len > 0 {
+            len -= 1;
+            let kernel = pending[len];
+            let predicted = predict(kernel);
+            let mut entry = Entry::EMPTY;
+            entry.state = true;
+            // The closure consists of the kernel, and the items of the
+            // predicted productions with nothing recognised.
+            let mut rule = 0;
+            let mut item = kernel;
+            loop {
+                let target = match item.next() {
+                    None => {
+                        if entry.reduce.is_some() {
+                            return Err("Conflict between reductions");
+                        }
+                        entry.reduce = Some(item.rule);
+                        None
+                    }
+                    // The repetition loops until the count is exhausted.
+                    Some(Symbol::Repeat(_, non_term)) => {
+                        let past = item.advance();
+                        if !queued[past.index()] {
+                            queued[past.index()] = true;
+                            pending[len] = past;
+                            len += 1;
+                        }
+                        match entry.goto[non_term as usize] {
+                            Some((_, other)) if !same(other, item) => {
+                                return Err("Conflict between transitions");
+                            }
+                            _ => {}
+                        }
+                        entry.goto[non_term as usize] = Some((non_term, item));
+                        Some(item)
+                    }
+                    Some(Symbol::N(non_term)) => {
+                        let target = item.advance();
+                        match entry.goto[non_term as usize] {
+                            Some((_, other)) if !same(other, target) => {
+                                return Err("Conflict between transitions");
+                            }
+                            _ => {}
+                        }
+                        entry.goto[non_term as usize] =
+                            Some((non_term, target));
+                        Some(target)
+                    }
+                    Some(Symbol::T(kind)) => {
+                        let target = item.advance();
+                        match entry.shift[kind as usize] {
+                            Some((_, other)) if !same(other, target) => {
+                                return Err("Conflict between shifts");
+                            }
+                            _ => {}
+                        }
+                        entry.shift[kind as usize] = Some((kind, target));
+                        Some(target)
+                    }
+                };
+                if let Some(target) = target
+                    && !queued[target.index()]
+                {
+                    queued[target.index()] = true;
+                    pending[len] = target;
+                    len += 1;
+                }
+                // Move on to the next predicted production.
+                while rule < GRAMMAR.len()
+                    && (!predicted[rule] || same(kernel, Item::new(rule, 0)))
+                {
+                    rule += 1;
+                }
+                if rule == GRAMMAR.len() {
+                    break;
+                }
+                item = Item::new(rule, 0);
+                rule += 1;
+            }
+            if entry.reduce.is_some() {
+                let mut kind = 0;
+                while kind < KINDS {
+                    if entry.shift[kind].is_some() {
+                        return Err("Conflict between a reduction and a shift");
+                    }
+                    kind += 1;
+                }
+            }
+            entries[kernel.index()] = entry;
+        }
+        Ok(Table { entries })
+    }
+
+    /// Returns the actions and transitions of the state identified by `item`.
+    pub fn entry(&self, item: Item) -> Option<&Entry> {
+        self.entries.get(item.index()).filter(|entry| entry.state)
+    }
+
+    /// Returns the states of the parser with their actions and transitions.
+    #[cfg(test)]
+    pub fn entries(&self) -> impl Iterator<Item = (Item, &Entry)> {
+        (0..GRAMMAR.len())
+            .flat_map(|rule| (0..DOTS).map(move |dot| (rule, dot)))
+            .map(|(rule, dot)| Item::new(rule, dot))
+            .filter_map(|item| Some((item, self.entry(item)?)))
+    }
+}
+
+/// Returns `true` if `a` and `b` are the same item.
+const fn same(a: Item, b: Item) -> bool {
+    a.rule == b.rule && a.dot == b.dot
+}
+
+/// Returns the number of items per production, that is one more than the
+/// length of the longest right-hand side.
+const fn dots() -> usize {
+    let mut dots = 0;
+    let mut rule = 0;
+    while rule < GRAMMAR.len() {
+        if GRAMMAR[rule].right.len() >= dots {
+            dots = GRAMMAR[rule].right.len() + 1;
+        }
+        rule += 1;
+    }
+    dots
+}
+
+/// Returns the productions predicted by `kernel`, that is the productions
+/// whose items with nothing recognised belong to the LR(0) closure of
+/// `kernel`.
+const fn predict(kernel: Item) -> [bool; GRAMMAR.len()] {
+    let mut predicted = [false; GRAMMAR.len()];
+    predict_from(kernel, &mut predicted);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut rule = 0;
+        while rule < GRAMMAR.len() {
+            if predicted[rule] {
+                changed |= predict_from(Item::new(rule, 0), &mut predicted);
+            }
+            rule += 1;
+        }
+    }
+    predicted
+}
+
+/// Marks the productions of the non-terminal symbol following the dot of
+/// `item`, and returns `true` if any of them was not marked yet.
+const fn predict_from(item: Item, predicted: &mut [bool]) -> bool {
+    let (Some(Symbol::N(non_term)) | Some(Symbol::Repeat(_, non_term))) =
+        item.next()
+    else {
+        return false;
+    };
+    let mut changed = false;
+    let mut rule = 0;
+    while rule < GRAMMAR.len() {
+        if let NonTermExt::NonTerm(left) = GRAMMAR[rule].left
+            && left as usize == non_term as usize
+            && !predicted[rule]
+        {
+            predicted[rule] = true;
+            changed = true;
+        }
+        rule += 1;
+    }
+    changed
+}
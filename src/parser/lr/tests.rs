@@ -17,6 +17,7 @@ You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::grammar::Symbol;
 use super::*;
 use crate::parser::Parser as _;
 
@@ -78,7 +79,7 @@ fn decode_tstr() {
     let text1 = random_text();
     let text2 = random_text();
     let text = [text1.clone(), text2.clone()].concat();
-    let expected = Value::Tstr(text);
+    let expected = Value::Tstr(String::from_utf8(text).unwrap());
     let token1 = Term::TstrX;
     let token2 = Term::Tstr(text1);
     let token3 = Term::Tstr(text2);
@@ -121,8 +122,7 @@ fn decode_array0() {
     let mut parser = Parser::cbor();
     let token = Term::Array(0);
     let expected = Value::Array(vec![]);
-    let actual;
-    actual = parser.consume(token).unwrap();
+    let actual = parser.consume(token).unwrap();
     assert_eq!(Some(expected), actual);
 }
 
@@ -193,8 +193,7 @@ fn decode_map0() {
     let mut parser = Parser::cbor();
     let token = Term::Map(0);
     let expected = Value::Map(vec![]);
-    let actual;
-    actual = parser.consume(token).unwrap();
+    let actual = parser.consume(token).unwrap();
     assert_eq!(Some(expected), actual);
 }
 
@@ -263,6 +262,361 @@ fn decode_mapx() {
     panic!("Test fell through the end");
 }
 
+#[test]
+fn decode_strings_nested() {
+    let mut parser = Parser::cbor();
+    let expected = Value::Array(vec![
+        Value::Bstr(vec![0x01]),
+        Value::Tag(0, Box::new(Value::Tstr("a".into()))),
+        Value::Map(vec![(Value::Tstr("b".into()), Value::Bstr(vec![]))]),
+    ]);
+    let tokens = [
+        Term::Array(3),
+        Term::Bstr(vec![0x01]),
+        Term::Tag(0),
+        Term::Tstr(b"a".to_vec()),
+        Term::MapX,
+        Term::TstrX,
+        Term::Tstr(b"b".to_vec()),
+        Term::Break,
+        Term::Bstr(vec![]),
+        Term::Break,
+    ];
+    for token in tokens {
+        if let Some(actual) = parser.consume(token).unwrap() {
+            assert_eq!(expected, actual);
+            return;
+        }
+    }
+    panic!("Test fell through the end");
+}
+
+#[test]
+fn decode_tag_deeply_nested() {
+    let mut parser = Parser::cbor();
+    let depth = 1_000;
+    for tag in 0..depth {
+        assert_eq!(None, parser.consume(Term::Tag(tag)).unwrap());
+    }
+    let mut result = parser.consume(Term::Uint(0)).unwrap().unwrap();
+    for tag in 0..depth {
+        let (actual, tagged) = result.as_tag().unwrap();
+        assert_eq!(tag, actual);
+        result = tagged;
+    }
+    assert_eq!(Value::Uint(0), result);
+}
+
+#[test]
+fn single_trailing_input() {
+    let mut parser = Parser::cbor();
+    assert_eq!(Some(Value::Uint(0)), parser.consume(Term::Uint(0)).unwrap());
+    let result = parser.consume(Term::Uint(1));
+    assert!(matches!(result, Err(Error::TrailingInput)));
+}
+
+#[test]
+fn sequence() {
+    let mut parser = Parser::sequence();
+    let tokens = [
+        Term::Uint(0),
+        Term::Array(2),
+        Term::Uint(1),
+        Term::Nint(2),
+        Term::Tag(3),
+        Term::Simple(4),
+    ];
+    let expected = vec![
+        Value::Uint(0),
+        Value::Array(vec![Value::Uint(1), Value::Nint(2)]),
+        Value::Tag(3, Box::new(Value::Simple(4))),
+    ];
+    let mut actual = Vec::new();
+    for token in tokens {
+        if let Some(value) = parser.consume(token).unwrap() {
+            actual.push(value);
+        }
+    }
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn reset() {
+    let mut parser = Parser::cbor();
+    assert_eq!(None, parser.consume(Term::Array(2)).unwrap());
+    assert_eq!(None, parser.consume(Term::Uint(1)).unwrap());
+    parser.reset();
+    assert_eq!(Some(Value::Uint(0)), parser.consume(Term::Uint(0)).unwrap());
+    parser.reset();
+    assert_eq!(Some(Value::Uint(1)), parser.consume(Term::Uint(1)).unwrap());
+}
+
+#[test]
+fn map_duplicate_keys() {
+    let tokens = || {
+        [
+            Term::Map(3),
+            Term::Uint(1),
+            Term::Uint(10),
+            Term::Uint(2),
+            Term::Uint(20),
+            Term::Uint(1),
+            Term::Uint(30),
+        ]
+    };
+    let decode = |duplicate_keys| {
+        let mut parser = Parser::cbor();
+        parser.set_options(Options {
+            duplicate_keys,
+            ..Options::default()
+        });
+        for token in tokens() {
+            if let Some(value) = parser.consume(token)? {
+                return Ok(value);
+            }
+        }
+        Err(Error::Incomplete)
+    };
+    let entry = |k, v| (Value::Uint(k), Value::Uint(v));
+    let actual = decode(DuplicateKeys::KeepAll).unwrap();
+    let expected = vec![entry(1, 10), entry(2, 20), entry(1, 30)];
+    assert_eq!(Value::Map(expected), actual);
+    let actual = decode(DuplicateKeys::KeepFirst).unwrap();
+    let expected = vec![entry(1, 10), entry(2, 20)];
+    assert_eq!(Value::Map(expected), actual);
+    let actual = decode(DuplicateKeys::KeepLast).unwrap();
+    let expected = vec![entry(1, 30), entry(2, 20)];
+    assert_eq!(Value::Map(expected), actual);
+    let error = decode(DuplicateKeys::Reject).unwrap_err();
+    assert!(matches!(error.cause(), Error::DuplicateKey(Value::Uint(1))));
+    assert_eq!(".{1}", error.path().unwrap().to_string());
+}
+
+#[test]
+fn mapx_duplicate_keys() {
+    let mut parser = Parser::cbor();
+    parser.set_options(Options {
+        duplicate_keys: DuplicateKeys::KeepLast,
+        ..Options::default()
+    });
+    let tokens = [
+        Term::MapX,
+        Term::Uint(1),
+        Term::Uint(10),
+        Term::Uint(2),
+        Term::Uint(20),
+        Term::Uint(1),
+        Term::Uint(30),
+        Term::Break,
+    ];
+    let expected = Value::Map(vec![
+        (Value::Uint(1), Value::Uint(30)),
+        (Value::Uint(2), Value::Uint(20)),
+    ]);
+    for token in tokens {
+        if let Some(actual) = parser.consume(token).unwrap() {
+            assert_eq!(expected, actual);
+            return;
+        }
+    }
+    panic!("Test fell through the end");
+}
+
+#[test]
+fn tstr_invalid_utf8() {
+    let mut parser = Parser::cbor();
+    let result = parser.consume(Term::Tstr(vec![0x41, 0xff]));
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+}
+
+#[test]
+fn tstrx_split_code_point() {
+    // U+00E9 encoded as 0xc3 0xa9, split across two chunks
+    let mut parser = Parser::cbor();
+    let result = parser
+        .consume(Term::TstrX)
+        .and_then(|_| parser.consume(Term::Tstr(vec![0x41, 0xc3])));
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+}
+
+#[test]
+fn tstrx_utf8() {
+    let mut parser = Parser::cbor();
+    let result = parser
+        .consume(Term::TstrX)
+        .and_then(|_| parser.consume(Term::Tstr("é".into())))
+        .and_then(|_| parser.consume(Term::Tstr("ß".into())))
+        .and_then(|_| parser.consume(Term::Break))
+        .unwrap()
+        .unwrap();
+    assert_eq!(Some("éß"), result.as_str());
+}
+
+#[test]
+fn tag_registry() {
+    // Transform unsigned bignums into integers, and reject large ones
+    let mut tags = TagRegistry::default();
+    tags.register(2, |tag, value| {
+        let bytes = value.as_bstr().unwrap_or_default();
+        if bytes.len() > 8 {
+            return Err(Error::InvalidTag(tag, "bignum too large".into()));
+        }
+        let n = bytes.iter().fold(0, |n, byte| n << 8 | u64::from(*byte));
+        Ok(Value::Uint(n))
+    });
+    let options = Options { tags, ..Options::default() };
+    let decode = |bytes: Vec<u8>| {
+        let mut parser = Parser::cbor();
+        parser.set_options(options.clone());
+        parser
+            .consume(Term::Array(2))
+            .and_then(|_| parser.consume(Term::Tag(2)))
+            .and_then(|_| parser.consume(Term::Bstr(bytes)))
+            .and_then(|_| parser.consume(Term::Tag(3)))
+            .and_then(|_| parser.consume(Term::Uint(0)))
+    };
+    let expected = Value::Array(vec![
+        Value::Uint(0x0100),
+        Value::Tag(3, Box::new(Value::Uint(0))),
+    ]);
+    assert_eq!(Some(expected), decode(vec![0x01, 0x00]).unwrap());
+    let error = decode(vec![0x01; 9]).unwrap_err();
+    assert!(matches!(error.cause(), Error::InvalidTag(2, _)));
+    assert_eq!("[0]", error.path().unwrap().to_string());
+}
+
+#[test]
+fn unexpected_terminal() {
+    let mut parser = Parser::cbor();
+    parser.consume(Term::Array(2)).unwrap();
+    parser.consume(Term::Uint(1)).unwrap();
+    let error = parser.consume(Term::Break).unwrap_err();
+    assert!(matches!(error.cause(), Error::UnexpectedT(_, Term::Break)));
+    let mut parser = Parser::cbor();
+    parser.consume(Term::BstrX).unwrap();
+    let result = parser.consume(Term::Uint(1));
+    let Err(Error::UnexpectedT(expected, Term::Uint(1))) = result else {
+        panic!("Expected unexpected terminal symbol");
+    };
+    assert_eq!(vec![Kind::BstrX, Kind::Bstr, Kind::Break], expected);
+}
+
+#[test]
+fn map_excessive() {
+    let mut parser = Parser::cbor();
+    let error = parser.consume(Term::Map(u64::MAX)).unwrap_err();
+    let cause = error.cause();
+    assert!(matches!(cause, Error::Excessive(u64::MAX)));
+}
+
+/// Tests that the tables cover the grammar, and that each state of the tables
+/// corresponds to a state of the parser.
+#[test]
+fn table_matches_grammar() {
+    use std::collections::HashSet;
+    // Each production is reduced by some state.
+    let reduced = TABLE
+        .entries()
+        .filter_map(|(_, entry)| entry.reduce.map(usize::from))
+        .collect::<HashSet<_>>();
+    assert_eq!((0..GRAMMAR.len()).collect::<HashSet<_>>(), reduced);
+    let productions = Parser::<Options>::PRODUCTIONS;
+    for (index, production) in productions.iter().enumerate() {
+        assert_eq!(index + 1, production.num_id);
+    }
+    // Walk the tables from the initial state.
+    let sample = |kind| match kind {
+        Kind::Uint => Term::Uint(0),
+        Kind::Nint => Term::Nint(0),
+        Kind::Float => Term::Float(Precision::Half, 0),
+        Kind::Simple => Term::Simple(0),
+        Kind::Tag => Term::Tag(0),
+        Kind::Bstr => Term::Bstr(Vec::new()),
+        Kind::BstrX => Term::BstrX,
+        Kind::Tstr => Term::Tstr(Vec::new()),
+        Kind::TstrX => Term::TstrX,
+        Kind::Array => Term::Array(1),
+        Kind::ArrayX => Term::ArrayX,
+        Kind::Map => Term::Map(1),
+        Kind::MapX => Term::MapX,
+        Kind::Break => Term::Break,
+    };
+    let kinds = [
+        Kind::Uint,
+        Kind::Nint,
+        Kind::BstrX,
+        Kind::Bstr,
+        Kind::TstrX,
+        Kind::Tstr,
+        Kind::ArrayX,
+        Kind::Array,
+        Kind::MapX,
+        Kind::Map,
+        Kind::Tag,
+        Kind::Simple,
+        Kind::Float,
+        Kind::Break,
+    ];
+    let non_terms = [
+        NonTerm::ArrayXSeq,
+        NonTerm::Bstr,
+        NonTerm::BstrXSeq,
+        NonTerm::MapXSeq,
+        NonTerm::Tstr,
+        NonTerm::TstrXSeq,
+        NonTerm::Value,
+    ];
+    // The parser enters the item past a repetition once it is complete.
+    let entered = |target: Item, state: &State| match target.next() {
+        Some(Symbol::Repeat(..)) if state.count.0 == state.count.1 => {
+            target.advance()
+        }
+        _ => target,
+    };
+    // Repetitions are visited once per count, until the count is exhausted.
+    let mut visited = HashSet::new();
+    let mut pending = vec![State::init()];
+    while let Some(state) = pending.pop() {
+        let item = state.item;
+        if !visited.insert((item, state.count)) {
+            continue;
+        }
+        let entry = TABLE.entry(item).unwrap();
+        for kind in kinds {
+            if let Some(target) = entry.shift(kind) {
+                let next = State::shift(target, sample(kind)).unwrap();
+                assert_eq!(entered(target, &next), next.item, "{item} on {kind:?}");
+                pending.push(next);
+            }
+        }
+        for non_term in non_terms {
+            if let Some(target) = entry.goto(non_term) {
+                let next = State::goto(target, &state);
+                assert_eq!(entered(target, &next), next.item, "{item} on {non_term}");
+                pending.push(next);
+            }
+        }
+    }
+    let items = TABLE.entries().map(|(item, _)| item).collect::<HashSet<_>>();
+    let visited = visited.into_iter().map(|(item, _)| item).collect();
+    assert_eq!(items, visited);
+}
+
+#[test]
+fn clone_partial() {
+    fn assert_clone_send<T: Clone + Send>() {}
+    assert_clone_send::<Parser>();
+    let mut parser = Parser::cbor();
+    assert_eq!(None, parser.consume(Term::Array(2)).unwrap());
+    assert_eq!(None, parser.consume(Term::Uint(1)).unwrap());
+    let mut clone = parser.clone();
+    let handle = std::thread::spawn(move || clone.consume(Term::Uint(3)));
+    let expected = Value::Array(vec![Value::Uint(1), Value::Uint(2)]);
+    assert_eq!(Some(expected), parser.consume(Term::Uint(2)).unwrap());
+    let expected = Value::Array(vec![Value::Uint(1), Value::Uint(3)]);
+    assert_eq!(Some(expected), handle.join().unwrap().unwrap());
+}
+
 // =============================================================================
 // Utilities
 // =============================================================================
@@ -279,8 +633,8 @@ fn random_nint() -> u64 {
 fn random_bytes() -> Vec<u8> {
     const CAPACITY: usize = 8;
     let mut result: Vec<u8> = Vec::with_capacity(CAPACITY);
-    let mut iter = rand::random_iter::<u8>();
-    while let Some(value) = iter.next() {
+    let iter = rand::random_iter::<u8>();
+    for value in iter {
         result.push(value);
         if result.len() >= CAPACITY {
             return result;
@@ -17,158 +17,186 @@ You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::{Builder, Error, Slot};
+use crate::budget::Budget;
 use crate::value::Value;
-use super::Error;
 
 /// Holds intermediate results as values are being reconstructed from tokens.
+///
+/// The stack charges its entries, and the growth of arrays, maps, and
+/// strings, to its memory budget.
 #[derive(Clone, Debug)]
-pub struct ValueStack {
-    inner: Vec<Value>,
+pub struct ValueStack<T = Value> {
+    inner: Vec<Slot<T>>,
     upper: usize,
+    budget: Budget,
 }
 
-impl Default for ValueStack {
-    fn default() -> ValueStack {
+impl<T> Default for ValueStack<T> {
+    fn default() -> ValueStack<T> {
         ValueStack {
             inner: Default::default(),
             upper: 16384,
+            budget: Budget::default(),
         }
     }
 }
 
-impl ValueStack {
+impl<T> ValueStack<T> {
     /// Allows a peek at the value on top of the stack
     #[cfg(debug_assertions)]
-    pub fn last(&self) -> Option<&Value> {
+    pub fn last(&self) -> Option<&Slot<T>> {
         self.inner.last()
     }
 
+    /// Returns the memory budget of the stack
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// Sets the memory budget of the stack
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
+    /// Returns the values on the stack, from the bottom up.
+    pub(crate) fn slots(&self) -> &[Slot<T>] {
+        &self.inner
+    }
+
+    /// Discards all values
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
     /// Pops a value from the stack
-    pub fn pop(&mut self) -> Option<Value> {
+    pub fn pop(&mut self) -> Option<Slot<T>> {
         self.inner.pop()
     }
 
+    /// Pops a built data item from the stack
+    pub fn pop_item(&mut self) -> Option<T> {
+        match self.inner.pop() {
+            Some(Slot::Item(item)) => Some(item),
+            _ => None,
+        }
+    }
+
     /// Pushes `value`
-    pub fn push(&mut self, value: Value) -> Result<(), Error> {
+    pub fn push(&mut self, value: Slot<T>) -> Result<(), Error> {
         if self.inner.len() < self.upper {
-            Ok(self.inner.push(value))
+            self.budget.charge_elements::<Slot<T>>(1)?;
+            self.inner.push(value);
+            Ok(())
         } else {
             Err(Error::InsufficientStackSize)
         }
     }
 
+    /// Pushes the built data item `item`
+    pub fn push_item(&mut self, item: T) -> Result<(), Error> {
+        self.push(Slot::Item(item))
+    }
+
     /// Merges the two elements on top of the stack into an array.
     ///
     /// Expects `value array[…]` on top.
     ///
     /// Pops `value`, and replaces `array[…]` with `array[… value]`.
-    pub fn merge_value_array(&mut self) {
-        let Some(array) = self.inner.pop() else {
-            panic!("Expected value stack [… value array[…]]");
-        };
-        let Some(mut elements) = array.as_array() else {
-            panic!("Expected value stack [… value array[…]]");
+    pub fn merge_value_array(&mut self) -> Result<(), Error> {
+        let Some(Slot::Array(mut elements)) = self.inner.pop() else {
+            return Err(Error::Internal);
         };
-        let Some(value) = self.inner.pop() else {
-            panic!("Expected value stack [… value array[…]]");
+        let Some(value) = self.pop_item() else {
+            return Err(Error::Internal);
         };
+        let capacity = elements.capacity();
         elements.push(value);
-        self.inner.push(Value::Array(elements));
+        self.budget
+            .charge_elements::<T>(elements.capacity() - capacity)?;
+        self.inner.push(Slot::Array(elements));
+        Ok(())
     }
 
     /// Merges the three elements on top of the stack into a map.
     ///
-    /// Expects `value value map[…]` on top.
+    /// Expects `map[…] value value` on top.
     ///
-    /// Pops `value` and `value`, and replaces `map[…]` with `map[… (value,value)]`.
-    pub fn merge_value_value_map(&mut self) {
-        let Some(map) = self.inner.pop() else {
-            panic!("Expected value stack [… value value map[…]]");
-        };
-        let Some(mut entries) = map.as_map() else {
-            panic!("Expected value stack [… value value map[…]]");
-        };
-        let Some(value2) = self.inner.pop() else {
-            panic!("Expected value stack [… value value map[…]]");
-        };
-        let Some(value1) = self.inner.pop() else {
-            panic!("Expected value stack [… value value map[…]]");
-        };
-        entries.push((value1, value2));
-        self.inner.push(Value::Map(entries));
+    /// Pops `value` and `value`, and appends the entry `(value,value)` to
+    /// `map[…]` with `builder`.
+    pub fn merge_map_value_value<B>(
+        &mut self,
+        builder: &mut B,
+    ) -> Result<(), Error>
+    where
+        B: Builder<Output = T>,
+    {
+        let Some(value2) = self.pop_item() else {
+            return Err(Error::Internal);
+        };
+        let Some(value1) = self.pop_item() else {
+            return Err(Error::Internal);
+        };
+        let Some(Slot::Map(map)) = self.inner.last_mut() else {
+            return Err(Error::Internal);
+        };
+        let allocated = map.allocated();
+        builder.entry(map, (value1, value2))?;
+        Ok(self.budget.charge(map.allocated() - allocated)?)
     }
 
     /// Reverses the array on top of the stack.
     ///
     /// Expects `array` on top.
-    pub fn reverse_array(&mut self) {
-        let Some(coll) = self.inner.pop() else {
-            panic!("Expected value stack [… array[…]]");
-        };
-        let Some(mut elements) = coll.as_array() else {
-            panic!("Expected value stack [… array[…]]");
+    pub fn reverse_array(&mut self) -> Result<(), Error> {
+        let Some(Slot::Array(elements)) = self.inner.last_mut() else {
+            return Err(Error::Internal);
         };
         elements.reverse();
-        self.inner.push(Value::Array(elements));
+        Ok(())
     }
 
-    /// Reverses the map on top of the stack.
+    /// Merges the two byte strings on top of the stack.
     ///
-    /// Expects `map` on top.
-    pub fn reverse_map(&mut self) {
-        let Some(coll) = self.inner.pop() else {
-            panic!("Expected value stack [… map[…]]");
-        };
-        let Some(mut elements) = coll.as_map() else {
-            panic!("Expected value stack [… map[…]]");
-        };
-        elements.reverse();
-        self.inner.push(Value::Map(elements));
-    }
-
-    /// Prepends the `bytes` to the bstr on top of the stack
+    /// Expects `bstr bstr` on top.
     ///
-    /// Expects `bstr` on top.
-    pub fn bstr_prepend(&mut self, bytes: Vec<u8>) {
-        let Some(value) = self.inner.pop() else {
-            panic!("Expected value stack [… bstr]");
+    /// Pops the second `bstr`, and appends its bytes to the first one.
+    pub fn merge_bstr_bstr(&mut self) -> Result<(), Error> {
+        let Some(Slot::Bytes(more_bytes)) = self.inner.pop() else {
+            return Err(Error::Internal);
         };
-        let Some(more_bytes) = value.as_bstr() else {
-            panic!("Expected value stack [… bstr]");
+        let Some(Slot::Bytes(bytes)) = self.inner.last_mut() else {
+            return Err(Error::Internal);
         };
-        self.inner.push(Value::Bstr([bytes, more_bytes].concat()));
+        self.budget.charge(more_bytes.len())?;
+        bytes.extend_from_slice(&more_bytes);
+        Ok(())
     }
 
-    /// Prepends the `bytes` to the tstr on top of the stack
+    /// Merges the two text strings on top of the stack.
     ///
-    /// Expects `tstr` on top.
-    pub fn tstr_prepend(&mut self, bytes: Vec<u8>) {
-        let Some(value) = self.inner.pop() else {
-            panic!("Expected value stack [… tstr]");
-        };
-        let Some(more_bytes) = value.as_tstr() else {
-            panic!("Expected value stack [… tstr]");
-        };
-        self.inner.push(Value::Tstr([bytes, more_bytes.into()].concat()));
-    }
-
-    /// Promotes the value on top of the stack to a tagged value
+    /// Expects `tstr tstr` on top.
     ///
-    /// Expects a value on top of the stack
-    pub fn to_tagged(&mut self, tag: u64) {
-        let Some(value) = self.inner.pop() else {
-            panic!("Expected value stack [… value]");
+    /// Pops the second `tstr`, and appends its text to the first one.
+    pub fn merge_tstr_tstr(&mut self) -> Result<(), Error> {
+        let Some(Slot::Text(more_text)) = self.inner.pop() else {
+            return Err(Error::Internal);
+        };
+        let Some(Slot::Text(text)) = self.inner.last_mut() else {
+            return Err(Error::Internal);
         };
-        self.inner.push(Value::Tag(tag, Box::new(value)));
+        self.budget.charge(more_text.len())?;
+        text.push_str(&more_text);
+        Ok(())
     }
 }
 
-impl std::fmt::Display for ValueStack {
+impl<T: std::fmt::Debug> std::fmt::Display for ValueStack<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = self
             .inner
             .iter()
-            .map(|x| x.to_string())
+            .map(|x| format!("{x:?}"))
             .collect::<Vec<String>>()
             .join(", ");
         write!(f, "{{{string}}}")
@@ -0,0 +1,250 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::{fmt, mem};
+
+/// Decoding options shared by the parsers
+///
+/// The default options accept any well-formed input.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Policy for maps with duplicate keys
+    pub duplicate_keys: DuplicateKeys,
+    /// Handlers for tagged items
+    pub tags: TagRegistry,
+    /// Policy for tags without a handler
+    pub unknown_tags: UnknownTags,
+}
+
+impl Options {
+    /// Applies the tag handler for `tag` to the tagged item `value`, or the
+    /// policy for unknown tags if there is no such handler.
+    pub fn apply_tag(&self, tag: u64, value: Value) -> Result<Value, Error> {
+        if let Some(handler) = self.tags.handlers.get(&tag) {
+            return handler(tag, value);
+        }
+        match self.unknown_tags {
+            UnknownTags::Keep => Ok(Value::Tag(tag, Box::new(value))),
+            UnknownTags::Strip => Ok(value),
+            UnknownTags::Reject => Err(Error::UnknownTag(tag)),
+        }
+    }
+}
+
+/// Policy for maps with duplicate keys
+///
+/// RFC 8949, section 5.6, considers maps with duplicate keys invalid. Keys are
+/// compared by [data-model equality](Value::data_model_eq).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DuplicateKeys {
+    /// Fail with [Error::DuplicateKey].
+    Reject,
+    /// Keep the first entry with a given key, and drop all later entries.
+    KeepFirst,
+    /// Keep the last entry with a given key; the entry takes the position of
+    /// the first entry with that key.
+    KeepLast,
+    /// Keep all entries.
+    #[default]
+    KeepAll,
+}
+
+impl DuplicateKeys {
+    /// Appends `entry` to the entries of `map`, applying the policy.
+    pub fn insert(
+        self,
+        map: &mut Entries<Value>,
+        entry: (Value, Value),
+    ) -> Result<(), Error> {
+        if self == DuplicateKeys::KeepAll {
+            map.push(entry);
+            return Ok(());
+        }
+        let key = entry.0.canonical();
+        let size = mem::size_of::<(Value, usize)>() + heap_size(&key);
+        map.keys.allocated += size;
+        match (self, map.keys.positions.entry(key)) {
+            (_, Entry::Vacant(vacant)) => {
+                vacant.insert(map.entries.len());
+                map.entries.push(entry);
+            }
+            (DuplicateKeys::Reject, Entry::Occupied(_)) => {
+                return Err(Error::DuplicateKey(entry.0));
+            }
+            (DuplicateKeys::KeepLast, Entry::Occupied(occupied)) => {
+                map.entries[*occupied.get()] = entry;
+            }
+            (_, Entry::Occupied(_)) => {}
+        }
+        Ok(())
+    }
+
+    /// Applies the policy to all `entries` of a map, in order.
+    pub fn collect(
+        self,
+        entries: Vec<(Value, Value)>,
+    ) -> Result<Vec<(Value, Value)>, Error> {
+        if self == DuplicateKeys::KeepAll {
+            return Ok(entries);
+        }
+        let mut map = Entries::default();
+        map.entries.reserve(entries.len());
+        for entry in entries {
+            self.insert(&mut map, entry)?;
+        }
+        Ok(map.entries)
+    }
+}
+
+/// Index of the keys of a map, for the lookup of duplicate keys
+///
+/// The index maps the keys in canonical form to the position of their entry,
+/// so that keys are compared by [data-model equality](Value::data_model_eq),
+/// and a lookup takes logarithmic time. It counts the bytes allocated for the
+/// keys, so that the parsers can charge them to their budget.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct KeyIndex {
+    positions: BTreeMap<Value, usize>,
+    allocated: usize,
+}
+
+impl KeyIndex {
+    /// Returns the number of bytes allocated for the keys.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+}
+
+/// Returns the number of bytes `value` has allocated on the heap.
+fn heap_size(value: &Value) -> usize {
+    let size = mem::size_of::<Value>();
+    match value {
+        Value::Bstr(bytes) => bytes.len(),
+        Value::Tstr(text) => text.len(),
+        Value::Array(items) => {
+            items.iter().map(|item| size + heap_size(item)).sum()
+        }
+        Value::Map(entries) => entries
+            .iter()
+            .map(|(key, item)| 2 * size + heap_size(key) + heap_size(item))
+            .sum(),
+        Value::Tag(_, item) => size + heap_size(item),
+        _ => 0,
+    }
+}
+
+/// Policy for tags without a handler in the [TagRegistry]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UnknownTags {
+    /// Keep the tag.
+    #[default]
+    Keep,
+    /// Drop the tag, and keep the tagged item.
+    Strip,
+    /// Fail with [Error::UnknownTag].
+    Reject,
+}
+
+/// Handler for tagged items
+///
+/// The handler receives the tag number and the tagged item, and returns the
+/// value that replaces the tagged value: the unchanged `Value::Tag` to accept
+/// the item, or any other value to transform it. It returns an error, usually
+/// [Error::InvalidTag], to reject the item.
+pub type TagHandler =
+    Arc<dyn Fn(u64, Value) -> Result<Value, Error> + Send + Sync>;
+
+/// Registry of handlers per tag number
+///
+/// # Example
+///
+///     use cbor::parser::{Error, Options, TagRegistry};
+///     use cbor::value::Value;
+///
+///     let mut tags = TagRegistry::standard();
+///     // Replace epoch-based date/time by its number of seconds
+///     tags.register(1, |_, value| match value {
+///         Value::Uint(_) => Ok(value),
+///         _ => Err(Error::InvalidTag(1, "expected integer seconds".into())),
+///     });
+///     let options = Options { tags, ..Options::default() };
+///     let value = options.apply_tag(1, Value::Uint(0)).unwrap();
+///     assert_eq!(Value::Uint(0), value);
+#[derive(Clone, Default)]
+pub struct TagRegistry {
+    handlers: HashMap<u64, TagHandler>,
+}
+
+impl TagRegistry {
+    /// Returns a registry with handlers that check the content type of the
+    /// tags defined in RFC 8949, section 3.4:
+    /// - tag 0 (standard date/time string) requires a text string;
+    /// - tag 1 (epoch-based date/time) requires an integer or a float;
+    /// - tags 2 and 3 (bignums) require a byte string.
+    pub fn standard() -> Self {
+        let mut registry = Self::default();
+        registry.register(0, |tag, value| match value {
+            Value::Tstr(_) => Ok(Value::Tag(tag, Box::new(value))),
+            _ => Err(Error::InvalidTag(tag, "expected text string".into())),
+        });
+        registry.register(1, |tag, value| match value {
+            Value::Uint(_) | Value::Nint(_) | Value::Float(..) => {
+                Ok(Value::Tag(tag, Box::new(value)))
+            }
+            _ => Err(Error::InvalidTag(tag, "expected number".into())),
+        });
+        for bignum in [2, 3] {
+            registry.register(bignum, |tag, value| match value {
+                Value::Bstr(_) => Ok(Value::Tag(tag, Box::new(value))),
+                _ => Err(Error::InvalidTag(tag, "expected byte string".into())),
+            });
+        }
+        registry
+    }
+
+    /// Registers `handler` for `tag`, replacing any previous handler.
+    pub fn register<Handler>(&mut self, tag: u64, handler: Handler)
+    where
+        Handler: Fn(u64, Value) -> Result<Value, Error> + Send + Sync + 'static,
+    {
+        self.handlers.insert(tag, Arc::new(handler));
+    }
+
+    /// Removes the handler for `tag`.
+    pub fn unregister(&mut self, tag: u64) {
+        self.handlers.remove(&tag);
+    }
+
+    /// Returns `true` if there is a handler for `tag`.
+    pub fn contains(&self, tag: u64) -> bool {
+        self.handlers.contains_key(&tag)
+    }
+}
+
+impl fmt::Debug for TagRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tags = self.handlers.keys().collect::<Vec<_>>();
+        tags.sort();
+        f.debug_struct("TagRegistry").field("tags", &tags).finish()
+    }
+}
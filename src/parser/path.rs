@@ -0,0 +1,245 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use std::fmt;
+
+/// Logical path from the top-level data item to a nested data item
+///
+/// The path is displayed as a sequence of segments, e.g.
+/// `[3].{"readings"}[17]` for the 18th item of the array at key
+/// `"readings"` of the map that is the 4th item of the top-level array.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path(pub Vec<Segment>);
+
+/// Segment of a [Path]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Segment {
+    /// Item of an array at this index; displayed as `[index]`.
+    Index(u64),
+    /// Item of the map entry at this index, and with this key if the key is
+    /// a number, a string, or a simple value; displayed as `.{key}`, or as
+    /// `.{#index}` otherwise.
+    Item(u64, Option<Value>),
+    /// Key of the map entry at this index; displayed as `.{#index}.key`.
+    Key(u64),
+}
+
+impl Path {
+    /// Returns `true` if the path refers to the top-level data item.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|segment| segment.fmt(f))
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Index(index) => write!(f, "[{index}]"),
+            Segment::Item(_, Some(Value::Uint(n))) => write!(f, ".{{{n}}}"),
+            Segment::Item(_, Some(Value::Nint(n))) => {
+                write!(f, ".{{{}}}", -1 - i128::from(*n))
+            }
+            Segment::Item(_, Some(Value::Tstr(text))) => {
+                write!(f, ".{{{text:?}}}")
+            }
+            Segment::Item(_, Some(Value::Bstr(bytes))) => {
+                write!(f, ".{{h'")?;
+                bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))?;
+                write!(f, "'}}")
+            }
+            Segment::Item(_, Some(key)) => write!(f, ".{{{key}}}"),
+            Segment::Item(index, None) => write!(f, ".{{#{index}}}"),
+            Segment::Key(index) => write!(f, ".{{#{index}}}.key"),
+        }
+    }
+}
+
+/// Keeps track of the path to the data item being parsed.
+///
+/// A parser computes the [Update] for each token before consuming it, and
+/// applies the update once it has consumed the token successfully. Until
+/// then, the tracker refers to the data item the token belongs to.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tracker {
+    frames: Vec<Frame>,
+}
+
+/// Open data item
+#[derive(Clone, Debug)]
+pub(crate) enum Frame {
+    /// Array with this many items, or of indefinite length, and the number of
+    /// items parsed so far
+    Array(Option<u64>, u64),
+    /// Map with this many entries, or of indefinite length, the number of
+    /// entries parsed so far, and the key of the current entry once parsed
+    Map(Option<u64>, u64, Option<Option<Value>>),
+    /// Tag
+    Tag,
+    /// String of indefinite length
+    String,
+}
+
+/// Change of the path caused by a token
+#[derive(Debug)]
+pub(crate) enum Update {
+    /// The token opens a data item.
+    Open(Frame),
+    /// The token completes a data item, possibly a map key.
+    Complete(Option<Value>),
+    /// The token closes the innermost data item.
+    Close,
+    /// The token does not change the path.
+    Keep,
+}
+
+impl Update {
+    /// Returns `true` if the token opens an array or a map.
+    pub fn opens_container(&self) -> bool {
+        matches!(self, Update::Open(Frame::Array(..) | Frame::Map(..)))
+    }
+}
+
+impl Tracker {
+    /// Discards all open data items.
+    pub fn reset(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Returns the number of open data items.
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the open data items, from the top level inwards.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Returns the path to the data item being parsed.
+    pub fn path(&self) -> Path {
+        let segments = self.frames.iter().filter_map(|frame| match frame {
+            Frame::Array(_, index) => Some(Segment::Index(*index)),
+            Frame::Map(_, index, None) => Some(Segment::Key(*index)),
+            Frame::Map(_, index, Some(key)) => {
+                Some(Segment::Item(*index, key.clone()))
+            }
+            Frame::Tag | Frame::String => None,
+        });
+        Path(segments.collect())
+    }
+
+    /// Attaches the path to `error`, unless it refers to the top level.
+    pub fn wrap(&self, error: Error) -> Error {
+        let path = self.path();
+        if path.is_empty() {
+            error
+        } else {
+            Error::At(path, Box::new(error))
+        }
+    }
+
+    /// Returns the change of the path caused by `term`.
+    pub fn update(&self, term: &Term) -> Update {
+        let in_string = matches!(self.frames.last(), Some(Frame::String));
+        let is_key = matches!(self.frames.last(), Some(Frame::Map(_, _, None)));
+        let is_indefinite = matches!(
+            self.frames.last(),
+            Some(Frame::Array(None, _) | Frame::Map(None, _, _) | Frame::String)
+        );
+        match term {
+            // Elsewhere, a misplaced `%break` takes the place of a data item.
+            Term::Break if is_indefinite => Update::Close,
+            // Only nested strings change the path within a string; any other
+            // token is a chunk, or an error that does not end the string.
+            Term::BstrX | Term::TstrX if in_string => {
+                Update::Open(Frame::String)
+            }
+            _ if in_string => Update::Keep,
+            Term::Array(0) | Term::Map(0) => Update::Complete(None),
+            Term::Array(n) => Update::Open(Frame::Array(Some(*n), 0)),
+            Term::ArrayX => Update::Open(Frame::Array(None, 0)),
+            Term::Map(n) => Update::Open(Frame::Map(Some(*n), 0, None)),
+            Term::MapX => Update::Open(Frame::Map(None, 0, None)),
+            Term::Tag(_) => Update::Open(Frame::Tag),
+            Term::BstrX | Term::TstrX => Update::Open(Frame::String),
+            Term::Uint(n) if is_key => Update::Complete(Some(Value::Uint(*n))),
+            Term::Nint(n) if is_key => Update::Complete(Some(Value::Nint(*n))),
+            Term::Float(p, n) if is_key => {
+                Update::Complete(Some(Value::Float(*p, *n)))
+            }
+            Term::Simple(n) if is_key => {
+                Update::Complete(Some(Value::Simple(*n)))
+            }
+            Term::Bstr(bytes) if is_key => {
+                Update::Complete(Some(Value::Bstr(bytes.clone())))
+            }
+            Term::Tstr(bytes) if is_key => Update::Complete(
+                String::from_utf8(bytes.clone()).ok().map(Value::Tstr),
+            ),
+            _ => Update::Complete(None),
+        }
+    }
+
+    /// Applies the change of the path caused by a consumed token.
+    pub fn apply(&mut self, update: Update) {
+        let mut key = match update {
+            Update::Keep => return,
+            Update::Open(frame) => return self.frames.push(frame),
+            Update::Complete(key) => key,
+            Update::Close => {
+                self.frames.pop();
+                None
+            }
+        };
+        loop {
+            match self.frames.last_mut() {
+                None => return,
+                // A nested string is a chunk of the enclosing string.
+                Some(Frame::String) => return,
+                Some(Frame::Tag) => {}
+                Some(Frame::Array(count, index)) => {
+                    *index += 1;
+                    if *count != Some(*index) {
+                        return;
+                    }
+                }
+                Some(Frame::Map(_, _, current @ None)) => {
+                    *current = Some(key);
+                    return;
+                }
+                Some(Frame::Map(count, index, current)) => {
+                    *current = None;
+                    *index += 1;
+                    if *count != Some(*index) {
+                        return;
+                    }
+                }
+            }
+            self.frames.pop();
+            key = None;
+        }
+    }
+}
@@ -0,0 +1,124 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::path::{Frame, Tracker};
+use super::*;
+
+/// Read-only view of a partially parsed data item
+///
+/// The view shows the data items opened so far, from the top level inwards,
+/// together with the items each of them has collected. Use it, e.g., to
+/// display the progress of a slow transfer, or to find out where it stalled.
+#[derive(Debug)]
+pub struct Progress<'a, T> {
+    /// Path to the data item being parsed
+    pub path: Path,
+    /// Open data items, from the top level inwards
+    pub open: Vec<Open<'a, T>>,
+}
+
+/// Data item that has been opened, but is not complete yet
+#[derive(Debug)]
+pub struct Open<'a, T> {
+    /// Type of the data item
+    pub kind: OpenKind,
+    /// Number of items of an array, or of entries of a map, if the length is
+    /// definite
+    pub length: Option<u64>,
+    /// Items collected so far; labels and items alternate for maps.
+    ///
+    /// Strings do not collect any items.
+    pub items: Vec<&'a T>,
+}
+
+/// Type of an [Open] data item
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OpenKind {
+    /// Array
+    Array,
+    /// Map
+    Map,
+    /// Tagged data item, waiting for its tag content
+    Tag,
+    /// Byte string or text string of indefinite length
+    String,
+}
+
+impl<T> Open<'_, T> {
+    /// Returns the number of items, or entries, that a container of definite
+    /// length still expects.
+    pub fn remaining(&self) -> Option<u64> {
+        let collected = self.items.len() as u64;
+        let collected = match self.kind {
+            OpenKind::Map => collected / 2,
+            _ => collected,
+        };
+        self.length.map(|length| length.saturating_sub(collected))
+    }
+}
+
+impl<'a, T> Progress<'a, T> {
+    /// Returns the view of the open data items tracked by `path`, with the
+    /// items collected in `slots`.
+    ///
+    /// The parsers keep the collected items on their value stacks in input
+    /// order, either in the partial container, or as separate entries. The
+    /// items are assigned to the open data items in that order.
+    pub(crate) fn new(path: &Tracker, slots: &'a [Slot<T>]) -> Self {
+        let mut items = slots.iter().flat_map(|slot| {
+            let items: Box<dyn Iterator<Item = &'a T>> = match slot {
+                Slot::Item(item) => Box::new(std::iter::once(item)),
+                Slot::Array(items) => Box::new(items.iter()),
+                Slot::Map(map) => Box::new(
+                    map.entries.iter().flat_map(|(label, item)| [label, item]),
+                ),
+                Slot::Bytes(_) | Slot::Text(_) => Box::new(std::iter::empty()),
+            };
+            items
+        });
+        let open = path
+            .frames()
+            .iter()
+            .map(|frame| {
+                let (kind, length, count) = match frame {
+                    Frame::Array(length, index) => {
+                        (OpenKind::Array, *length, *index)
+                    }
+                    Frame::Map(length, index, key) => {
+                        let count = 2 * index + u64::from(key.is_some());
+                        (OpenKind::Map, *length, count)
+                    }
+                    Frame::Tag => (OpenKind::Tag, None, 0),
+                    Frame::String => (OpenKind::String, None, 0),
+                };
+                let count = usize::try_from(count).unwrap_or(usize::MAX);
+                let items = items.by_ref().take(count).collect();
+                Open {
+                    kind,
+                    length,
+                    items,
+                }
+            })
+            .collect();
+        Progress {
+            path: path.path(),
+            open,
+        }
+    }
+}
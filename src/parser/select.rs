@@ -0,0 +1,211 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::path::{Tracker, Update};
+use super::*;
+use std::fmt;
+
+/// Path to the data items to be selected
+///
+/// A selector is a sequence of steps from the top-level data item: the item
+/// of an array at an index, or the item of a map entry with a key. Keys are
+/// compared by [data-model equality](Value::data_model_eq), and only keys
+/// that are numbers, strings or simple values can be selected.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selector(pub Vec<Step>);
+
+/// Step of a [Selector]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Item of an array at this index
+    Index(u64),
+    /// Item of the map entry with this key
+    Key(Value),
+}
+
+impl Selector {
+    /// Returns the selector of the top-level data item.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a step to the item of an array at `index`.
+    pub fn index(mut self, index: u64) -> Self {
+        self.0.push(Step::Index(index));
+        self
+    }
+
+    /// Appends a step to the item of the map entry with `key`.
+    pub fn key(mut self, key: Value) -> Self {
+        self.0.push(Step::Key(key));
+        self
+    }
+
+    /// Returns `true` if the selector selects the data item at `path`.
+    fn selects(&self, path: &Path) -> bool {
+        self.0.len() == path.0.len()
+            && self.0.iter().zip(&path.0).all(|step| match step {
+                (Step::Index(i), Segment::Index(j)) => i == j,
+                (Step::Key(key), Segment::Item(_, Some(label))) => {
+                    key.data_model_eq(label)
+                }
+                _ => false,
+            })
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments = self.0.iter().map(|step| match step {
+            Step::Index(index) => Segment::Index(*index),
+            Step::Key(key) => Segment::Item(0, Some(key.clone())),
+        });
+        Path(segments.collect()).fmt(f)
+    }
+}
+
+/// Parser that extracts the selected data items
+///
+/// The parser follows the structure of the input at token level. It feeds
+/// the tokens of the data items that match a [Selector] into the inner
+/// parser, and skips all other tokens without building any values. Once the
+/// top-level data item is complete, the parser produces the selected data
+/// items in input order, each with its path. A data item nested in a
+/// selected data item is part of the latter, and not produced separately.
+///
+/// The parser re-arms itself after each top-level data item, and thus also
+/// works on CBOR sequences. The skipped tokens are not checked beyond what
+/// is needed to follow the structure of the input.
+///
+/// # Example
+///
+///     use cbor::decoder::Decoder;
+///     use cbor::parser::{ll, Select, Selector};
+///     use cbor::value::Value;
+///
+///     let key = |text: &str| Value::Tstr(text.into());
+///     let selectors = vec![
+///         Selector::new().key(key("hdr")).key(key("seq")),
+///         Selector::new().key(key("payload")),
+///     ];
+///     let mut decoder =
+///         Decoder::new(Select::new(ll::Parser::cbor(), selectors));
+///     // {"hdr": {"seq": 7, "ttl": 9}, "payload": h'00', "trailer": [0]}
+///     let bytes = b"\xa3\x63hdr\xa2\x63seq\x07\x63ttl\x09\
+///                   \x67payload\x41\x00\x67trailer\x81\x00";
+///     let (selection, _) = decoder.decode(bytes).unwrap();
+///     let selection = selection
+///         .iter()
+///         .map(|(path, value)| (path.to_string(), value))
+///         .collect::<Vec<_>>();
+///     let expected = [
+///         (r#".{"hdr"}.{"seq"}"#.to_string(), &Value::Uint(7)),
+///         (r#".{"payload"}"#.to_string(), &Value::Bstr(vec![0])),
+///     ];
+///     assert_eq!(expected.to_vec(), selection);
+#[derive(Clone, Debug)]
+pub struct Select<P: Parser> {
+    inner: P,
+    selectors: Vec<Selector>,
+    path: Tracker,
+    /// Path of the selected data item being parsed
+    current: Option<Path>,
+    selection: Vec<(Path, P::Output)>,
+}
+
+impl<P: Parser> Select<P> {
+    /// Returns a parser that extracts the data items matching `selectors`
+    /// with the `inner` parser.
+    ///
+    /// The inner parser must parse a single data item.
+    pub fn new(inner: P, selectors: Vec<Selector>) -> Self {
+        Select {
+            inner,
+            selectors,
+            path: Tracker::default(),
+            current: None,
+            selection: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the token at the current path belongs to a selected
+    /// data item, and starts the data item if it matches a selector.
+    fn select(&mut self) -> bool {
+        if self.current.is_some() {
+            return true;
+        }
+        let path = self.path.path();
+        if !self.selectors.iter().any(|selector| selector.selects(&path)) {
+            return false;
+        }
+        self.inner.reset();
+        self.current = Some(path);
+        true
+    }
+}
+
+impl<P: Parser> Parser for Select<P> {
+    type Output = Vec<(Path, P::Output)>;
+
+    fn consume(&mut self, term: Term) -> Result<Option<Self::Output>, Error> {
+        let update = self.path.update(&term);
+        if self.select() {
+            match self.inner.consume(term) {
+                Ok(Some(value)) => {
+                    if let Some(path) = self.current.take() {
+                        self.selection.push((path, value));
+                    }
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    let error = match error {
+                        Error::At(_, error) => *error,
+                        error => error,
+                    };
+                    return Err(self.path.wrap(error));
+                }
+            }
+        } else if matches!(term, Term::Break)
+            && !matches!(update, Update::Close)
+        {
+            let error = Error::Unexpected(format!("Unexpected {term}"));
+            return Err(self.path.wrap(error));
+        }
+        self.path.apply(update);
+        if self.path.depth() > 0 {
+            Ok(None)
+        } else {
+            Ok(Some(std::mem::take(&mut self.selection)))
+        }
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.path.reset();
+        self.current = None;
+        self.selection.clear();
+    }
+
+    fn set_budget(&mut self, budget: Budget) {
+        self.inner.set_budget(budget);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,166 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+
+fn text(text: &str) -> Value {
+    Value::Tstr(text.into())
+}
+
+fn tstr(text: &str) -> Term {
+    Term::Tstr(text.as_bytes().to_vec())
+}
+
+/// Selected data items per top-level data item, with their paths displayed
+type Selections<T> = Vec<Vec<(String, T)>>;
+
+/// Feeds `terms` to `parser`, and returns the selections produced.
+fn select<P: Parser>(
+    parser: &mut Select<P>,
+    terms: Vec<Term>,
+) -> Result<Selections<P::Output>, Error> {
+    let mut selections = Vec::new();
+    for term in terms {
+        if let Some(selection) = parser.consume(term)? {
+            let selection = selection
+                .into_iter()
+                .map(|(path, value)| (path.to_string(), value))
+                .collect();
+            selections.push(selection);
+        }
+    }
+    Ok(selections)
+}
+
+#[test]
+fn select_keys() {
+    // {"hdr": {"seq": 7, "ttl": 9}, "payload": [_ 1, 2], "trailer": 99("x")}
+    let terms = || {
+        vec![
+            Term::Map(3),
+            tstr("hdr"),
+            Term::Map(2),
+            tstr("seq"),
+            Term::Uint(7),
+            tstr("ttl"),
+            Term::Uint(9),
+            tstr("payload"),
+            Term::ArrayX,
+            Term::Uint(1),
+            Term::Uint(2),
+            Term::Break,
+            tstr("trailer"),
+            Term::Tag(99),
+            Term::Tstr(vec![0xff]),
+        ]
+    };
+    let selectors = || {
+        vec![
+            Selector::new().key(text("payload")),
+            Selector::new().key(text("hdr")).key(text("seq")),
+            Selector::new().key(text("missing")),
+        ]
+    };
+    let expected = vec![vec![
+        (r#".{"hdr"}.{"seq"}"#.to_string(), Value::Uint(7)),
+        (
+            r#".{"payload"}"#.to_string(),
+            Value::Array(vec![Value::Uint(1), Value::Uint(2)]),
+        ),
+    ]];
+    // The trailer is neither built nor checked.
+    let options = Options {
+        unknown_tags: UnknownTags::Reject,
+        ..Options::default()
+    };
+    let mut ll = ll::Parser::cbor();
+    ll.set_options(options.clone());
+    let mut ll = Select::new(ll, selectors());
+    assert_eq!(expected, select(&mut ll, terms()).unwrap());
+    let mut lr = lr::Parser::cbor();
+    lr.set_options(options);
+    let mut lr = Select::new(lr, selectors());
+    assert_eq!(expected, select(&mut lr, terms()).unwrap());
+}
+
+#[test]
+fn select_sequence() {
+    // [0, [1, 2]] 5 [3, [4]]
+    let terms = vec![
+        Term::Array(2),
+        Term::Uint(0),
+        Term::Array(2),
+        Term::Uint(1),
+        Term::Uint(2),
+        Term::Uint(5),
+        Term::Array(2),
+        Term::Uint(3),
+        Term::Array(1),
+        Term::Uint(4),
+    ];
+    let selectors = vec![Selector::new().index(1).index(0)];
+    let mut parser = Select::new(ll::Parser::cbor(), selectors);
+    let expected = vec![
+        vec![("[1][0]".to_string(), Value::Uint(1))],
+        vec![],
+        vec![("[1][0]".to_string(), Value::Uint(4))],
+    ];
+    assert_eq!(expected, select(&mut parser, terms).unwrap());
+    // The empty selector selects the top-level data item.
+    let mut parser = Select::new(lr::Parser::cbor(), vec![Selector::new()]);
+    let selections = select(&mut parser, vec![Term::Uint(5)]).unwrap();
+    assert_eq!(vec![vec![(String::new(), Value::Uint(5))]], selections);
+}
+
+#[test]
+fn select_errors() {
+    // {"a": [0, 0(0)]}
+    let terms = || {
+        vec![
+            Term::Map(1),
+            tstr("a"),
+            Term::Array(2),
+            Term::Uint(0),
+            Term::Tag(0),
+            Term::Uint(0),
+        ]
+    };
+    let options = Options {
+        tags: TagRegistry::standard(),
+        ..Options::default()
+    };
+    let mut inner = ll::Parser::cbor();
+    inner.set_options(options);
+    let selectors = vec![Selector::new().key(text("a"))];
+    let mut parser = Select::new(inner, selectors);
+    let error = select(&mut parser, terms()).unwrap_err();
+    assert!(matches!(error.cause(), Error::InvalidTag(0, _)));
+    assert_eq!(r#".{"a"}[1]"#, error.path().unwrap().to_string());
+    // A misplaced `%break` is rejected in skipped data items, too.
+    parser.reset();
+    let terms = vec![Term::Array(2), Term::Break];
+    let error = select(&mut parser, terms).unwrap_err();
+    assert_eq!("[0]", error.path().unwrap().to_string());
+}
+
+#[test]
+fn display_selector() {
+    let selector = Selector::new().key(text("a")).index(2).key(Value::Nint(0));
+    assert_eq!(r#".{"a"}[2].{-1}"#, selector.to_string());
+}
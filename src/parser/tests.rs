@@ -15,3 +15,446 @@ fn display_error_unexpected_nt() {
     let actual = format!("{error}");
     assert_eq!(expected, actual)
 }
+
+#[test]
+fn duplicate_keys() {
+    let entries = vec![
+        (Value::Uint(1), Value::Uint(10)),
+        (Value::Float(Precision::Half, 0x3c00), Value::Uint(20)),
+        (Value::Uint(1), Value::Uint(30)),
+        (
+            Value::Float(Precision::Double, 0x3ff0_0000_0000_0000),
+            Value::Uint(40),
+        ),
+    ];
+    let actual = DuplicateKeys::KeepAll.collect(entries.clone()).unwrap();
+    assert_eq!(entries, actual);
+    let actual = DuplicateKeys::KeepFirst.collect(entries.clone()).unwrap();
+    assert_eq!(vec![entries[0].clone(), entries[1].clone()], actual);
+    let actual = DuplicateKeys::KeepLast.collect(entries.clone()).unwrap();
+    assert_eq!(vec![entries[2].clone(), entries[3].clone()], actual);
+    let actual = DuplicateKeys::Reject.collect(entries);
+    assert!(matches!(actual, Err(Error::DuplicateKey(Value::Uint(1)))));
+}
+
+#[test]
+fn duplicate_keys_canonical() {
+    // Maps as keys are equal regardless of the order of their entries.
+    let key = |entries: [(u64, u64); 2]| {
+        let entries = entries.map(|(k, v)| (Value::Uint(k), Value::Uint(v)));
+        Value::Map(entries.to_vec())
+    };
+    let entries = vec![
+        (key([(1, 10), (2, 20)]), Value::Uint(0)),
+        (key([(2, 20), (1, 10)]), Value::Uint(1)),
+        (key([(2, 10), (1, 20)]), Value::Uint(2)),
+    ];
+    let actual = DuplicateKeys::KeepFirst.collect(entries.clone()).unwrap();
+    assert_eq!(vec![entries[0].clone(), entries[2].clone()], actual);
+}
+
+#[test]
+fn duplicate_keys_data_model_eq() {
+    // Keys are duplicates if and only if they are equal in the data model.
+    let entry = |key, item| (Value::Uint(key), Value::Uint(item));
+    let keys = [
+        Value::Float(Precision::Half, 0x7e00),
+        Value::Float(Precision::Single, 0x7fc0_0000),
+        Value::Float(Precision::Half, 0x8000),
+        Value::Float(Precision::Double, 0),
+        Value::Map(vec![entry(1, 1), entry(1, 1)]),
+        Value::Map(vec![entry(1, 1), entry(2, 2)]),
+        Value::Map(vec![entry(2, 2), entry(1, 1)]),
+        Value::Uint(0),
+    ];
+    for a in &keys {
+        for b in &keys {
+            let entries =
+                vec![(a.clone(), Value::Uint(0)), (b.clone(), Value::Uint(1))];
+            let kept = DuplicateKeys::KeepFirst.collect(entries).unwrap();
+            assert_eq!(a.data_model_eq(b), kept.len() == 1, "{a} {b}");
+        }
+    }
+}
+
+#[test]
+fn duplicate_key_path() {
+    // {"a": 0, "b": 0, "a": 0, "c": 0}, of definite and indefinite length
+    let text = |text: &str| Term::Tstr(text.as_bytes().to_vec());
+    let terms = |head: Term| {
+        let mut terms = vec![head.clone()];
+        for label in ["a", "b", "a", "c"] {
+            terms.extend([text(label), Term::Uint(0)]);
+        }
+        if head == Term::MapX {
+            terms.push(Term::Break);
+        }
+        terms
+    };
+    let options = || Options {
+        duplicate_keys: DuplicateKeys::Reject,
+        ..Options::default()
+    };
+    for head in [Term::Map(4), Term::MapX] {
+        let mut ll = ll::Parser::with_builder(options());
+        let mut lr = lr::Parser::with_builder(options());
+        let errors = [
+            first_error(&mut ll, terms(head.clone())),
+            first_error(&mut lr, terms(head)),
+        ];
+        for error in errors {
+            let error = error.unwrap();
+            let Error::DuplicateKey(key) = error.cause() else {
+                panic!("Unexpected error {error}");
+            };
+            assert_eq!(&Value::Tstr("a".into()), key);
+            // The error refers to the duplicate entry, not the last one.
+            assert_eq!(r#".{"a"}"#, error.path().unwrap().to_string());
+        }
+    }
+}
+
+/// Returns the number of bytes `parser` charges to its budget for `terms`.
+fn budget_used(mut parser: impl Parser, terms: Vec<Term>) -> usize {
+    let budget = Budget::new(usize::MAX);
+    parser.set_budget(budget.clone());
+    for term in terms {
+        parser.consume(term).unwrap();
+    }
+    budget.used()
+}
+
+#[test]
+fn duplicate_keys_budget() {
+    // {h'00…00': 0}, with a key of 1000 bytes
+    let terms = || vec![Term::Map(1), Term::Bstr(vec![0; 1000]), Term::Uint(0)];
+    let options = |duplicate_keys| Options {
+        duplicate_keys,
+        ..Options::default()
+    };
+    // The index of the keys is charged to the budget.
+    let ll = |policy| ll::Parser::with_builder(options(policy));
+    let keep_all = budget_used(ll(DuplicateKeys::KeepAll), terms());
+    let reject = budget_used(ll(DuplicateKeys::Reject), terms());
+    assert!(reject >= keep_all + 1000, "{reject} < {keep_all} + 1000");
+    let lr = |policy| lr::Parser::with_builder(options(policy));
+    let keep_all = budget_used(lr(DuplicateKeys::KeepAll), terms());
+    let reject = budget_used(lr(DuplicateKeys::Reject), terms());
+    assert!(reject >= keep_all + 1000, "{reject} < {keep_all} + 1000");
+}
+
+#[test]
+fn unknown_tags() {
+    let mut options = Options::default();
+    let value = || Value::Uint(0);
+    let tagged = Value::Tag(99, Box::new(value()));
+    assert_eq!(tagged, options.apply_tag(99, value()).unwrap());
+    options.unknown_tags = UnknownTags::Strip;
+    assert_eq!(value(), options.apply_tag(99, value()).unwrap());
+    options.unknown_tags = UnknownTags::Reject;
+    let result = options.apply_tag(99, value());
+    assert!(matches!(result, Err(Error::UnknownTag(99))));
+}
+
+#[test]
+fn standard_tags() {
+    let options = Options {
+        tags: TagRegistry::standard(),
+        unknown_tags: UnknownTags::Reject,
+        ..Options::default()
+    };
+    let valid = [
+        (0, Value::Tstr("2013-03-21T20:04:00Z".into())),
+        (1, Value::Uint(1363896240)),
+        (1, Value::Float(Precision::Double, 0x41d4_52d9_ec20_0000)),
+        (2, Value::Bstr(vec![0x01, 0x00])),
+        (3, Value::Bstr(vec![0x01, 0x00])),
+    ];
+    for (tag, value) in valid {
+        let expected = Value::Tag(tag, Box::new(value.clone()));
+        assert_eq!(expected, options.apply_tag(tag, value).unwrap());
+    }
+    let invalid = [
+        (0, Value::Uint(0)),
+        (1, Value::Tstr("now".into())),
+        (2, Value::Uint(0)),
+        (3, Value::Tstr("0".into())),
+    ];
+    for (tag, value) in invalid {
+        let result = options.apply_tag(tag, value);
+        assert!(matches!(result, Err(Error::InvalidTag(t, _)) if t == tag));
+    }
+}
+
+/// Feeds `terms` to `parser`, and returns the first error.
+fn first_error(parser: &mut impl Parser, terms: Vec<Term>) -> Option<Error> {
+    terms.into_iter().find_map(|term| parser.consume(term).err())
+}
+
+#[test]
+fn error_path() {
+    // [0, 0, 0, {"readings": [0, …, 0, 0(0)]}]
+    let readings = || {
+        let mut terms = vec![Term::Array(4)];
+        terms.extend([Term::Uint(0), Term::Uint(0), Term::Uint(0)]);
+        terms.push(Term::Map(1));
+        terms.push(Term::Tstr(b"readings".to_vec()));
+        terms.push(Term::Array(18));
+        terms.extend((0..17).map(Term::Uint));
+        terms.extend([Term::Tag(0), Term::Uint(0)]);
+        terms
+    };
+    // [{0(0): 0}]
+    let key = || {
+        let tag = [Term::Tag(0), Term::Uint(0)];
+        let mut terms = vec![Term::Array(1), Term::Map(1)];
+        terms.extend(tag.into_iter().chain([Term::Uint(0)]));
+        terms
+    };
+    let cases: [(&dyn Fn() -> Vec<Term>, _); 2] = [
+        (&readings, r#"[3].{"readings"}[17]"#),
+        (&key, "[0].{#0}.key"),
+    ];
+    let options = Options {
+        tags: TagRegistry::standard(),
+        ..Options::default()
+    };
+    for (terms, expected) in cases {
+        let mut ll = ll::Parser::cbor();
+        ll.set_options(options.clone());
+        let mut lr = lr::Parser::cbor();
+        lr.set_options(options.clone());
+        let errors =
+            [first_error(&mut ll, terms()), first_error(&mut lr, terms())];
+        for error in errors {
+            let error = error.unwrap();
+            assert_eq!(expected, error.path().unwrap().to_string());
+            assert!(matches!(error.cause(), Error::InvalidTag(0, _)));
+        }
+    }
+    let error = first_error(&mut ll::Parser::cbor(), vec![Term::Break]);
+    assert!(error.unwrap().path().is_none());
+}
+
+/// Collects the chunks of strings
+#[derive(Default)]
+struct Chunks {
+    bytes: Vec<u8>,
+    text: String,
+}
+
+impl event::Handler for Chunks {
+    fn bstr_chunk(&mut self, chunk: &[u8]) {
+        self.bytes.extend_from_slice(chunk);
+    }
+
+    fn tstr_chunk(&mut self, chunk: &str) {
+        self.text.push_str(chunk);
+    }
+}
+
+/// Feeds `terms` to `parser`, and returns the last value.
+fn last_value(
+    mut parser: impl Parser<Output = Value>,
+    terms: &[Term],
+) -> Value {
+    let values = terms.iter().cloned().map(|term| parser.consume(term));
+    values.filter_map(Result::unwrap).last().unwrap()
+}
+
+/// Tests that the chunks of an indefinite-length string may be
+/// indefinite-length strings themselves, whichever the parser.
+#[test]
+fn nested_chunks() {
+    let cases = [
+        (
+            &[0x5f, 0x5f, 0x41, 0x01, 0xff, 0x41, 0x02, 0xff][..],
+            Value::Bstr(vec![0x01, 0x02]),
+        ),
+        (
+            &[0x7f, 0x61, 0x61, 0x7f, 0x61, 0x62, 0xff, 0xff][..],
+            Value::Tstr("ab".into()),
+        ),
+    ];
+    for (bytes, expected) in cases {
+        let mut scanner = crate::scanner::Scanner::default();
+        let terms = bytes
+            .iter()
+            .filter_map(|byte| scanner.consume(*byte).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(expected, last_value(ll::Parser::cbor(), &terms));
+        assert_eq!(expected, last_value(lr::Parser::cbor(), &terms));
+        let mut driver = event::Driver::new(Chunks::default());
+        let accepted = terms.into_iter().map(|term| driver.consume(term));
+        assert_eq!(Some(true), accepted.map(Result::unwrap).last());
+        let chunks = driver.into_handler();
+        match expected {
+            Value::Bstr(bytes) => assert_eq!(bytes, chunks.bytes),
+            _ => assert_eq!(Value::Tstr(chunks.text), expected),
+        }
+    }
+}
+
+/// Domain type built directly by the parsers
+#[derive(Debug, PartialEq)]
+enum Node {
+    Int(i128),
+    Text(String),
+    List(Vec<Node>),
+    Pairs(Vec<(Node, Node)>),
+    Other,
+}
+
+/// Builds [Node]s, and counts the data items built
+#[derive(Default)]
+struct NodeBuilder(usize);
+
+impl Builder for NodeBuilder {
+    type Output = Node;
+    fn uint(&mut self, n: u64) -> Result<Node, Error> {
+        self.0 += 1;
+        Ok(Node::Int(n.into()))
+    }
+    fn nint(&mut self, n: u64) -> Result<Node, Error> {
+        self.0 += 1;
+        Ok(Node::Int(-1 - i128::from(n)))
+    }
+    fn float(&mut self, _: Precision, _: u64) -> Result<Node, Error> {
+        Err(Error::Unexpected("float".into()))
+    }
+    fn simple(&mut self, _: u8) -> Result<Node, Error> {
+        self.0 += 1;
+        Ok(Node::Other)
+    }
+    fn bytes(&mut self, _: Vec<u8>) -> Result<Node, Error> {
+        self.0 += 1;
+        Ok(Node::Other)
+    }
+    fn text(&mut self, text: String) -> Result<Node, Error> {
+        self.0 += 1;
+        Ok(Node::Text(text))
+    }
+    fn array(&mut self, items: Vec<Node>) -> Result<Node, Error> {
+        self.0 += 1;
+        Ok(Node::List(items))
+    }
+    fn map(&mut self, entries: Vec<(Node, Node)>) -> Result<Node, Error> {
+        self.0 += 1;
+        Ok(Node::Pairs(entries))
+    }
+    fn tag(&mut self, _: u64, item: Node) -> Result<Node, Error> {
+        Ok(item)
+    }
+}
+
+#[test]
+fn custom_builder() {
+    // [1, -2, (_ "a", "b"), {_ 1: [_ ]}, 24(h'')]
+    let terms = || {
+        vec![
+            Term::Array(5),
+            Term::Uint(1),
+            Term::Nint(1),
+            Term::TstrX,
+            Term::Tstr(b"a".to_vec()),
+            Term::Tstr(b"b".to_vec()),
+            Term::Break,
+            Term::MapX,
+            Term::Uint(1),
+            Term::ArrayX,
+            Term::Break,
+            Term::Break,
+            Term::Tag(24),
+            Term::Bstr(Vec::new()),
+        ]
+    };
+    let expected = Node::List(vec![
+        Node::Int(1),
+        Node::Int(-2),
+        Node::Text("ab".into()),
+        Node::Pairs(vec![(Node::Int(1), Node::List(Vec::new()))]),
+        Node::Other,
+    ]);
+    let mut ll = ll::Parser::with_builder(NodeBuilder::default());
+    let mut lr = lr::Parser::with_builder(NodeBuilder::default());
+    let (mut ll_result, mut lr_result) = (None, None);
+    for term in terms() {
+        ll_result = ll.consume(term).unwrap();
+    }
+    for term in terms() {
+        lr_result = lr.consume(term).unwrap();
+    }
+    assert_eq!(Some(&expected), ll_result.as_ref());
+    assert_eq!(Some(&expected), lr_result.as_ref());
+    assert_eq!(8, ll.builder().0);
+    assert_eq!(8, lr.builder().0);
+    // Errors of the builder carry the path of the data item.
+    let float = Term::Float(Precision::Half, 0);
+    let terms = || vec![Term::Array(2), Term::Uint(0), float.clone()];
+    let mut ll = ll::Parser::with_builder(NodeBuilder::default());
+    let mut lr = lr::Parser::with_builder(NodeBuilder::default());
+    for error in [first_error(&mut ll, terms()), first_error(&mut lr, terms())]
+    {
+        let error = error.unwrap();
+        assert!(matches!(error.cause(), Error::Unexpected(_)));
+        assert_eq!("[1]", error.path().unwrap().to_string());
+    }
+}
+
+#[test]
+fn progress() {
+    // [1, {_ "a": [7, …], "b": 2(…
+    let terms = || {
+        vec![
+            Term::Array(3),
+            Term::Uint(1),
+            Term::MapX,
+            Term::Tstr(b"a".to_vec()),
+            Term::Array(2),
+            Term::Uint(7),
+        ]
+    };
+    let check = |progress: Progress<'_, Value>| {
+        assert_eq!(r#"[1].{"a"}[1]"#, progress.path.to_string());
+        let open = progress
+            .open
+            .iter()
+            .map(|open| (open.kind, open.remaining(), open.items.clone()))
+            .collect::<Vec<_>>();
+        let label = Value::Tstr("a".into());
+        let expected = vec![
+            (OpenKind::Array, Some(2), vec![&Value::Uint(1)]),
+            (OpenKind::Map, None, vec![&label]),
+            (OpenKind::Array, Some(1), vec![&Value::Uint(7)]),
+        ];
+        assert_eq!(expected, open);
+    };
+    let mut ll = ll::Parser::cbor();
+    let mut lr = lr::Parser::cbor();
+    assert!(ll.progress().open.is_empty());
+    for term in terms() {
+        ll.consume(term).unwrap();
+    }
+    for term in terms() {
+        lr.consume(term).unwrap();
+    }
+    check(ll.progress());
+    check(lr.progress());
+    // Complete the entry, and open a tag.
+    let more = || vec![Term::Uint(8), Term::Tstr(b"b".to_vec()), Term::Tag(2)];
+    for term in more() {
+        ll.consume(term).unwrap();
+    }
+    for term in more() {
+        lr.consume(term).unwrap();
+    }
+    for progress in [ll.progress(), lr.progress()] {
+        let [_, map, tag] = &progress.open[..] else {
+            panic!("Expected three open data items");
+        };
+        let entry = Value::Array(vec![Value::Uint(7), Value::Uint(8)]);
+        let items = [Value::Tstr("a".into()), entry, Value::Tstr("b".into())];
+        assert_eq!(items.iter().collect::<Vec<_>>(), map.items);
+        assert_eq!(OpenKind::Tag, tag.kind);
+    }
+}
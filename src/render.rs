@@ -0,0 +1,322 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! # Example
+//!
+//!     use cbor::decoder::Decoder;
+//!     use cbor::render::Renderer;
+//!
+//!     // 1(1363896240)
+//!     let bytes = [0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0];
+//!     let (value, _) = Decoder::ll().decode(&bytes).unwrap();
+//!     assert_eq!("1(1363896240)", value.diagnostic().to_string());
+//!
+//!     // [_ -1, "a", h'0a0b']
+//!     let bytes = [0x9f, 0x20, 0x61, 0x61, 0x42, 0x0a, 0x0b, 0xff];
+//!     let mut decoder = Decoder::new(Renderer::default());
+//!     let (text, _) = decoder.decode(&bytes).unwrap();
+//!     assert_eq!(r#"[_ -1, "a", h'0a0b']"#, text);
+
+use std::fmt;
+
+use crate::parser::{Error, Parser};
+use crate::token::{Precision, Token};
+use crate::value::{Value, float_to_f64};
+
+/// Value in diagnostic notation
+///
+/// The [Display](fmt::Display) implementation renders the value in the
+/// diagnostic notation of RFC 8949, section 8. Since a value does not retain
+/// the encoding details of its data item, such as indefinite lengths, use a
+/// [Renderer] to render those.
+#[derive(Clone, Copy, Debug)]
+pub struct Diagnostic<'a>(pub &'a Value);
+
+impl Value {
+    /// Returns the value for display in diagnostic notation.
+    pub fn diagnostic(&self) -> Diagnostic<'_> {
+        Diagnostic(self)
+    }
+}
+
+impl fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Uint(n) => write!(f, "{n}"),
+            Value::Nint(n) => write!(f, "{}", -1 - i128::from(*n)),
+            Value::Float(precision, bits) => write_float(f, *precision, *bits),
+            Value::Bstr(bytes) => write_bytes(f, bytes),
+            Value::Tstr(text) => write_text(f, text),
+            Value::Simple(n) => write_simple(f, *n),
+            Value::Tag(tag, tagged) => {
+                write!(f, "{tag}({})", Diagnostic(tagged))
+            }
+            Value::Array(elements) => {
+                f.write_str("[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", Diagnostic(element))?;
+                }
+                f.write_str("]")
+            }
+            Value::Map(entries) => {
+                f.write_str("{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", Diagnostic(key), Diagnostic(value))?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Writes a floating-point number like a JSON number, or as `NaN`,
+/// `Infinity` or `-Infinity`.
+///
+/// Numbers of single precision are written with the fewest digits that
+/// identify them in single precision, e.g., `0.1` rather than
+/// `0.10000000149011612`.
+fn write_float(
+    f: &mut impl fmt::Write,
+    precision: Precision,
+    bits: u64,
+) -> fmt::Result {
+    let number = float_to_f64(precision, bits);
+    if number.is_nan() {
+        f.write_str("NaN")
+    } else if number.is_infinite() {
+        f.write_str(if number > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
+        })
+    } else if precision == Precision::Single {
+        write!(f, "{:?}", number as f32)
+    } else {
+        write!(f, "{number:?}")
+    }
+}
+
+/// Writes a byte string in base16, e.g., `h'0a0b'`.
+fn write_bytes(f: &mut impl fmt::Write, bytes: &[u8]) -> fmt::Result {
+    f.write_str("h'")?;
+    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))?;
+    f.write_str("'")
+}
+
+/// Writes a text string with the escapes of JSON.
+fn write_text(f: &mut impl fmt::Write, text: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", u32::from(c))?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Writes a simple value by its name, or as `simple(n)`.
+fn write_simple(f: &mut impl fmt::Write, n: u8) -> fmt::Result {
+    match n {
+        20 => f.write_str("false"),
+        21 => f.write_str("true"),
+        22 => f.write_str("null"),
+        23 => f.write_str("undefined"),
+        n => write!(f, "simple({n})"),
+    }
+}
+
+/// Parser that renders a data item in diagnostic notation
+///
+/// Unlike [Diagnostic], the renderer works on tokens, and thus renders
+/// indefinite lengths: `[_ 1, 2]` and `{_ 1: 2}` for arrays and maps, and
+/// `(_ h'01', h'02')` for strings of chunks. It neither builds values, nor
+/// checks map keys or tags.
+#[derive(Clone, Debug, Default)]
+pub struct Renderer {
+    output: String,
+    frames: Vec<Frame>,
+}
+
+/// Open data item
+#[derive(Clone, Debug)]
+struct Frame {
+    kind: FrameKind,
+    /// Number of items or map entries expected, or `None` for indefinite
+    /// length
+    remaining: Option<u64>,
+    /// Number of items rendered, counting keys and values of map entries
+    count: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FrameKind {
+    Array,
+    Map,
+    Tag,
+    Bstr,
+    Tstr,
+}
+
+impl Renderer {
+    /// Writes the separator that precedes the next item.
+    fn separate(&mut self) {
+        let Some(frame) = self.frames.last() else {
+            return;
+        };
+        let separator = match frame.kind {
+            FrameKind::Tag => "",
+            FrameKind::Bstr | FrameKind::Tstr if frame.count == 0 => "(_ ",
+            FrameKind::Map if frame.count % 2 == 1 => ": ",
+            _ if frame.count == 0 => "",
+            _ => ", ",
+        };
+        self.output.push_str(separator);
+    }
+
+    /// Opens a data item with the given number of items, and writes `open`.
+    fn open(&mut self, kind: FrameKind, remaining: Option<u64>, open: &str) {
+        self.output.push_str(open);
+        self.frames.push(Frame {
+            kind,
+            remaining,
+            count: 0,
+        });
+        if remaining == Some(0) {
+            self.close();
+        }
+    }
+
+    /// Closes the innermost data item, and records its completion.
+    fn close(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        let close = match (frame.kind, frame.count) {
+            (FrameKind::Array, _) => "]",
+            (FrameKind::Map, _) => "}",
+            (FrameKind::Tag, _) => ")",
+            (FrameKind::Bstr, 0) => "''_",
+            (FrameKind::Tstr, 0) => "\"\"_",
+            (FrameKind::Bstr | FrameKind::Tstr, _) => ")",
+        };
+        self.output.push_str(close);
+        self.complete();
+    }
+
+    /// Records the completion of an item, and closes the innermost data item
+    /// if it is complete.
+    fn complete(&mut self) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        frame.count += 1;
+        if frame.kind == FrameKind::Map && frame.count % 2 == 1 {
+            return;
+        }
+        if let Some(remaining) = &mut frame.remaining {
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.close();
+            }
+        }
+    }
+
+    /// Returns the kind of the innermost data item, if it is a string of
+    /// chunks.
+    fn chunks(&self) -> Option<FrameKind> {
+        let kind = self.frames.last()?.kind;
+        matches!(kind, FrameKind::Bstr | FrameKind::Tstr).then_some(kind)
+    }
+}
+
+impl Parser for Renderer {
+    type Output = String;
+
+    fn consume(&mut self, term: Token) -> Result<Option<String>, Error> {
+        let unexpected =
+            |term: &Token| Err(Error::Unexpected(format!("Unexpected {term}")));
+        match (self.chunks(), &term) {
+            (_, Token::Break) => {}
+            (None, _)
+            | (Some(FrameKind::Bstr), Token::Bstr(_))
+            | (Some(FrameKind::Tstr), Token::Tstr(_)) => {}
+            _ => return unexpected(&term),
+        }
+        if !matches!(term, Token::Break) {
+            self.separate();
+        }
+        match term {
+            // A map must not break between a key and its value.
+            Token::Break => match self.frames.last() {
+                Some(Frame {
+                    kind: FrameKind::Map,
+                    count,
+                    ..
+                }) if count % 2 == 1 => return unexpected(&term),
+                Some(frame) if frame.remaining.is_none() => self.close(),
+                _ => return unexpected(&term),
+            },
+            Token::Tstr(bytes) => {
+                let text = String::from_utf8(bytes)?;
+                let _ = write_text(&mut self.output, &text);
+                self.complete();
+            }
+            Token::BstrX => self.open(FrameKind::Bstr, None, ""),
+            Token::TstrX => self.open(FrameKind::Tstr, None, ""),
+            Token::ArrayX => self.open(FrameKind::Array, None, "[_ "),
+            Token::Array(n) => self.open(FrameKind::Array, Some(n), "["),
+            Token::MapX => self.open(FrameKind::Map, None, "{_ "),
+            Token::Map(n) => self.open(FrameKind::Map, Some(n), "{"),
+            Token::Tag(tag) => {
+                self.open(FrameKind::Tag, Some(1), &format!("{tag}("))
+            }
+            token => {
+                let value =
+                    Value::try_from(token).map_err(|_| Error::Internal)?;
+                self.output += &value.diagnostic().to_string();
+                self.complete();
+            }
+        }
+        if self.frames.is_empty() {
+            Ok(Some(std::mem::take(&mut self.output)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.output.clear();
+        self.frames.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,147 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use crate::decoder::Decoder;
+
+fn render(bytes: &[u8]) -> String {
+    let mut decoder = Decoder::new(Renderer::default());
+    let (text, count) = decoder.decode(bytes).unwrap();
+    assert_eq!(bytes.len(), count);
+    text
+}
+
+fn diagnostic(bytes: &[u8]) -> String {
+    let (value, _) = Decoder::ll().decode(bytes).unwrap();
+    value.diagnostic().to_string()
+}
+
+/// Examples of RFC 8949, appendix A
+const EXAMPLES: [(&[u8], &str); 24] = [
+    (b"\x00", "0"),
+    (
+        b"\x1b\xff\xff\xff\xff\xff\xff\xff\xff",
+        "18446744073709551615",
+    ),
+    (
+        b"\x3b\xff\xff\xff\xff\xff\xff\xff\xff",
+        "-18446744073709551616",
+    ),
+    (b"\x20", "-1"),
+    (b"\x39\x03\xe7", "-1000"),
+    (b"\xf9\x3e\x00", "1.5"),
+    (b"\xf9\x80\x00", "-0.0"),
+    (b"\xfb\x7e\x37\xe4\x3c\x88\x00\x75\x9c", "1e300"),
+    (b"\xf9\x7c\x00", "Infinity"),
+    (b"\xf9\x7e\x00", "NaN"),
+    (b"\xfa\xff\x80\x00\x00", "-Infinity"),
+    (b"\xf4", "false"),
+    (b"\xf5", "true"),
+    (b"\xf6", "null"),
+    (b"\xf7", "undefined"),
+    (b"\xf0", "simple(16)"),
+    (b"\xf8\xff", "simple(255)"),
+    (b"\xc1\x1a\x51\x4b\x67\xb0", "1(1363896240)"),
+    (b"\x44\x01\x02\x03\x04", "h'01020304'"),
+    (b"\x62\x22\x5c", r#""\"\\""#),
+    (b"\x63\xe6\xb0\xb4", "\"\u{6c34}\""),
+    (b"\x83\x01\x82\x02\x03\x82\x04\x05", "[1, [2, 3], [4, 5]]"),
+    (
+        b"\xa2\x61\x61\x01\x61\x62\x82\x02\x03",
+        r#"{"a": 1, "b": [2, 3]}"#,
+    ),
+    (b"\xa0", "{}"),
+];
+
+#[test]
+fn examples() {
+    for (bytes, expected) in EXAMPLES {
+        assert_eq!(expected, diagnostic(bytes));
+        assert_eq!(expected, render(bytes));
+    }
+}
+
+#[test]
+fn single_precision() {
+    assert_eq!("0.1", diagnostic(b"\xfa\x3d\xcc\xcc\xcd"));
+    assert_eq!("0.1", render(b"\xfa\x3d\xcc\xcc\xcd"));
+    assert_eq!("100000.0", diagnostic(b"\xfa\x47\xc3\x50\x00"));
+    assert_eq!("0.1", diagnostic(b"\xfb\x3f\xb9\x99\x99\x99\x99\x99\x9a"));
+}
+
+#[test]
+fn indefinite() {
+    let examples: [(&[u8], &str); 8] = [
+        (
+            b"\x5f\x42\x01\x02\x43\x03\x04\x05\xff",
+            "(_ h'0102', h'030405')",
+        ),
+        (b"\x7f\x65strea\x64ming\xff", r#"(_ "strea", "ming")"#),
+        (b"\x5f\xff", "''_"),
+        (b"\x7f\xff", "\"\"_"),
+        (b"\x9f\xff", "[_ ]"),
+        (
+            b"\x9f\x01\x82\x02\x03\x9f\x04\x05\xff\xff",
+            "[_ 1, [2, 3], [_ 4, 5]]",
+        ),
+        (
+            b"\xbf\x61\x61\x01\x61\x62\x9f\x02\x03\xff\xff",
+            r#"{_ "a": 1, "b": [_ 2, 3]}"#,
+        ),
+        (b"\xc2\x9f\xc3\x80\xff", "2([_ 3([])])"),
+    ];
+    for (bytes, expected) in examples {
+        assert_eq!(expected, render(bytes));
+    }
+    let expected = r#"[1, [2, 3], [4, 5]]"#;
+    assert_eq!(expected, diagnostic(examples[5].0));
+}
+
+#[test]
+fn escapes() {
+    let value = Value::Tstr("a\"b\\c\nd\u{1}".into());
+    assert_eq!(r#""a\"b\\c\nd\u0001""#, value.diagnostic().to_string());
+}
+
+#[test]
+fn unexpected() {
+    let mut renderer = Renderer::default();
+    let result = renderer.consume(Token::Break);
+    assert!(matches!(result, Err(Error::Unexpected(_))));
+    renderer.reset();
+    assert_eq!(None, renderer.consume(Token::BstrX).unwrap());
+    let result = renderer.consume(Token::Uint(0));
+    assert!(matches!(result, Err(Error::Unexpected(_))));
+    renderer.reset();
+    assert_eq!(None, renderer.consume(Token::Array(1)).unwrap());
+    let result = renderer.consume(Token::Break);
+    assert!(matches!(result, Err(Error::Unexpected(_))));
+    renderer.reset();
+    // bf 01 ff: the map breaks after a key without a value
+    for term in [Token::MapX, Token::Uint(1)] {
+        assert_eq!(None, renderer.consume(term).unwrap());
+    }
+    let result = renderer.consume(Token::Break);
+    assert!(matches!(result, Err(Error::Unexpected(_))));
+    renderer.reset();
+    let result = renderer.consume(Token::Tstr(vec![0xff]));
+    assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+    renderer.reset();
+    assert_eq!(Some("0".into()), renderer.consume(Token::Uint(0)).unwrap());
+}
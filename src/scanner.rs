@@ -36,7 +36,8 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::mem;
 
-use crate::token::{Kind, Token};
+use crate::budget::Budget;
+use crate::token::{Kind, Precision, Token};
 
 pub mod error;
 pub use error::Error;
@@ -49,6 +50,48 @@ pub use error::Error;
 #[derive(Debug, Default)]
 pub struct Scanner {
     state: ScanState,
+    offset: u64,
+    head: Head,
+    budget: Budget,
+}
+
+/// Head of an encoded token
+///
+/// The head records the offset and the initial byte of the token. The initial
+/// byte encodes the major type and the width of the argument, and so
+/// preserves encoding details that are not represented in the token; the
+/// argument bytes themselves are not kept.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Head {
+    /// Offset of the initial byte in the input
+    pub offset: u64,
+    /// Initial byte
+    pub initial_byte: u8,
+}
+
+impl Head {
+    /// Returns the major type encoded in the initial byte.
+    pub fn major_type(&self) -> u8 {
+        self.initial_byte >> 5
+    }
+
+    /// Returns the additional information encoded in the initial byte.
+    pub fn additional_info(&self) -> u8 {
+        self.initial_byte & 0x1f
+    }
+
+    /// Returns the number of argument bytes following the initial byte, or
+    /// `None` for indefinite-length items and `%break`.
+    pub fn argument_width(&self) -> Option<usize> {
+        match self.additional_info() {
+            0..=23 => Some(0),
+            24 => Some(1),
+            25 => Some(2),
+            26 => Some(4),
+            27 => Some(8),
+            _ => None,
+        }
+    }
 }
 
 impl Scanner {
@@ -70,7 +113,14 @@ impl Scanner {
         // TODO: Define invalid state to stand in for `self.state` until
         //       overwritten by `new_state`
         let state = mem::take(&mut self.state);
-        match consume(state, byte) {
+        if let ScanState::Head = state {
+            self.head = Head {
+                offset: self.offset,
+                initial_byte: byte,
+            };
+        }
+        self.offset += 1;
+        match consume(state, byte, &self.budget) {
             Incomplete(scan_state) => {
                 self.state = scan_state;
                 Ok(None)
@@ -91,12 +141,12 @@ impl Scanner {
     /// - `Err(scan_error)` if something went wrong.
     pub fn consume_until_complete<'a, Iter>(
         &mut self,
-        iter: &mut  Iter,
+        iter: &mut Iter,
     ) -> Result<Option<Token>, Error>
     where
         Iter: Iterator<Item = &'a u8>,
     {
-        while let Some(byte) = iter.next() {
+        for byte in iter.by_ref() {
             if let some @ Some(_) = self.consume(*byte)? {
                 return Ok(some);
             }
@@ -104,6 +154,24 @@ impl Scanner {
         Ok(None)
     }
 
+    /// Returns the number of bytes consumed by the scanner.
+    ///
+    /// The count includes the bytes consumed before any [reset](Scanner::reset).
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the head of the last token, or of the token being scanned.
+    pub fn head(&self) -> Head {
+        self.head
+    }
+
+    /// Sets the memory budget, which the scanner charges with the payload
+    /// bytes of strings.
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
     /// Resets the scanner.
     pub fn reset(&mut self) {
         self.state = ScanState::default()
@@ -154,6 +222,8 @@ enum ScanState {
         arg: u64,
         /// Number of bytes pending
         pending: usize,
+        /// Width of the argument
+        width: Argc,
     },
     /// Expecting the next byte to be part of the binary payload.
     Pay {
@@ -201,6 +271,9 @@ impl From<Argc> for usize {
     }
 }
 
+/// Returns the token of kind `kind`.
+///
+/// Floats carry their precision, and are built by [token_float] instead.
 fn token(kind: Kind, argument: u64, payload: Vec<u8>) -> ScanResult {
     ScanResult::Complete(
         ScanState::Head,
@@ -216,13 +289,25 @@ fn token(kind: Kind, argument: u64, payload: Vec<u8>) -> ScanResult {
             Kind::MapX => Token::MapX,
             Kind::Map => Token::Map(argument),
             Kind::Tag => Token::Tag(argument),
-            Kind::Simple => Token::Simple(argument.try_into().unwrap()),
-            Kind::Float => Token::Float(argument),
+            Kind::Simple => match u8::try_from(argument) {
+                Ok(value) => Token::Simple(value),
+                Err(_) => return ScanResult::Error(Error::Internal),
+            },
+            Kind::Float => return ScanResult::Error(Error::Internal),
             Kind::Break => Token::Break,
         },
     )
 }
 
+fn token_float(width: Argc, bits: u64) -> ScanResult {
+    let precision = match width {
+        Argc::N2 => Precision::Half,
+        Argc::N4 => Precision::Single,
+        _ => Precision::Double,
+    };
+    ScanResult::Complete(ScanState::Head, Token::Float(precision, bits))
+}
+
 fn token_uint(arg: u64) -> ScanResult {
     token(Kind::Uint, arg, Vec::new())
 }
@@ -288,11 +373,15 @@ fn gather_argument(kind: Kind, count: Argc) -> ScanResult {
         kind,
         arg: 0,
         pending: count.into(),
+        width: count,
     })
 }
 
-fn gather_bytes(kind: Kind, count: u64) -> ScanResult {
+fn gather_bytes(kind: Kind, count: u64, budget: &Budget) -> ScanResult {
     if let Ok(sz) = count.try_into() {
+        if let Err(exceeded) = budget.charge(sz) {
+            return ScanResult::Error(Error::BudgetExceeded(exceeded.0));
+        }
         ScanResult::Incomplete(ScanState::Pay {
             kind,
             bytes: Vec::with_capacity(sz),
@@ -309,7 +398,7 @@ fn gather_bytes(kind: Kind, count: u64) -> ScanResult {
 /// returns the token; otherwise, returns `Incomplete`, signalling
 /// to the caller to provide more bytes. In case of an error,
 /// returns the error.
-fn consume(state: ScanState, byte: u8) -> ScanResult {
+fn consume(state: ScanState, byte: u8, budget: &Budget) -> ScanResult {
     match state {
         ScanState::Head => match byte {
             // UINT
@@ -326,7 +415,9 @@ fn consume(state: ScanState, byte: u8) -> ScanResult {
             0x3b => gather_argument(Kind::Nint, Argc::N8),
             // BSTR
             0x40 => token_bstr_empty(),
-            0x41..=0x57 => gather_bytes(Kind::Bstr, (byte - 0x40).into()),
+            0x41..=0x57 => {
+                gather_bytes(Kind::Bstr, (byte - 0x40).into(), budget)
+            }
             0x58 => gather_argument(Kind::Bstr, Argc::N1),
             0x59 => gather_argument(Kind::Bstr, Argc::N2),
             0x5a => gather_argument(Kind::Bstr, Argc::N4),
@@ -334,7 +425,9 @@ fn consume(state: ScanState, byte: u8) -> ScanResult {
             0x5f => token_bstr_indef(),
             // TSTR
             0x60 => token_tstr_empty(),
-            0x61..=0x77 => gather_bytes(Kind::Tstr, (byte - 0x60).into()),
+            0x61..=0x77 => {
+                gather_bytes(Kind::Tstr, (byte - 0x60).into(), budget)
+            }
             0x78 => gather_argument(Kind::Tstr, Argc::N1),
             0x79 => gather_argument(Kind::Tstr, Argc::N2),
             0x7a => gather_argument(Kind::Tstr, Argc::N4),
@@ -378,13 +471,21 @@ fn consume(state: ScanState, byte: u8) -> ScanResult {
             kind,
             mut arg,
             mut pending,
+            width,
         } => {
             assert!(pending > 0);
             arg <<= 8;
             arg |= u64::from(byte);
             pending -= 1;
             if pending > 0 {
-                ScanResult::Incomplete(ScanState::Arg { kind, arg, pending })
+                ScanResult::Incomplete(ScanState::Arg {
+                    kind,
+                    arg,
+                    pending,
+                    width,
+                })
+            } else if kind == Kind::Float {
+                token_float(width, arg)
             } else if arg == 0 {
                 match kind {
                     Kind::Bstr => token_bstr_empty(),
@@ -395,7 +496,7 @@ fn consume(state: ScanState, byte: u8) -> ScanResult {
                 }
             } else {
                 match kind {
-                    Kind::Bstr | Kind::Tstr => gather_bytes(kind, arg),
+                    Kind::Bstr | Kind::Tstr => gather_bytes(kind, arg, budget),
                     _ => token(kind, arg, Vec::new()),
                 }
             }
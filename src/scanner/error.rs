@@ -19,7 +19,7 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 /// What could possibly go wrong when scanning binary data for CBOR encoded
 /// information?
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Error {
     /// The scanner encountered an unexpected end-of-file
     UnexpectedEof,
@@ -28,6 +28,10 @@ pub enum Error {
     /// The scanner encountered a byte count or item count that can not be
     /// represented in the `usize` of the platform.
     Excessive(u64),
+    /// The payload of a string exceeds the memory budget of this many bytes.
+    BudgetExceeded(usize),
+    /// Internal error
+    Internal,
 }
 
 impl std::error::Error for Error {}
@@ -39,6 +43,10 @@ impl std::fmt::Display for Error {
             UnexpectedEof => write!(f, "Unexpected EOF"),
             UnexpectedHead(head) => write!(f, "Unexpected head: {head}"),
             Excessive(count) => write!(f, "Excessive count ({count})"),
+            BudgetExceeded(limit) => {
+                write!(f, "Memory budget of {limit} bytes exceeded")
+            }
+            Internal => write!(f, "500 Internal Error"),
         }
     }
 }
@@ -109,7 +109,7 @@ mod uint {
             let token =
                 scanner.consume_until_complete(&mut iter).unwrap().unwrap();
             assert_eq!(None, iter.next());
-            assert_eq!(Token::Uint(value.into()), token)
+            assert_eq!(Token::Uint(value), token)
         }
     }
 }
@@ -205,3 +205,53 @@ mod bstr {
         assert_eq!(Token::Bstr(expected), token)
     }
 }
+
+mod float {
+
+    use super::*;
+    use crate::token::Precision;
+
+    /// Tests that the precision of a float follows from the width of its
+    /// argument, not from its bits.
+    #[test]
+    fn precision() {
+        let bytes = [0xf9, 0, 1, 0xfa, 0, 0, 0, 1, 0xfb, 0, 0, 0, 0, 0, 0, 0, 1];
+        let expected = [Precision::Half, Precision::Single, Precision::Double];
+        let mut scanner = Scanner::default();
+        let mut iter = bytes.iter();
+        for precision in expected {
+            let token = scanner.consume_until_complete(&mut iter).unwrap();
+            assert_eq!(Some(Token::Float(precision, 1)), token);
+        }
+    }
+    /// Tests that a float without precision is an internal error, not a
+    /// panic.
+    #[test]
+    fn without_precision() {
+        let result = token(Kind::Float, 0, Vec::new());
+        assert!(matches!(result, ScanResult::Error(Error::Internal)));
+    }
+}
+
+mod head {
+
+    use super::*;
+
+    /// Tests that the scanner reports the head and offset of each token.
+    #[test]
+    fn offsets_and_widths() {
+        let bytes = [0x01, 0x19, 0x01, 0x00, 0x9f, 0xfb, 0, 0, 0, 0, 0, 0, 0, 0];
+        let expected = [(0, Some(0)), (1, Some(2)), (4, None), (5, Some(8))];
+        let mut scanner = Scanner::default();
+        let mut iter = bytes.iter();
+        for (offset, width) in expected {
+            scanner.consume_until_complete(&mut iter).unwrap().unwrap();
+            let head = scanner.head();
+            assert_eq!(offset, head.offset);
+            assert_eq!(width, head.argument_width());
+        }
+        assert_eq!(bytes.len() as u64, scanner.offset());
+        assert_eq!(7, scanner.head().major_type());
+        assert_eq!(27, scanner.head().additional_info());
+    }
+}
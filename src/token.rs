@@ -71,8 +71,23 @@ impl std::fmt::Display for Kind {
     }
 }
 
+/// Precision of an encoded floating-point number
+///
+/// The precision follows from the width of the argument: half precision for
+/// two bytes, single precision for four bytes, and double precision for eight
+/// bytes.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Precision {
+    /// IEEE 754 binary16
+    Half,
+    /// IEEE 754 binary32
+    Single,
+    /// IEEE 754 binary64
+    Double,
+}
+
 /// Structured representation of a token.
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Token {
     /// Token for a natural number
     Uint(u64),
@@ -98,8 +113,8 @@ pub enum Token {
     Tag(u64),
     /// Token for a simple value
     Simple(u8),
-    /// Token for a floating-point value
-    Float(u64),
+    /// Token for a floating-point value, with the bits in the encoded precision
+    Float(Precision, u64),
     /// Token for the end of a sequence of indefinite length
     Break,
 }
@@ -121,7 +136,7 @@ impl Token {
             Map(_)    => Kind::Map,
             Tag(_)    => Kind::Tag,
             Simple(_) => Kind::Simple,
-            Float(_)  => Kind::Float,
+            Float(..) => Kind::Float,
             Break     => Kind::Break,
         }
     }
@@ -131,20 +146,20 @@ impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Token::*;
         match self {
-            Uint(arg)   => write!(f, "%uint({arg})"),
-            Nint(arg)   => write!(f, "%nint({arg})"),
-            BstrX       => write!(f, "%bstrx"),
-            Bstr(bytes) => write!(f, "%bstr{bytes:?}"),
-            TstrX       => write!(f, "%tstrx"),
-            Tstr(bytes) => write!(f, "%tstr{bytes:?}"),
-            ArrayX      => write!(f, "%arrayx"),
-            Array(arg)  => write!(f, "%array({arg})"),
-            MapX        => write!(f, "%mapx"),
-            Map(arg)    => write!(f, "%map({arg})"),
-            Tag(arg)    => write!(f, "%tag({arg})"),
-            Simple(arg) => write!(f, "%simple({arg})"),
-            Float(arg)  => write!(f, "%float({arg})"),
-            Break       => write!(f, "%break"),
+            Uint(arg)     => write!(f, "%uint({arg})"),
+            Nint(arg)     => write!(f, "%nint({arg})"),
+            BstrX         => write!(f, "%bstrx"),
+            Bstr(bytes)   => write!(f, "%bstr{bytes:?}"),
+            TstrX         => write!(f, "%tstrx"),
+            Tstr(bytes)   => write!(f, "%tstr{bytes:?}"),
+            ArrayX        => write!(f, "%arrayx"),
+            Array(arg)    => write!(f, "%array({arg})"),
+            MapX          => write!(f, "%mapx"),
+            Map(arg)      => write!(f, "%map({arg})"),
+            Tag(arg)      => write!(f, "%tag({arg})"),
+            Simple(arg)   => write!(f, "%simple({arg})"),
+            Float(_, arg) => write!(f, "%float({arg})"),
+            Break         => write!(f, "%break"),
         }
     }
 }
@@ -17,7 +17,7 @@ You should have received a copy of the GNU General Public License
 along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::Token;
+use super::{Precision, Token};
 
 #[test]
 fn display_uint() {
@@ -117,7 +117,7 @@ fn display_simple() {
 
 #[test]
 fn display_float() {
-    let token = Token::Float(12345);
+    let token = Token::Float(Precision::Half, 12345);
     let expected = "%float(12345)";
     let actual = format!("{token}");
     assert_eq!(expected, actual)
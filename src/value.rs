@@ -29,22 +29,35 @@ along with this program. If not, see <https://www.gnu.org/licenses/>.
 //! - a sequence of pairs of values.
 
 use crate::token;
+pub use crate::token::Precision;
+use std::cmp::Ordering;
 use std::error;
 use std::fmt;
 
+/// Lookup of items by index, key, or pointer
+mod index;
+pub use index::ValueIndex;
+
 /// CBOR value
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Values are ordered in the bytewise lexicographic order of their encodings,
+/// as required for the deterministic encoding by RFC 8949, section 4.2.1.
+/// Unlike the deterministic encoding, however, the order and the equality of
+/// values take map entries in their given order, and floating-point numbers
+/// in their given width and bits; see [Value::data_model_eq()] for an
+/// equality that does not.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Value {
     /// Non-negative integer
     Uint(u64),
     /// Negative integer
     Nint(u64),
-    /// Floating-point number
-    Float(u64),
+    /// Floating-point number, with the bits in the given precision
+    Float(Precision, u64),
     /// Byte string
     Bstr(Vec<u8>),
     /// Text string
-    Tstr(Vec<u8>),
+    Tstr(String),
     /// Simple value
     Simple(u8),
     /// Tagged value
@@ -65,6 +78,24 @@ impl Value {
         }
     }
 
+    /// Return reference to value as array, or `None`
+    pub fn as_array_ref(&self) -> Option<&Vec<Value>> {
+        if let Value::Array(elements) = self {
+            Some(elements)
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable reference to value as array, or `None`
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        if let Value::Array(elements) = self {
+            Some(elements)
+        } else {
+            None
+        }
+    }
+
     /// Return value as byte string, or `None`
     pub fn as_bstr(self) -> Option<Vec<u8>> {
         if let Value::Bstr(bytes) = self {
@@ -74,6 +105,53 @@ impl Value {
         }
     }
 
+    /// Return reference to value as byte string, or `None`
+    pub fn as_bstr_ref(&self) -> Option<&Vec<u8>> {
+        if let Value::Bstr(bytes) = self {
+            Some(bytes)
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable reference to value as byte string, or `None`
+    pub fn as_bstr_mut(&mut self) -> Option<&mut Vec<u8>> {
+        if let Value::Bstr(bytes) = self {
+            Some(bytes)
+        } else {
+            None
+        }
+    }
+
+    /// Return value as precision and bits of floating-point number, or `None`
+    pub fn as_float(self) -> Option<(Precision, u64)> {
+        if let Value::Float(precision, bits) = self {
+            Some((precision, bits))
+        } else {
+            None
+        }
+    }
+
+    /// Return reference to value as precision and bits of floating-point
+    /// number, or `None`
+    pub fn as_float_ref(&self) -> Option<(Precision, &u64)> {
+        if let Value::Float(precision, bits) = self {
+            Some((*precision, bits))
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable reference to value as precision and bits of
+    /// floating-point number, or `None`
+    pub fn as_float_mut(&mut self) -> Option<(&mut Precision, &mut u64)> {
+        if let Value::Float(precision, bits) = self {
+            Some((precision, bits))
+        } else {
+            None
+        }
+    }
+
     /// Return value as map, or `None`
     pub fn as_map(self) -> Option<Vec<(Value, Value)>> {
         if let Value::Map(entries) = self {
@@ -92,10 +170,42 @@ impl Value {
         }
     }
 
-    /// Return value as negative integer, or `None`
+    /// Return mutable reference to value as map, or `None`
+    pub fn as_map_mut(&mut self) -> Option<&mut Vec<(Value, Value)>> {
+        if let Value::Map(entries) = self {
+            Some(entries)
+        } else {
+            None
+        }
+    }
+
+    /// Return value as the argument of a negative integer, or `None`
+    ///
+    /// The argument `n` stands for the integer `-1 - n`; see
+    /// [as_i128](Value::as_i128) for the integer itself.
     pub fn as_nint(self) -> Option<u64> {
         if let Value::Nint(number) = self {
-            Some(number) // TODO: return -1 - number here, but get the conversion right
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    /// Return reference to value as the argument of a negative integer, or
+    /// `None`
+    pub fn as_nint_ref(&self) -> Option<&u64> {
+        if let Value::Nint(number) = self {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable reference to value as the argument of a negative
+    /// integer, or `None`
+    pub fn as_nint_mut(&mut self) -> Option<&mut u64> {
+        if let Value::Nint(number) = self {
+            Some(number)
         } else {
             None
         }
@@ -110,6 +220,24 @@ impl Value {
         }
     }
 
+    /// Return reference to value as simple value, or `None`
+    pub fn as_simple_ref(&self) -> Option<&u8> {
+        if let Value::Simple(number) = self {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable reference to value as simple value, or `None`
+    pub fn as_simple_mut(&mut self) -> Option<&mut u8> {
+        if let Value::Simple(number) = self {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
     /// Return value as tagged value, or `None`
     pub fn as_tag(self) -> Option<(u64, Value)> {
         if let Value::Tag(tag, tagged) = self {
@@ -119,10 +247,55 @@ impl Value {
         }
     }
 
+    /// Return reference to value as tagged value, or `None`
+    pub fn as_tag_ref(&self) -> Option<(u64, &Value)> {
+        if let Value::Tag(tag, tagged) = self {
+            Some((*tag, tagged))
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable references to value as tagged value, or `None`
+    pub fn as_tag_mut(&mut self) -> Option<(&mut u64, &mut Value)> {
+        if let Value::Tag(tag, tagged) = self {
+            Some((tag, tagged))
+        } else {
+            None
+        }
+    }
+
     /// Return value as text string, or `None`
     pub fn as_tstr(self) -> Option<String> {
-        if let Value::Tstr(bytes) = self {
-            Some(String::from_utf8(bytes).unwrap())
+        if let Value::Tstr(text) = self {
+            Some(text)
+        } else {
+            None
+        }
+    }
+
+    /// Return reference to value as text string, or `None`
+    pub fn as_tstr_ref(&self) -> Option<&String> {
+        if let Value::Tstr(text) = self {
+            Some(text)
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable reference to value as text string, or `None`
+    pub fn as_tstr_mut(&mut self) -> Option<&mut String> {
+        if let Value::Tstr(text) = self {
+            Some(text)
+        } else {
+            None
+        }
+    }
+
+    /// Return value as string slice, or `None`
+    pub fn as_str(&self) -> Option<&str> {
+        if let Value::Tstr(text) = self {
+            Some(text)
         } else {
             None
         }
@@ -136,55 +309,302 @@ impl Value {
             None
         }
     }
-}
 
-impl std::fmt::Display for Value {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// Return reference to value as unsigned integer, or `None`
+    pub fn as_uint_ref(&self) -> Option<&u64> {
+        if let Value::Uint(number) = self {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    /// Return mutable reference to value as unsigned integer, or `None`
+    pub fn as_uint_mut(&mut self) -> Option<&mut u64> {
+        if let Value::Uint(number) = self {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the value is an array.
+    pub fn is_array(&self) -> bool {
+        matches!(self, Value::Array(_))
+    }
+
+    /// Returns `true` if the value is a byte string.
+    pub fn is_bstr(&self) -> bool {
+        matches!(self, Value::Bstr(_))
+    }
+
+    /// Returns `true` if the value is a floating-point number.
+    pub fn is_float(&self) -> bool {
+        matches!(self, Value::Float(..))
+    }
+
+    /// Returns `true` if the value is a map.
+    pub fn is_map(&self) -> bool {
+        matches!(self, Value::Map(_))
+    }
+
+    /// Returns `true` if the value is a negative integer.
+    pub fn is_nint(&self) -> bool {
+        matches!(self, Value::Nint(_))
+    }
+
+    /// Returns `true` if the value is a simple value.
+    pub fn is_simple(&self) -> bool {
+        matches!(self, Value::Simple(_))
+    }
+
+    /// Returns `true` if the value is a tagged value.
+    pub fn is_tag(&self) -> bool {
+        matches!(self, Value::Tag(..))
+    }
+
+    /// Returns `true` if the value is a text string.
+    pub fn is_tstr(&self) -> bool {
+        matches!(self, Value::Tstr(_))
+    }
+
+    /// Returns `true` if the value is an unsigned integer.
+    pub fn is_uint(&self) -> bool {
+        matches!(self, Value::Uint(_))
+    }
+
+    /// Returns the name of the type of the value, e.g., `"uint"` or `"map"`.
+    pub fn type_name(&self) -> &'static str {
         match self {
-            Value::Uint(n) => write!(f, "uint({n})"),
-            Value::Nint(n) => write!(f, "nint({n})"),
-            Value::Float(n) => write!(f, "float({n})"),
-            Value::Bstr(bs) => {
-                write!(
-                    f,
-                    "bstr[{}]",
-                    bs.iter()
-                        .map(|x| format!("{:x}", x))
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
+            Value::Uint(_) => "uint",
+            Value::Nint(_) => "nint",
+            Value::Float(..) => "float",
+            Value::Bstr(_) => "bstr",
+            Value::Tstr(_) => "tstr",
+            Value::Simple(_) => "simple",
+            Value::Tag(..) => "tag",
+            Value::Array(_) => "array",
+            Value::Map(_) => "map",
+        }
+    }
+
+    /// Return integer value as `i128`
+    ///
+    /// The conversion never fails for integers, as `i128` covers the full
+    /// range of CBOR integers from -2^64 to 2^64 - 1.
+    pub fn as_i128(&self) -> Result<i128, TryFromValueError> {
+        match self {
+            Value::Uint(number) => Ok(i128::from(*number)),
+            Value::Nint(number) => Ok(-1 - i128::from(*number)),
+            _ => Err(TryFromValueError::NotInteger),
+        }
+    }
+
+    /// Return integer value as `i64`
+    pub fn as_i64(&self) -> Result<i64, TryFromValueError> {
+        let number = self.as_i128()?;
+        i64::try_from(number).map_err(|_| TryFromValueError::OutOfRange)
+    }
+
+    /// Return integer value as `u64`
+    pub fn as_u64(&self) -> Result<u64, TryFromValueError> {
+        match self {
+            Value::Uint(number) => Ok(*number),
+            Value::Nint(_) => Err(TryFromValueError::OutOfRange),
+            _ => Err(TryFromValueError::NotInteger),
+        }
+    }
+
+    /// Compares two values for equality in the CBOR data model.
+    ///
+    /// Unlike `==`, the comparison ignores the order of map entries, and
+    /// compares floating-point numbers by their numerical value regardless of
+    /// their encoded width. Integers and floating-point numbers are never
+    /// equal. Maps are equal if they have the same entries the same number of
+    /// times.
+    ///
+    /// Two values are equal in the data model if and only if their canonical
+    /// forms, with floating-point numbers widened to double precision and map
+    /// entries sorted, are equal.
+    pub fn data_model_eq(&self, other: &Value) -> bool {
+        use Value::*;
+        match (self, other) {
+            (Uint(a), Uint(b)) | (Nint(a), Nint(b)) => a == b,
+            (Float(p, a), Float(q, b)) => {
+                let (a, b) = (float_to_f64(*p, *a), float_to_f64(*q, *b));
+                a.to_bits() == b.to_bits()
             }
-            Value::Tstr(_) => write!(f, "tstr(…)"),
-            Value::Simple(n) => write!(f, "simple({n})"),
-            Value::Tag(t, v) => write!(f, "tag({t}, {v})"),
-            Value::Array(elements) => {
-                write!(
-                    f,
-                    "array({})",
-                    elements
-                        .iter()
-                        .map(|x| x.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
+            (Bstr(a), Bstr(b)) => a == b,
+            (Tstr(a), Tstr(b)) => a == b,
+            (Simple(a), Simple(b)) => a == b,
+            (Tag(a, x), Tag(b, y)) => a == b && x.data_model_eq(y),
+            (Array(a), Array(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b).all(|(x, y)| x.data_model_eq(y))
+            }
+            (Map(a), Map(b)) => {
+                a.len() == b.len() && self.canonical() == other.canonical()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the value in canonical form: floating-point numbers are
+    /// widened to double precision, and map entries are sorted.
+    ///
+    /// Canonical forms are equal if the values are equal in the data model,
+    /// so that values can be ordered and indexed by data-model equality.
+    pub(crate) fn canonical(&self) -> Value {
+        match self {
+            Value::Float(precision, bits) => {
+                let bits = float_to_f64(*precision, *bits).to_bits();
+                Value::Float(Precision::Double, bits)
+            }
+            Value::Array(items) => {
+                Value::Array(items.iter().map(Value::canonical).collect())
             }
             Value::Map(entries) => {
-                write!(
-                    f,
-                    "map({})",
-                    entries
-                        .iter()
-                        .map(entry_to_string)
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
+                let mut entries = entries
+                    .iter()
+                    .map(|(key, item)| (key.canonical(), item.canonical()))
+                    .collect::<Vec<_>>();
+                entries.sort();
+                Value::Map(entries)
+            }
+            Value::Tag(tag, item) => {
+                Value::Tag(*tag, Box::new(item.canonical()))
             }
+            _ => self.clone(),
         }
     }
 }
 
-fn entry_to_string(lv: &(Value, Value)) -> String {
-    format!("{}: {}", lv.0.to_string(), lv.1.to_string())
+impl Value {
+    /// Returns the initial byte and the argument of the encoded head.
+    ///
+    /// Arguments are encoded as short as possible, and floating-point numbers
+    /// in their given precision.
+    fn head(&self) -> (u8, u64) {
+        let (major_type, argument) = match self {
+            Value::Uint(n) => (0, *n),
+            Value::Nint(n) => (1, *n),
+            Value::Bstr(bytes) => (2, bytes.len() as u64),
+            Value::Tstr(text) => (3, text.len() as u64),
+            Value::Array(elements) => (4, elements.len() as u64),
+            Value::Map(entries) => (5, entries.len() as u64),
+            Value::Tag(tag, _) => (6, *tag),
+            Value::Simple(n) => (7, u64::from(*n)),
+            Value::Float(precision, bits) => {
+                let additional_info = match precision {
+                    Precision::Half => 25,
+                    Precision::Single => 26,
+                    Precision::Double => 27,
+                };
+                return (7 << 5 | additional_info, *bits);
+            }
+        };
+        let additional_info = match argument {
+            0..=23 => argument as u8,
+            24..=0xff => 24,
+            0x100..=0xffff => 25,
+            0x1_0000..=0xffff_ffff => 26,
+            _ => 27,
+        };
+        (major_type << 5 | additional_info, argument)
+    }
+}
+
+impl Ord for Value {
+    /// Compares the encodings of two values bytewise.
+    ///
+    /// Heads with the same initial byte have arguments of the same width, so
+    /// that the arguments compare like their bytes. Since encodings are
+    /// prefix-free, the contents compare item by item.
+    fn cmp(&self, other: &Value) -> Ordering {
+        use Value::*;
+        let contents = || match (self, other) {
+            (Bstr(a), Bstr(b)) => a.cmp(b),
+            (Tstr(a), Tstr(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (Array(a), Array(b)) => a.cmp(b),
+            (Map(a), Map(b)) => a.cmp(b),
+            (Tag(_, a), Tag(_, b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        };
+        self.head().cmp(&other.head()).then_with(contents)
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Widens the bits of a floating-point number in `precision` to double
+/// precision.
+///
+/// Bits beyond the width of `precision` are ignored.
+pub(crate) fn float_to_f64(precision: Precision, bits: u64) -> f64 {
+    match precision {
+        Precision::Half => half_to_f64(bits as u16),
+        Precision::Single => f64::from(f32::from_bits(bits as u32)),
+        Precision::Double => f64::from_bits(bits),
+    }
+}
+
+/// Converts a double-precision floating-point number to half precision, or
+/// returns `None` if the conversion would lose information.
+pub(crate) fn f64_to_half(value: f64) -> Option<u16> {
+    let bits = value.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let mantissa = ((bits >> 42) & 0x03ff) as u16;
+    let exponent = ((bits >> 52) & 0x07ff) as i32 - 1023;
+    let half = if value.is_nan() || value.is_infinite() {
+        sign | 0x7c00 | mantissa
+    } else if value == 0.0 {
+        sign
+    } else if (-14..=15).contains(&exponent) {
+        sign | ((exponent + 15) as u16) << 10 | mantissa
+    } else if (-24..-14).contains(&exponent) {
+        let subnormal = value.abs() * 2f64.powi(24);
+        if subnormal.fract() != 0.0 {
+            return None;
+        }
+        sign | subnormal as u16
+    } else {
+        return None;
+    };
+    // Any bits lost in the conversion show in the round trip.
+    (half_to_f64(half).to_bits() == bits).then_some(half)
+}
+
+/// Converts a half-precision floating-point number to double precision.
+fn half_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x03ff);
+    let magnitude = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => f64::INFINITY,
+        0x1f => {
+            // Preserve the payload of NaN
+            let payload = u64::from(bits & 0x03ff) << 42;
+            return f64::from_bits(
+                u64::from(bits & 0x8000) << 48
+                    | 0x7ff0_0000_0000_0000
+                    | payload,
+            );
+        }
+        _ => (1024.0 + mantissa) * 2f64.powi(exponent - 25),
+    };
+    sign * magnitude
+}
+
+/// Displays the value in diagnostic notation; see [Value::diagnostic()].
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.diagnostic().fmt(f)
+    }
 }
 
 impl TryFrom<token::Token> for Value {
@@ -192,8 +612,8 @@ impl TryFrom<token::Token> for Value {
 
     /// Constructs a CBOR value from a parser token.
     ///
-    /// The construction is not defined for all tokens. For those tokens an
-    /// error is returned.
+    /// The construction is not defined for all tokens, nor for text strings
+    /// that are not valid UTF-8. For those tokens an error is returned.
     fn try_from(token: token::Token) -> Result<Value, Self::Error> {
         use token::Token::*;
         match token {
@@ -204,12 +624,14 @@ impl TryFrom<token::Token> for Value {
             Nint(n) => Ok(Value::Nint(n)),
             BstrX => Ok(Value::Bstr(Vec::new())),
             Bstr(bytes) => Ok(Value::Bstr(bytes)),
-            TstrX => Ok(Value::Tstr(Vec::new())),
-            Tstr(bytes) => Ok(Value::Tstr(bytes)),
+            TstrX => Ok(Value::Tstr(String::new())),
+            Tstr(bytes) => String::from_utf8(bytes)
+                .map(Value::Tstr)
+                .map_err(|_| TryFromTokenError(token::Kind::Tstr)),
             ArrayX => Ok(Value::Array(Vec::new())),
             MapX => Ok(Value::Map(Vec::new())),
             Simple(s) => Ok(Value::Simple(s)),
-            Float(_) => todo!(),
+            Float(precision, n) => Ok(Value::Float(precision, n)),
         }
     }
 }
@@ -229,3 +651,70 @@ impl fmt::Display for TryFromTokenError {
 }
 
 impl error::Error for TryFromTokenError {}
+
+impl TryFrom<i128> for Value {
+    type Error = TryFromValueError;
+
+    /// Constructs an integer value, which is a non-negative integer for
+    /// `number >= 0`, and a negative integer otherwise.
+    ///
+    /// The construction fails for numbers outside of the range of CBOR
+    /// integers from -2^64 to 2^64 - 1.
+    fn try_from(number: i128) -> Result<Value, Self::Error> {
+        let (value, argument) = if number >= 0 {
+            (Value::Uint as fn(u64) -> Value, number)
+        } else {
+            (Value::Nint as fn(u64) -> Value, -1 - number)
+        };
+        u64::try_from(argument)
+            .map(value)
+            .map_err(|_| TryFromValueError::OutOfRange)
+    }
+}
+
+macro_rules! try_from_value {
+    ($($int:ty),*) => {
+        $(
+            impl TryFrom<Value> for $int {
+                type Error = TryFromValueError;
+
+                /// Converts an integer value, if it is in the range of the
+                /// target type.
+                fn try_from(value: Value) -> Result<$int, Self::Error> {
+                    let number = value.as_i128()?;
+                    <$int>::try_from(number)
+                        .map_err(|_| TryFromValueError::OutOfRange)
+                }
+            }
+        )*
+    };
+}
+
+try_from_value!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+/// Indicates that converting between a value and an integer failed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TryFromValueError {
+    /// The value is not an integer.
+    NotInteger,
+    /// The integer is out of the range of the target type.
+    OutOfRange,
+}
+
+impl fmt::Display for TryFromValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryFromValueError::NotInteger => {
+                write!(f, "Value is not an integer")
+            }
+            TryFromValueError::OutOfRange => write!(f, "Integer out of range"),
+        }
+    }
+}
+
+impl error::Error for TryFromValueError {}
+
+#[cfg(test)]
+mod tests;
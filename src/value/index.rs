@@ -0,0 +1,171 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::Value;
+use std::ops;
+
+/// Index into a [Value]
+///
+/// An index looks up an item of an array, or the value of a map entry by its
+/// key; see [Value::get()].
+pub trait ValueIndex {
+    /// Returns the item of `value` at this index, or `None` if there is no
+    /// such item.
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+}
+
+/// Text key of a map entry
+impl ValueIndex for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        entry(value, |key| key.as_str() == Some(self))
+    }
+}
+
+/// Text key of a map entry
+impl ValueIndex for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(value)
+    }
+}
+
+/// Index of an array item, or unsigned integer key of a map entry
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match value {
+            Value::Array(elements) => elements.get(*self),
+            _ => {
+                let index = Value::Uint(u64::try_from(*self).ok()?);
+                entry(value, |key| *key == index)
+            }
+        }
+    }
+}
+
+/// Key of a map entry, compared by [data-model equality](Value::data_model_eq)
+impl ValueIndex for Value {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        entry(value, |key| key.data_model_eq(self))
+    }
+}
+
+impl<T: ValueIndex + ?Sized> ValueIndex for &T {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+}
+
+/// Returns the value of the first entry of `map` with a matching key.
+fn entry(map: &Value, matches: impl Fn(&Value) -> bool) -> Option<&Value> {
+    let entries = map.as_map_ref()?;
+    entries
+        .iter()
+        .find(|(key, _)| matches(key))
+        .map(|(_, value)| value)
+}
+
+impl Value {
+    /// Returns the item at `index`, or `None` if there is no such item.
+    ///
+    /// The index is one of
+    /// - a text string, which looks up the map entry with that text key; or
+    /// - a `usize`, which looks up the array item at that index, or the map
+    ///   entry with that unsigned integer key; or
+    /// - a [Value], which looks up the map entry with that key.
+    ///
+    /// Maps are searched linearly, and the first matching entry wins.
+    ///
+    /// # Example
+    ///
+    ///     use cbor::value::Value;
+    ///
+    ///     let value = Value::Map(vec![
+    ///         (Value::Tstr("temp".into()), Value::Uint(21)),
+    ///         (Value::Nint(0), Value::Array(vec![Value::Uint(7)])),
+    ///     ]);
+    ///     assert_eq!(Some(&Value::Uint(21)), value.get("temp"));
+    ///     let item = value.get(Value::Nint(0)).and_then(|item| item.get(0));
+    ///     assert_eq!(Some(&Value::Uint(7)), item);
+    ///     assert_eq!(None, value.get("pressure"));
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Looks up a nested item by a pointer such as `/payload/0/temp`.
+    ///
+    /// The pointer is a sequence of reference tokens, each prefixed by `/`,
+    /// after [RFC 6901]: `~1` stands for `/`, and `~0` for `~`. The empty
+    /// pointer refers to the value itself. A token refers to the array item
+    /// at the index it denotes, or to the map entry with the text key it
+    /// denotes, or else to the map entry with the integer key it denotes.
+    ///
+    /// [RFC 6901]: https://www.rfc-editor.org/info/rfc6901
+    ///
+    /// # Example
+    ///
+    ///     use cbor::value::Value;
+    ///
+    ///     // {"payload": [{"temp": 21}], -1: 2}
+    ///     let value = Value::Map(vec![
+    ///         (
+    ///             Value::Tstr("payload".into()),
+    ///             Value::Array(vec![Value::Map(vec![(
+    ///                 Value::Tstr("temp".into()),
+    ///                 Value::Uint(21),
+    ///             )])]),
+    ///         ),
+    ///         (Value::Nint(0), Value::Uint(2)),
+    ///     ]);
+    ///     let temp = value.pointer("/payload/0/temp");
+    ///     assert_eq!(Some(&Value::Uint(21)), temp);
+    ///     assert_eq!(Some(&Value::Uint(2)), value.pointer("/-1"));
+    ///     assert_eq!(None, value.pointer("payload"));
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        let mut tokens = pointer.strip_prefix('/')?.split('/');
+        tokens.try_fold(self, |value, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match value {
+                Value::Array(elements) => {
+                    elements.get(token.parse::<usize>().ok()?)
+                }
+                _ => value.get(&token).or_else(|| {
+                    let key = token.parse::<i128>().ok()?;
+                    value.get(Value::try_from(key).ok()?)
+                }),
+            }
+        })
+    }
+}
+
+impl<I: ValueIndex> ops::Index<I> for Value {
+    type Output = Value;
+
+    /// Returns the item at `index`; see [Value::get()].
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no item at `index`.
+    fn index(&self, index: I) -> &Value {
+        let type_name = self.type_name();
+        self.get(index)
+            .unwrap_or_else(|| panic!("No such item in {type_name}"))
+    }
+}
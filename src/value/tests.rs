@@ -0,0 +1,377 @@
+// -*- mode: rust; coding: utf-8-unix; -*-
+/*
+cbor: Utilities for decoding Concise Binary Object Notation
+Copyright (C) 2025 GLVI Gesellschaft für Luftverkehrsinformatik mbH.
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or (at
+your option) any later version.
+
+This program is distributed in the hope that it will be useful, but
+WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+
+#[test]
+fn data_model_eq_float_width() {
+    // 1.0 in half, single, and double precision
+    let half = Value::Float(Precision::Half, 0x3c00);
+    let single = Value::Float(Precision::Single, 0x3f80_0000);
+    let double = Value::Float(Precision::Double, 0x3ff0_0000_0000_0000);
+    assert!(half.data_model_eq(&single));
+    assert!(single.data_model_eq(&double));
+    assert!(double.data_model_eq(&half));
+    assert_ne!(half, single);
+}
+
+#[test]
+fn data_model_eq_float_special() {
+    // Negative zero, subnormal, infinity, and NaN in half and double precision
+    let pairs = [
+        (0x8000, 0x8000_0000_0000_0000),
+        (0x0001, 0x3e70_0000_0000_0000),
+        (0x7c00, 0x7ff0_0000_0000_0000),
+        (0x7e00, 0x7ff8_0000_0000_0000),
+    ];
+    for (half, double) in pairs {
+        let half = Value::Float(Precision::Half, half);
+        assert!(half.data_model_eq(&Value::Float(Precision::Double, double)));
+    }
+    let zero = Value::Float(Precision::Double, 0);
+    let negative_zero = Value::Float(Precision::Double, 0x8000_0000_0000_0000);
+    assert!(!zero.data_model_eq(&negative_zero));
+}
+
+#[test]
+fn data_model_eq_float_subnormal() {
+    // The smallest subnormal number in each precision has the bits 1.
+    let half = Value::Float(Precision::Half, 1);
+    let single = Value::Float(Precision::Single, 1);
+    let double = Value::Float(Precision::Double, 1);
+    assert!(half.data_model_eq(&Value::Float(Precision::Double, 0x3e70 << 48)));
+    assert!(single.data_model_eq(&Value::Float(Precision::Double, 0x36a << 52)));
+    assert!(!half.data_model_eq(&single));
+    assert!(!single.data_model_eq(&double));
+    assert!(half < single && single < double);
+}
+
+#[test]
+fn data_model_eq_int_float() {
+    assert!(!Value::Uint(0).data_model_eq(&Value::Float(Precision::Half, 0)));
+    assert!(!Value::Uint(0).data_model_eq(&Value::Nint(0)));
+    assert!(!Value::Bstr(vec![]).data_model_eq(&Value::Tstr(String::new())));
+}
+
+#[test]
+fn data_model_eq_map_order() {
+    let a = Value::Map(vec![
+        (Value::Uint(1), Value::Float(Precision::Half, 0x3c00)),
+        (Value::Uint(2), Value::Array(vec![Value::Uint(3)])),
+    ]);
+    let b = Value::Map(vec![
+        (Value::Uint(2), Value::Array(vec![Value::Uint(3)])),
+        (Value::Uint(1), Value::Float(Precision::Double, 0x3ff0 << 48)),
+    ]);
+    assert!(a.data_model_eq(&b));
+    let c = Value::Map(vec![(Value::Uint(1), Value::Uint(1))]);
+    assert!(!a.data_model_eq(&c));
+}
+
+#[test]
+fn data_model_eq_map_duplicates() {
+    let entry = |key, item| (Value::Uint(key), Value::Uint(item));
+    let a = Value::Map(vec![entry(1, 1), entry(1, 1)]);
+    let b = Value::Map(vec![entry(1, 1), entry(2, 2)]);
+    assert!(!a.data_model_eq(&b));
+    assert!(!b.data_model_eq(&a));
+    let c = Value::Map(vec![entry(2, 2), entry(1, 1), entry(1, 1)]);
+    let d = Value::Map(vec![entry(1, 1), entry(2, 2), entry(2, 2)]);
+    assert!(!c.data_model_eq(&d));
+    assert!(!d.data_model_eq(&c));
+    let e = Value::Map(vec![entry(1, 1), entry(2, 2), entry(1, 1)]);
+    assert!(c.data_model_eq(&e));
+    assert!(e.data_model_eq(&c));
+}
+
+#[test]
+fn data_model_eq_tag() {
+    let a = Value::Tag(1, Box::new(Value::Float(Precision::Half, 0x3c00)));
+    let single = Value::Float(Precision::Single, 0x3f80_0000);
+    let b = Value::Tag(1, Box::new(single));
+    let c = Value::Tag(2, Box::new(Value::Float(Precision::Half, 0x3c00)));
+    assert!(a.data_model_eq(&b));
+    assert!(!a.data_model_eq(&c));
+}
+
+#[test]
+fn f64_to_half_exact() {
+    let exact = [
+        (1.0, 0x3c00),
+        (-2.0, 0xc000),
+        (65504.0, 0x7bff),
+        (5.960464477539063e-8, 0x0001),
+        (0.00006103515625, 0x0400),
+        (-0.0, 0x8000),
+        (f64::INFINITY, 0x7c00),
+        (f64::NAN, 0x7e00),
+    ];
+    for (value, half) in exact {
+        assert_eq!(Some(half), f64_to_half(value), "{value}");
+    }
+    for value in [0.1, 65520.0, 1.0e-8, 100000.0] {
+        assert_eq!(None, f64_to_half(value), "{value}");
+    }
+}
+
+#[test]
+fn integers() {
+    let min = Value::Nint(u64::MAX);
+    assert_eq!(Ok(-(1i128 << 64)), min.as_i128());
+    assert_eq!(Err(TryFromValueError::OutOfRange), min.as_i64());
+    assert_eq!(Ok(-1), Value::Nint(0).as_i64());
+    assert_eq!(Ok(i64::MIN), Value::Nint(i64::MAX as u64).as_i64());
+    let max = Value::Uint(u64::MAX);
+    assert_eq!(Ok(u64::MAX), max.as_u64());
+    assert_eq!(Err(TryFromValueError::OutOfRange), max.as_i64());
+    assert_eq!(Err(TryFromValueError::OutOfRange), Value::Nint(0).as_u64());
+    let text = Value::Tstr("1".into());
+    assert_eq!(Err(TryFromValueError::NotInteger), text.as_i128());
+    assert_eq!(Err(TryFromValueError::NotInteger), text.as_u64());
+
+    assert_eq!(Ok(-1i8), i8::try_from(Value::Nint(0)));
+    assert_eq!(Ok(255u8), u8::try_from(Value::Uint(255)));
+    let error = TryFromValueError::OutOfRange;
+    assert_eq!(Err(error), u8::try_from(Value::Uint(256)));
+    assert_eq!(Err(error), u32::try_from(Value::Nint(0)));
+    assert_eq!(Err(error), i8::try_from(Value::Nint(128)));
+    assert_eq!(Ok(-(1i128 << 64)), i128::try_from(min));
+    assert_eq!(Ok(u128::from(u64::MAX)), u128::try_from(max));
+    let error = TryFromValueError::NotInteger;
+    assert_eq!(Err(error), usize::try_from(Value::Float(Precision::Half, 0)));
+}
+
+#[test]
+fn integers_from_i128() {
+    assert_eq!(Ok(Value::Uint(0)), Value::try_from(0i128));
+    assert_eq!(Ok(Value::Nint(0)), Value::try_from(-1i128));
+    let max = i128::from(u64::MAX);
+    assert_eq!(Ok(Value::Uint(u64::MAX)), Value::try_from(max));
+    assert_eq!(Ok(Value::Nint(u64::MAX)), Value::try_from(-1 - max));
+    let error = Err(TryFromValueError::OutOfRange);
+    assert_eq!(error, Value::try_from(max + 1));
+    assert_eq!(error, Value::try_from(-2 - max));
+    for number in [-300i128, -1, 0, 23, 1 << 40] {
+        let value = Value::try_from(number).unwrap();
+        assert_eq!(Ok(number), value.as_i128());
+    }
+}
+
+#[test]
+fn accessors_ref_mut() {
+    let mut value = Value::Map(vec![(
+        Value::Tstr("a".into()),
+        Value::Tag(1, Box::new(Value::Array(vec![Value::Uint(0)]))),
+    )]);
+    let (key, item) = &value.as_map_ref().unwrap()[0];
+    assert_eq!(Some("a"), key.as_str());
+    assert_eq!(None, key.as_bstr_ref());
+    let (tag, tagged) = item.as_tag_ref().unwrap();
+    assert_eq!(1, tag);
+    assert_eq!(Some(&0), tagged.as_array_ref().unwrap()[0].as_uint_ref());
+
+    let (key, item) = &mut value.as_map_mut().unwrap()[0];
+    key.as_tstr_mut().unwrap().push('b');
+    let (tag, tagged) = item.as_tag_mut().unwrap();
+    *tag = 2;
+    let elements = tagged.as_array_mut().unwrap();
+    *elements[0].as_uint_mut().unwrap() = 1;
+    elements.push(Value::Nint(0));
+    *elements[1].as_nint_mut().unwrap() += 1;
+    assert_eq!(None, elements[1].as_uint_mut());
+
+    let expected = Value::Map(vec![(
+        Value::Tstr("ab".into()),
+        Value::Tag(
+            2,
+            Box::new(Value::Array(vec![Value::Uint(1), Value::Nint(1)])),
+        ),
+    )]);
+    assert_eq!(expected, value);
+
+    let mut value = Value::Bstr(vec![0]);
+    value.as_bstr_mut().unwrap().push(1);
+    assert_eq!(Some(&vec![0, 1]), value.as_bstr_ref());
+    let mut value = Value::Simple(20);
+    *value.as_simple_mut().unwrap() = 21;
+    assert_eq!(Some(&21), value.as_simple_ref());
+    let mut value = Value::Float(Precision::Half, 0x3c00);
+    *value.as_float_mut().unwrap().1 = 0x3e00;
+    assert_eq!(Some((Precision::Half, &0x3e00)), value.as_float_ref());
+    assert_eq!(Some((Precision::Half, 0x3e00)), value.as_float());
+}
+
+#[test]
+fn predicates() {
+    let values = [
+        Value::Uint(0),
+        Value::Nint(0),
+        Value::Float(Precision::Half, 0),
+        Value::Bstr(Vec::new()),
+        Value::Tstr(String::new()),
+        Value::Simple(0),
+        Value::Tag(0, Box::new(Value::Uint(0))),
+        Value::Array(Vec::new()),
+        Value::Map(Vec::new()),
+    ];
+    let predicates: [fn(&Value) -> bool; 9] = [
+        Value::is_uint,
+        Value::is_nint,
+        Value::is_float,
+        Value::is_bstr,
+        Value::is_tstr,
+        Value::is_simple,
+        Value::is_tag,
+        Value::is_array,
+        Value::is_map,
+    ];
+    for (i, value) in values.iter().enumerate() {
+        for (j, predicate) in predicates.iter().enumerate() {
+            assert_eq!(i == j, predicate(value), "{value}");
+        }
+        assert_eq!(value.diagnostic().to_string(), value.to_string());
+    }
+    let type_names = values.iter().map(Value::type_name).collect::<Vec<_>>();
+    let expected = [
+        "uint", "nint", "float", "bstr", "tstr", "simple", "tag", "array",
+        "map",
+    ];
+    assert_eq!(expected, type_names.as_slice());
+}
+
+fn message() -> Value {
+    // {"payload": [{"temp": 21, "a/b": 1, "c~d": 2}], 1: "one", -1: h'00'}
+    let text = |text: &str| Value::Tstr(text.into());
+    let reading = Value::Map(vec![
+        (text("temp"), Value::Uint(21)),
+        (text("a/b"), Value::Uint(1)),
+        (text("c~d"), Value::Uint(2)),
+    ]);
+    Value::Map(vec![
+        (text("payload"), Value::Array(vec![reading])),
+        (Value::Uint(1), text("one")),
+        (Value::Nint(0), Value::Bstr(vec![0])),
+    ])
+}
+
+#[test]
+fn get() {
+    let value = message();
+    let payload = value.get("payload").unwrap();
+    assert!(payload.is_array());
+    assert_eq!(Some(payload), value.get(String::from("payload")));
+    assert_eq!(Some(&Value::Tstr("one".into())), value.get(1));
+    assert_eq!(Some(&Value::Bstr(vec![0])), value.get(Value::Nint(0)));
+    assert_eq!(Some(&Value::Uint(21)), payload.get(0).unwrap().get("temp"));
+    assert_eq!(None, payload.get(1));
+    assert_eq!(None, payload.get("temp"));
+    assert_eq!(None, value.get(2));
+    assert_eq!(None, Value::Uint(0).get(0));
+}
+
+#[test]
+fn index() {
+    let value = message();
+    assert_eq!(Value::Uint(21), value["payload"][0]["temp"]);
+    assert_eq!(Value::Tstr("one".into()), value[1]);
+    assert_eq!(Value::Bstr(vec![0]), value[Value::Nint(0)]);
+}
+
+#[test]
+fn index_map_key() {
+    let entry = |key, item| (Value::Uint(key), Value::Uint(item));
+    let twice = Value::Map(vec![entry(1, 1), entry(1, 1)]);
+    let other = Value::Map(vec![entry(1, 1), entry(2, 2)]);
+    let value = Value::Map(vec![
+        (twice.clone(), Value::Uint(1)),
+        (other.clone(), Value::Uint(2)),
+    ]);
+    assert_eq!(Some(&Value::Uint(1)), value.get(&twice));
+    assert_eq!(Some(&Value::Uint(2)), value.get(&other));
+}
+
+#[test]
+#[should_panic(expected = "No such item in map")]
+fn index_missing() {
+    let _ = &message()["missing"];
+}
+
+#[test]
+fn pointer() {
+    let value = message();
+    assert_eq!(Some(&value), value.pointer(""));
+    assert_eq!(Some(&Value::Uint(21)), value.pointer("/payload/0/temp"));
+    assert_eq!(Some(&Value::Uint(1)), value.pointer("/payload/0/a~1b"));
+    assert_eq!(Some(&Value::Uint(2)), value.pointer("/payload/0/c~0d"));
+    assert_eq!(Some(&Value::Tstr("one".into())), value.pointer("/1"));
+    assert_eq!(Some(&Value::Bstr(vec![0])), value.pointer("/-1"));
+    assert_eq!(None, value.pointer("/payload/1"));
+    assert_eq!(None, value.pointer("/payload/x"));
+    assert_eq!(None, value.pointer("/2"));
+    assert_eq!(None, value.pointer("payload"));
+    assert_eq!(None, value.pointer("/payload/0/temp/0"));
+}
+
+#[test]
+fn order_deterministic() {
+    // Example of RFC 8949, section 4.2.1, extended by further types
+    let sorted = [
+        Value::Uint(10),
+        Value::Uint(100),
+        Value::Uint(1000),
+        Value::Nint(0),
+        Value::Bstr(vec![0xff]),
+        Value::Tstr("z".into()),
+        Value::Tstr("aa".into()),
+        Value::Array(vec![Value::Uint(100)]),
+        Value::Array(vec![Value::Nint(0)]),
+        Value::Array(vec![Value::Nint(0), Value::Uint(0)]),
+        Value::Map(vec![(Value::Uint(0), Value::Uint(0))]),
+        Value::Tag(1, Box::new(Value::Uint(0))),
+        Value::Simple(20),
+        Value::Simple(255),
+        Value::Float(Precision::Half, 0x3c00),
+        Value::Float(Precision::Single, 0x3f80_0001),
+        Value::Float(Precision::Double, 0x3ff0_0000_0000_0001),
+    ];
+    let mut values = sorted.to_vec();
+    values.reverse();
+    values.sort();
+    assert_eq!(sorted.to_vec(), values);
+}
+
+#[test]
+fn eq_hash_float_bits() {
+    use std::collections::{BTreeSet, HashSet};
+    let nan = Value::Float(Precision::Half, 0x7e00);
+    assert_eq!(nan, nan.clone());
+    // Negative zero, and 1.0 in half and single precision
+    let floats = [
+        Value::Float(Precision::Half, 0x0000),
+        Value::Float(Precision::Half, 0x8000),
+        Value::Float(Precision::Half, 0x3c00),
+        Value::Float(Precision::Single, 0x3f80_0000),
+        nan.clone(),
+        nan,
+    ];
+    assert_eq!(5, floats.iter().collect::<HashSet<_>>().len());
+    assert_eq!(5, floats.iter().collect::<BTreeSet<_>>().len());
+    assert_ne!(floats[2], floats[3]);
+    assert!(floats[2].data_model_eq(&floats[3]));
+}
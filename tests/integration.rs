@@ -21,34 +21,38 @@ use cbor::parser::{self, *};
 use cbor::scanner::{self, *};
 use cbor::value::*;
 
+/// Parser that produces values
+trait ValueParser: Parser<Output = Value> {}
+
+impl<P: Parser<Output = Value>> ValueParser for P {}
+
 const UNEXPECTED_EOF: Result<Value, parser::Error> =
     Err(parser::Error::Scanner(scanner::error::Error::UnexpectedEof));
 
 fn decode<const N: usize>(
     scanner: &mut Scanner,
-    parser: &mut impl Parser,
+    parser: &mut impl ValueParser,
     &bytes: &[u8; N],
 ) -> Result<Value, parser::Error> {
     for byte in bytes {
         if let Some(token) =
             scanner.consume(byte).map_err(parser::Error::Scanner)?
+            && let Some(value) = parser.consume(token)?
         {
-            if let Some(value) = parser.consume(token)? {
-                return Ok(value);
-            }
+            return Ok(value);
         }
     }
     UNEXPECTED_EOF
 }
 
-fn decode_uint_small(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_uint_small(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let byte: u8 = rand::random_range(0x00..0x18);
     let token = scanner.consume(byte).unwrap().unwrap();
     let value = parser.consume(token).unwrap().unwrap();
     assert_eq!(Value::Uint(byte.into()), value)
 }
 
-fn decode_nint_small(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_nint_small(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let val: u8 = rand::random_range(0x00..0x18);
     let byte = 0x20 | val;
     let token = scanner.consume(byte).unwrap().unwrap();
@@ -56,7 +60,7 @@ fn decode_nint_small(scanner: &mut Scanner, parser: &mut impl Parser) {
     assert_eq!(Value::Nint(val.into()), value)
 }
 
-fn decode_tag_small(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_tag_small(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let tag: u8 = rand::random_range(0x00..0x18);
     let val: u8 = rand::random_range(0x00..0x18);
     let bytes: [u8; 2] = [0xc0 | tag, val];
@@ -67,7 +71,7 @@ fn decode_tag_small(scanner: &mut Scanner, parser: &mut impl Parser) {
     );
 }
 
-fn decode_tag_u8(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_tag_u8(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let tag: u8 = rand::random_range(0x18..0xff);
     let val: u8 = rand::random_range(0x00..0x18);
     let bytes: [u8; 3] = [0xd8, tag, val];
@@ -78,7 +82,7 @@ fn decode_tag_u8(scanner: &mut Scanner, parser: &mut impl Parser) {
     );
 }
 
-fn decode_tag_recursive(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_tag_recursive(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let tag1: u8 = rand::random_range(0x00..0x18);
     let tag2: u8 = rand::random_range(0x00..0x18);
     let tag3: u8 = rand::random_range(0x00..0x18);
@@ -100,20 +104,20 @@ fn decode_tag_recursive(scanner: &mut Scanner, parser: &mut impl Parser) {
     );
 }
 
-fn decode_array0(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_array0(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let array: u8 = 0x80;
     let bytes: [u8; 1] = [array];
     let result = decode(scanner, parser, &bytes).unwrap();
     assert_eq!(Value::Array(vec![]), result);
 }
 
-fn decode_array1(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_array1(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let bytes: [u8; 2] = [0x81, 0x80];
     let result = decode(scanner, parser, &bytes).unwrap();
     assert_eq!(Value::Array(vec![Value::Array(vec![])]), result);
 }
 
-fn decode_array2(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_array2(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let bytes: [u8; 5] = [0x82, 0x81, 0x00, 0x81, 0x01];
     let result = decode(scanner, parser, &bytes).unwrap();
     assert_eq!(Value::Array(vec![
@@ -122,7 +126,7 @@ fn decode_array2(scanner: &mut Scanner, parser: &mut impl Parser) {
     ]), result);
 }
 
-fn decode_arrayx_empty(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_arrayx_empty(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let arrayx: u8 = 0x9f;
     let r#break: u8 = 0xff;
     let bytes: [u8; 2] = [arrayx, r#break];
@@ -130,7 +134,7 @@ fn decode_arrayx_empty(scanner: &mut Scanner, parser: &mut impl Parser) {
     assert_eq!(Value::Array(vec![]), result);
 }
 
-fn decode_arrayx(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_arrayx(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let arrayx: u8 = 0x9f;
     let val1: u8 = rand::random_range(0x00..0x18);
     let val2: u8 = rand::random_range(0x00..0x18);
@@ -148,7 +152,10 @@ fn decode_arrayx(scanner: &mut Scanner, parser: &mut impl Parser) {
     );
 }
 
-fn decode_arrayx_recursive(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_arrayx_recursive(
+    scanner: &mut Scanner,
+    parser: &mut impl ValueParser,
+) {
     let arrayx: u8 = 0x9f;
     let r#break: u8 = 0xff;
     let bytes: [u8; 6] = [arrayx, arrayx, arrayx, r#break, r#break, r#break];
@@ -159,7 +166,7 @@ fn decode_arrayx_recursive(scanner: &mut Scanner, parser: &mut impl Parser) {
     );
 }
 
-fn decode_bstrx_empty(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_bstrx_empty(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let bstrx: u8 = 0x5f;
     let r#break: u8 = 0xff;
     let bytes: [u8; 2] = [bstrx, r#break];
@@ -167,7 +174,7 @@ fn decode_bstrx_empty(scanner: &mut Scanner, parser: &mut impl Parser) {
     assert_eq!(Value::Bstr(vec![]), result);
 }
 
-fn decode_bstrx(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_bstrx(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let bstrx: u8 = 0x5f;
     let r#break: u8 = 0xff;
     let bytes: [u8; 8] = [bstrx, 0x42, 1, 2, 0x42, 3, 4, r#break];
@@ -175,20 +182,20 @@ fn decode_bstrx(scanner: &mut Scanner, parser: &mut impl Parser) {
     assert_eq!(Value::Bstr(vec![1,2,3,4]), result);
 }
 
-fn decode_tstrx_empty(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_tstrx_empty(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let tstrx: u8 = 0x7f;
     let r#break: u8 = 0xff;
     let bytes: [u8; 2] = [tstrx, r#break];
     let result = decode(scanner, parser, &bytes).unwrap();
-    assert_eq!(Value::Tstr(vec![]), result);
+    assert_eq!(Value::Tstr(String::new()), result);
 }
 
-fn decode_tstrx(scanner: &mut Scanner, parser: &mut impl Parser) {
+fn decode_tstrx(scanner: &mut Scanner, parser: &mut impl ValueParser) {
     let tstrx: u8 = 0x7f;
     let r#break: u8 = 0xff;
     let bytes: [u8; 8] = [tstrx, 0x62, 0x41, 0x42, 0x62, 0x43, 0x44, r#break];
     let result = decode(scanner, parser, &bytes).unwrap();
-    assert_eq!(Value::Tstr(vec![0x41, 0x42, 0x43, 0x44]), result);
+    assert_eq!(Value::Tstr("ABCD".into()), result);
 }
 
 mod ll {